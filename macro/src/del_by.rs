@@ -0,0 +1,44 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::DeriveInput;
+
+use crate::model::Model;
+
+pub fn derive(input: DeriveInput) -> syn::Result<TokenStream> {
+  let model = Model::from_input(&input)?;
+  Ok(impl_del_by(&input.ident, &model))
+}
+
+/// The `DbModelDelBy` impl, shared with `TurboModel`.
+pub fn impl_del_by(ident: &syn::Ident, model: &Model) -> TokenStream {
+  let table_name = &model.table_name;
+  quote! {
+    impl ::turbo_diesel::prelude::DbModelDelBy for #ident {
+      fn gen_del_query<__D>(
+        filter: &::turbo_diesel::prelude::GenericFilter,
+      ) -> ::std::result::Result<
+        ::diesel::query_builder::BoxedDeleteStatement<
+          'static,
+          <__D as ::diesel::Connection>::Backend,
+          <Self as ::diesel::associations::HasTable>::Table,
+        >,
+        ::diesel::result::Error,
+      >
+      where
+        __D: ::diesel::r2d2::R2D2Connection
+          + ::diesel::Connection
+          + ::diesel::connection::LoadConnection
+          + 'static,
+        Self: ::diesel::associations::HasTable
+          + ::turbo_diesel::prelude::FilterableColumns<
+            <__D as ::diesel::Connection>::Backend,
+          >,
+        <__D as ::diesel::Connection>::Backend:
+          ::diesel::internal::derives::multiconnection::DieselReserveSpecialization,
+      {
+        let query = ::diesel::delete(#table_name::table).into_boxed();
+        ::turbo_diesel::prelude::apply_where::<Self, _, _>(query, filter)
+      }
+    }
+  }
+}
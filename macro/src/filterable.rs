@@ -0,0 +1,38 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::DeriveInput;
+
+use crate::model::Model;
+
+pub fn derive(input: DeriveInput) -> syn::Result<TokenStream> {
+  let model = Model::from_input(&input)?;
+  let ident = &input.ident;
+  let table_name = &model.table_name;
+  let columns = &model.columns;
+  let names = columns.iter().map(|c| c.to_string()).collect::<Vec<_>>();
+  Ok(quote! {
+    impl<__DB> ::turbo_diesel::prelude::FilterableColumns<__DB> for #ident
+    where
+      __DB: ::diesel::backend::Backend,
+      #(
+        #table_name::#columns:
+          ::turbo_diesel::prelude::FilterColumn<#table_name::table, __DB>,
+      )*
+    {
+      const COLUMNS: &'static [&'static str] = &[#(#names),*];
+
+      fn visit_column<__V>(
+        column: &str,
+        visitor: __V,
+      ) -> ::std::result::Result<__V::Output, ::diesel::result::Error>
+      where
+        __V: ::turbo_diesel::prelude::ColumnVisitor<#table_name::table, __DB>,
+      {
+        match column {
+          #(#names => visitor.visit(#table_name::#columns),)*
+          _ => Err(::turbo_diesel::prelude::unknown_column(column)),
+        }
+      }
+    }
+  })
+}
@@ -0,0 +1,197 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{spanned::Spanned, Data, DeriveInput, Fields};
+
+/// Names of the clauses of `#[filter(op = "...")]`, in kebab-case
+/// like the serde names of `GenericClause`.
+const OPS: &[&str] = &[
+  "eq",
+  "ne",
+  "gt",
+  "lt",
+  "ge",
+  "le",
+  "like",
+  "not-like",
+  "i-like",
+  "starts-with",
+  "ends-with",
+  "contains-text",
+  "between",
+  "not-between",
+  "in",
+  "not-in",
+  "is-null",
+  "json-contains",
+  "has-key",
+  "array-contains",
+  "array-overlaps",
+  "full-text",
+];
+
+pub fn derive(input: DeriveInput) -> syn::Result<TokenStream> {
+  let fields = match &input.data {
+    Data::Struct(data) => match &data.fields {
+      Fields::Named(fields) => &fields.named,
+      _ => {
+        return Err(syn::Error::new(
+          input.span(),
+          "only structs with named fields are supported",
+        ))
+      }
+    },
+    _ => {
+      return Err(syn::Error::new(input.span(), "only structs are supported"))
+    }
+  };
+  let mut clauses = Vec::new();
+  for field in fields {
+    let Some(options) = parse_options(field)? else {
+      continue;
+    };
+    let ident = field.ident.as_ref().unwrap();
+    let column = options.column.unwrap_or_else(|| ident.to_string());
+    let clause = clause(&options.op);
+    let apply = quote! {
+      filter = filter.r#where(#column, #clause);
+    };
+    clauses.push(if is_option(&field.ty) {
+      quote! {
+        if let ::std::option::Option::Some(value) = self.#ident {
+          #apply
+        }
+      }
+    } else {
+      quote! {
+        let value = self.#ident;
+        #apply
+      }
+    });
+  }
+  let ident = &input.ident;
+  let (impl_generics, ty_generics, where_clause) =
+    input.generics.split_for_impl();
+  Ok(quote! {
+    impl #impl_generics ::turbo_diesel::prelude::IntoFilter
+      for #ident #ty_generics #where_clause
+    {
+      fn into_filter(self) -> ::turbo_diesel::prelude::GenericFilter {
+        #[allow(unused_mut)]
+        let mut filter = ::turbo_diesel::prelude::GenericFilter::new();
+        #({ #clauses })*
+        filter
+      }
+    }
+  })
+}
+
+/// What `#[filter(...)]` asks for on a field.
+struct Options {
+  /// Name of the clause, `eq` by default
+  op: syn::LitStr,
+  /// Name of the column, the name of the field by default
+  column: Option<String>,
+}
+
+/// Read the options of `#[filter(...)]`, `None` for a skipped field.
+fn parse_options(field: &syn::Field) -> syn::Result<Option<Options>> {
+  let mut op = None;
+  let mut column = None;
+  let mut skip = false;
+  for attr in field.attrs.iter().filter(|a| a.path().is_ident("filter")) {
+    attr.parse_nested_meta(|meta| {
+      if meta.path.is_ident("op") {
+        let value = meta.value()?.parse::<syn::LitStr>()?;
+        if !OPS.contains(&value.value().as_str()) {
+          return Err(syn::Error::new(
+            value.span(),
+            format!(
+              "unknown op {}, expected one of {}",
+              value.value(),
+              OPS.join(", ")
+            ),
+          ));
+        }
+        op = Some(value);
+        return Ok(());
+      }
+      if meta.path.is_ident("column") {
+        column = Some(meta.value()?.parse::<syn::LitStr>()?.value());
+        return Ok(());
+      }
+      if meta.path.is_ident("skip") {
+        skip = true;
+        return Ok(());
+      }
+      Err(meta.error("expected `op = \"...\"`, `column = \"...\"` or `skip`"))
+    })?;
+  }
+  if skip {
+    return Ok(None);
+  }
+  Ok(Some(Options {
+    op: op.unwrap_or_else(|| syn::LitStr::new("eq", field.span())),
+    column,
+  }))
+}
+
+/// The clause of `op` built from the field value, bound to `value`.
+fn clause(op: &syn::LitStr) -> TokenStream {
+  let clause = quote! { ::turbo_diesel::prelude::GenericClause };
+  let generic = quote! {
+    ::std::convert::Into::<::turbo_diesel::prelude::GenericValue>::into
+  };
+  let list = quote! {
+    ::std::iter::IntoIterator::into_iter(value).map(#generic).collect()
+  };
+  let text =
+    quote! { ::std::convert::Into::<::std::string::String>::into(value) };
+  match op.value().as_str() {
+    "eq" => quote! { #clause::Eq(#generic(value)) },
+    "ne" => quote! { #clause::Ne(#generic(value)) },
+    "gt" => quote! { #clause::Gt(#generic(value)) },
+    "lt" => quote! { #clause::Lt(#generic(value)) },
+    "ge" => quote! { #clause::Ge(#generic(value)) },
+    "le" => quote! { #clause::Le(#generic(value)) },
+    "like" => quote! { #clause::Like(#text) },
+    "not-like" => quote! { #clause::NotLike(#text) },
+    "i-like" => quote! { #clause::ILike(#text) },
+    "starts-with" => quote! { #clause::StartsWith(#text) },
+    "ends-with" => quote! { #clause::EndsWith(#text) },
+    "contains-text" => quote! { #clause::ContainsText(#text) },
+    "between" => quote! {{
+      let (lower, upper) = value;
+      #clause::Between(#generic(lower), #generic(upper))
+    }},
+    "not-between" => quote! {{
+      let (lower, upper) = value;
+      #clause::NotBetween(#generic(lower), #generic(upper))
+    }},
+    "in" => quote! { #clause::In(#list) },
+    "not-in" => quote! { #clause::NotIn(#list) },
+    "array-contains" => quote! { #clause::ArrayContains(#list) },
+    "array-overlaps" => quote! { #clause::ArrayOverlaps(#list) },
+    // `true` asks for the null rows, `false` for the others
+    "is-null" => quote! {
+      if value { #clause::IsNull } else { #clause::IsNotNull }
+    },
+    "json-contains" => quote! { #clause::JsonContains(value) },
+    "has-key" => quote! { #clause::HasKey(#text) },
+    "full-text" => {
+      quote! { #clause::FullText(#text, ::std::option::Option::None) }
+    }
+    _ => unreachable!("the op is checked by parse_options"),
+  }
+}
+
+/// The type is an `Option<...>`, whose `None` adds no clause.
+fn is_option(ty: &syn::Type) -> bool {
+  let syn::Type::Path(path) = ty else {
+    return false;
+  };
+  path
+    .path
+    .segments
+    .last()
+    .is_some_and(|segment| segment.ident == "Option")
+}
@@ -0,0 +1,94 @@
+use proc_macro::TokenStream;
+use syn::{parse_macro_input, DeriveInput};
+
+mod model;
+mod filterable;
+mod upsert;
+mod del_by;
+mod turbo_model;
+mod into_filter;
+mod primary_key;
+
+/// Implement `FilterableColumns` by mapping every field of the struct
+/// to the column of the same name in `#[diesel(table_name = ...)]`.
+#[proc_macro_derive(FilterableColumns, attributes(diesel))]
+pub fn derive_filterable_columns(input: TokenStream) -> TokenStream {
+  let input = parse_macro_input!(input as DeriveInput);
+  filterable::derive(input)
+    .unwrap_or_else(syn::Error::into_compile_error)
+    .into()
+}
+
+/// Implement `DbModelUpsert` for SQLite and Postgres,
+/// the conflict target being the primary key of the table,
+/// `create_or_ignore` skipping any conflict.
+/// `upsert_many` runs a single statement per chunk on Postgres,
+/// one per row on SQLite.
+/// The struct must also implement `Insertable` and `AsChangeset`.
+#[proc_macro_derive(DbModelUpsert, attributes(diesel))]
+pub fn derive_db_model_upsert(input: TokenStream) -> TokenStream {
+  let input = parse_macro_input!(input as DeriveInput);
+  upsert::derive(input)
+    .unwrap_or_else(syn::Error::into_compile_error)
+    .into()
+}
+
+/// Implement `DbModelDelBy` with a `gen_del_query` applying the where clause
+/// of the filter on `#[diesel(table_name = ...)]`.
+/// The struct must also implement `FilterableColumns`.
+#[proc_macro_derive(DbModelDelBy, attributes(diesel))]
+pub fn derive_db_model_del_by(input: TokenStream) -> TokenStream {
+  let input = parse_macro_input!(input as DeriveInput);
+  del_by::derive(input)
+    .unwrap_or_else(syn::Error::into_compile_error)
+    .into()
+}
+
+/// Implement every model trait of the crate: `DbModelCreate`,
+/// `DbModelReadByPk`, `DbModelRead`, `DbModelCount`, `DbModelExists`,
+/// `DbModelUpdate`, `DbModelDelByPk` and `DbModelDelBy`.
+/// Traits are left out with `#[turbo(skip(Update, DelBy))]`,
+/// skipping `Read` also skips `Count` and `Exists`.
+/// `#[turbo(soft_delete(deleted_at))]` also implements `DbModelSoftDelete`
+/// on the nullable timestamp column and skips the soft deleted rows
+/// in the read query.
+/// `#[turbo(timestamps(created_at, updated_at))]` also implements
+/// `Timestamped` on the two timestamp columns,
+/// `#[turbo(version(version))]` implements `DbModelVersioned`.
+/// The struct must also implement `FilterableColumns`.
+#[proc_macro_derive(TurboModel, attributes(diesel, turbo))]
+pub fn derive_turbo_model(input: TokenStream) -> TokenStream {
+  let input = parse_macro_input!(input as DeriveInput);
+  turbo_model::derive(input)
+    .unwrap_or_else(syn::Error::into_compile_error)
+    .into()
+}
+
+/// Implement `IntoFilter`, every field of the struct adding a where clause
+/// on the column of the same name, `eq` by default.
+/// `#[filter(op = "i-like", column = "name")]` picks the clause,
+/// in kebab-case like with serde, and the column,
+/// `#[filter(skip)]` leaves a field out.
+/// The `Option` fields only add their clause when they're `Some`.
+#[proc_macro_derive(IntoFilter, attributes(filter))]
+pub fn derive_into_filter(input: TokenStream) -> TokenStream {
+  let input = parse_macro_input!(input as DeriveInput);
+  into_filter::derive(input)
+    .unwrap_or_else(syn::Error::into_compile_error)
+    .into()
+}
+
+/// Implement `ToSql` and `FromSql` for a newtype of a primary key,
+/// `struct UserId(String)`, by the ones of the wrapped type,
+/// the SQL type being `#[diesel(sql_type = ...)]` of the derives
+/// `AsExpression` and `FromSqlRow` of diesel it goes with.
+/// The newtype is then the field of the model and the `pk` given to
+/// `read_by_pk` or `del_by_pk`, without unwrapping it at every call,
+/// and the field of the foreign keys referencing it.
+#[proc_macro_derive(PrimaryKey, attributes(diesel))]
+pub fn derive_primary_key(input: TokenStream) -> TokenStream {
+  let input = parse_macro_input!(input as DeriveInput);
+  primary_key::derive(input)
+    .unwrap_or_else(syn::Error::into_compile_error)
+    .into()
+}
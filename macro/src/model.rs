@@ -0,0 +1,92 @@
+use syn::{parenthesized, spanned::Spanned, Data, DeriveInput, Fields, Token};
+
+/// What the derives need to know about a diesel model.
+pub struct Model {
+  /// Path to the diesel table module, from `#[diesel(table_name = ...)]`
+  pub table_name: syn::Path,
+  /// Columns of the table mapped by the struct fields
+  pub columns: Vec<syn::Ident>,
+}
+
+impl Model {
+  pub fn from_input(input: &DeriveInput) -> syn::Result<Self> {
+    let fields = match &input.data {
+      Data::Struct(data) => match &data.fields {
+        Fields::Named(fields) => &fields.named,
+        _ => {
+          return Err(syn::Error::new(
+            input.span(),
+            "only structs with named fields are supported",
+          ))
+        }
+      },
+      _ => {
+        return Err(syn::Error::new(input.span(), "only structs are supported"))
+      }
+    };
+    let mut table_name = None;
+    for attr in input.attrs.iter().filter(|a| a.path().is_ident("diesel")) {
+      attr.parse_nested_meta(|meta| {
+        if meta.path.is_ident("table_name") {
+          table_name = Some(meta.value()?.parse()?);
+          return Ok(());
+        }
+        skip_meta(&meta)
+      })?;
+    }
+    let table_name = match table_name {
+      Some(table_name) => table_name,
+      None => default_table_name(&input.ident),
+    };
+    let mut columns = Vec::new();
+    for field in fields {
+      let mut column_name = None;
+      for attr in field.attrs.iter().filter(|a| a.path().is_ident("diesel")) {
+        attr.parse_nested_meta(|meta| {
+          if meta.path.is_ident("column_name") {
+            column_name = Some(meta.value()?.parse()?);
+            return Ok(());
+          }
+          skip_meta(&meta)
+        })?;
+      }
+      match column_name {
+        Some(column_name) => columns.push(column_name),
+        None => columns.push(field.ident.clone().unwrap()),
+      }
+    }
+    Ok(Self {
+      table_name,
+      columns,
+    })
+  }
+}
+
+/// Consume a diesel option we don't care about.
+pub fn skip_meta(meta: &syn::meta::ParseNestedMeta) -> syn::Result<()> {
+  if meta.input.peek(Token![=]) {
+    meta.value()?.parse::<syn::Expr>()?;
+  } else if meta.input.peek(syn::token::Paren) {
+    let content;
+    parenthesized!(content in meta.input);
+    content.parse::<proc_macro2::TokenStream>()?;
+  }
+  Ok(())
+}
+
+/// Same default as diesel: the snake cased struct name with an `s`.
+fn default_table_name(ident: &syn::Ident) -> syn::Path {
+  let mut name = String::new();
+  for (i, c) in ident.to_string().chars().enumerate() {
+    if c.is_uppercase() {
+      if i > 0 {
+        name.push('_');
+      }
+      name.extend(c.to_lowercase());
+    } else {
+      name.push(c);
+    }
+  }
+  name.push('s');
+  syn::Ident::new(&name, ident.span()).into()
+}
@@ -0,0 +1,73 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{spanned::Spanned, Data, DeriveInput, Fields};
+
+use crate::model::skip_meta;
+
+pub fn derive(input: DeriveInput) -> syn::Result<TokenStream> {
+  let inner = match &input.data {
+    Data::Struct(data) => match &data.fields {
+      Fields::Unnamed(fields) if fields.unnamed.len() == 1 => {
+        &fields.unnamed[0].ty
+      }
+      _ => {
+        return Err(syn::Error::new(
+          input.span(),
+          "only structs with a single unnamed field are supported",
+        ))
+      }
+    },
+    _ => {
+      return Err(syn::Error::new(input.span(), "only structs are supported"))
+    }
+  };
+  let mut sql_type = None;
+  for attr in input.attrs.iter().filter(|a| a.path().is_ident("diesel")) {
+    attr.parse_nested_meta(|meta| {
+      if meta.path.is_ident("sql_type") {
+        sql_type = Some(meta.value()?.parse::<syn::Type>()?);
+        return Ok(());
+      }
+      skip_meta(&meta)
+    })?;
+  }
+  let Some(sql_type) = sql_type else {
+    return Err(syn::Error::new(
+      input.span(),
+      "expected `#[diesel(sql_type = ...)]`",
+    ));
+  };
+  let ident = &input.ident;
+  Ok(quote! {
+    impl<__DB> ::diesel::serialize::ToSql<#sql_type, __DB> for #ident
+    where
+      __DB: ::diesel::backend::Backend,
+      #inner: ::diesel::serialize::ToSql<#sql_type, __DB>,
+    {
+      fn to_sql<'__b>(
+        &'__b self,
+        out: &mut ::diesel::serialize::Output<'__b, '_, __DB>,
+      ) -> ::diesel::serialize::Result {
+        <#inner as ::diesel::serialize::ToSql<#sql_type, __DB>>::to_sql(
+          &self.0,
+          out,
+        )
+      }
+    }
+
+    impl<__DB> ::diesel::deserialize::FromSql<#sql_type, __DB> for #ident
+    where
+      __DB: ::diesel::backend::Backend,
+      #inner: ::diesel::deserialize::FromSql<#sql_type, __DB>,
+    {
+      fn from_sql(
+        bytes: <__DB as ::diesel::backend::Backend>::RawValue<'_>,
+      ) -> ::diesel::deserialize::Result<Self> {
+        <#inner as ::diesel::deserialize::FromSql<#sql_type, __DB>>::from_sql(
+          bytes,
+        )
+        .map(Self)
+      }
+    }
+  })
+}
@@ -0,0 +1,201 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{parse::Parse, DeriveInput, Token};
+
+use crate::del_by::impl_del_by;
+use crate::model::Model;
+
+/// Traits implemented by `TurboModel`, by the name used in `skip(...)`.
+const TRAITS: &[&str] = &[
+  "Create", "ReadByPk", "Read", "Count", "Exists", "Update", "DelByPk", "DelBy",
+];
+
+pub fn derive(input: DeriveInput) -> syn::Result<TokenStream> {
+  let model = Model::from_input(&input)?;
+  let Options {
+    mut skip,
+    soft_delete,
+    timestamps,
+    version,
+  } = parse_options(&input)?;
+  // Counting and checking existence are built on the read query
+  if skip.iter().any(|name| name == "Read") {
+    skip.extend(["Count".to_owned(), "Exists".to_owned()]);
+  }
+  let ident = &input.ident;
+  let impls = TRAITS
+    .iter()
+    .filter(|name| !skip.iter().any(|skipped| skipped == *name))
+    .map(|name| match *name {
+      "Read" => impl_read(ident, &model, soft_delete.as_ref()),
+      "DelBy" => impl_del_by(ident, &model),
+      name => {
+        let path = syn::Ident::new(&format!("DbModel{name}"), ident.span());
+        quote! { impl ::turbo_diesel::prelude::#path for #ident {} }
+      }
+    });
+  let soft_delete = soft_delete.as_ref().map(|column| {
+    let table_name = &model.table_name;
+    quote! {
+      impl ::turbo_diesel::prelude::DbModelSoftDelete for #ident {
+        type DeletedAt = #table_name::#column;
+      }
+    }
+  });
+  let timestamps = timestamps.as_ref().map(|(created_at, updated_at)| {
+    let table_name = &model.table_name;
+    quote! {
+      impl ::turbo_diesel::prelude::Timestamped for #ident {
+        type CreatedAt = #table_name::#created_at;
+        type UpdatedAt = #table_name::#updated_at;
+      }
+    }
+  });
+  let version = version.as_ref().map(|column| {
+    let table_name = &model.table_name;
+    quote! {
+      impl ::turbo_diesel::prelude::DbModelVersioned for #ident {
+        type Version = #table_name::#column;
+      }
+    }
+  });
+  Ok(quote! { #(#impls)* #soft_delete #timestamps #version })
+}
+
+/// What `#[turbo(...)]` asks for.
+struct Options {
+  /// Trait names of `skip(...)`
+  skip: Vec<String>,
+  /// Column of `soft_delete(...)`
+  soft_delete: Option<syn::Ident>,
+  /// Created and updated columns of `timestamps(...)`
+  timestamps: Option<(syn::Ident, syn::Ident)>,
+  /// Column of `version(...)`
+  version: Option<syn::Ident>,
+}
+
+/// Read the options of `#[turbo(...)]`.
+fn parse_options(input: &DeriveInput) -> syn::Result<Options> {
+  let mut skip = Vec::new();
+  let mut soft_delete = None;
+  let mut timestamps = None;
+  let mut version = None;
+  for attr in input.attrs.iter().filter(|a| a.path().is_ident("turbo")) {
+    attr.parse_nested_meta(|meta| {
+      if meta.path.is_ident("soft_delete") {
+        let content;
+        syn::parenthesized!(content in meta.input);
+        soft_delete = Some(content.parse()?);
+        return Ok(());
+      }
+      if meta.path.is_ident("timestamps") {
+        let content;
+        syn::parenthesized!(content in meta.input);
+        let created_at = content.parse()?;
+        content.parse::<Token![,]>()?;
+        let updated_at = content.parse()?;
+        timestamps = Some((created_at, updated_at));
+        return Ok(());
+      }
+      if meta.path.is_ident("version") {
+        let content;
+        syn::parenthesized!(content in meta.input);
+        version = Some(content.parse()?);
+        return Ok(());
+      }
+      if !meta.path.is_ident("skip") {
+        return Err(meta.error(
+          "expected `skip(...)`, `soft_delete(...)`, `timestamps(...)` \
+           or `version(...)`",
+        ));
+      }
+      let content;
+      syn::parenthesized!(content in meta.input);
+      let names = content.parse_terminated(syn::Ident::parse, Token![,])?;
+      for name in names {
+        if !TRAITS.contains(&name.to_string().as_str()) {
+          return Err(syn::Error::new(
+            name.span(),
+            format!(
+              "unknown trait {name}, expected one of {}",
+              TRAITS.join(", ")
+            ),
+          ));
+        }
+        skip.push(name.to_string());
+      }
+      Ok(())
+    })?;
+  }
+  Ok(Options {
+    skip,
+    soft_delete,
+    timestamps,
+    version,
+  })
+}
+
+/// The `DbModelRead` impl, applying the filter on a boxed select.
+/// The soft deleted rows are left out unless the filter asks for them.
+fn impl_read(
+  ident: &syn::Ident,
+  model: &Model,
+  soft_delete: Option<&syn::Ident>,
+) -> TokenStream {
+  let table_name = &model.table_name;
+  let soft_delete = soft_delete.map(|column| {
+    quote! {
+      if !filter.with_deleted {
+        query = ::diesel::query_dsl::QueryDsl::filter(
+          query,
+          ::diesel::ExpressionMethods::is_null(#table_name::#column),
+        );
+      }
+    }
+  });
+  quote! {
+    impl ::turbo_diesel::prelude::DbModelRead for #ident {
+      fn gen_read_query<__D>(
+        filter: &::turbo_diesel::prelude::GenericFilter,
+      ) -> ::std::result::Result<
+        ::turbo_diesel::prelude::BoxedSelect<
+          <Self as ::diesel::associations::HasTable>::Table,
+          <__D as ::diesel::Connection>::Backend,
+        >,
+        ::diesel::result::Error,
+      >
+      where
+        __D: ::diesel::r2d2::R2D2Connection
+          + ::diesel::Connection
+          + ::diesel::connection::LoadConnection
+          + 'static,
+        Self: ::diesel::associations::HasTable
+          + ::turbo_diesel::prelude::FilterableColumns<
+            <__D as ::diesel::Connection>::Backend,
+          >,
+        <Self as ::diesel::associations::HasTable>::Table:
+          ::diesel::query_dsl::methods::BoxedDsl<
+            'static,
+            <__D as ::diesel::Connection>::Backend,
+            Output = ::turbo_diesel::prelude::BoxedSelect<
+              <Self as ::diesel::associations::HasTable>::Table,
+              <__D as ::diesel::Connection>::Backend,
+            >,
+          >,
+        <__D as ::diesel::Connection>::Backend:
+          ::diesel::internal::derives::multiconnection::DieselReserveSpecialization,
+      {
+        #[allow(unused_mut)]
+        let mut query =
+          ::diesel::query_dsl::QueryDsl::into_boxed(#table_name::table);
+        #soft_delete
+        query = ::turbo_diesel::prelude::apply_distinct_on::<Self, _, _>(
+          query,
+          <#table_name::table as ::diesel::Table>::all_columns(),
+          filter,
+        )?;
+        ::turbo_diesel::prelude::apply_where::<Self, _, _>(query, filter)
+      }
+    }
+  }
+}
@@ -0,0 +1,74 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::DeriveInput;
+
+use crate::model::Model;
+
+pub fn derive(input: DeriveInput) -> syn::Result<TokenStream> {
+  let model = Model::from_input(&input)?;
+  let ident = &input.ident;
+  let table_name = &model.table_name;
+  let columns = &model.columns;
+  // Diesel can't batch the inserts on SQLite, the rows are upserted in turn
+  let sqlite_upsert_many = quote! {
+    items
+      .into_iter()
+      .map(|item| Self::upsert_query(conn, item))
+      .collect()
+  };
+  let pg_upsert_many = quote! {
+    use ::diesel::prelude::*;
+    use ::diesel::upsert::excluded;
+    let table = #table_name::table;
+    ::diesel::insert_into(table)
+      .values(&items)
+      .on_conflict(::diesel::Table::primary_key(&table))
+      .do_update()
+      .set((#(#table_name::#columns.eq(excluded(#table_name::#columns)),)*))
+      .get_results(conn)
+  };
+  let impls = [
+    (quote! { ::diesel::SqliteConnection }, sqlite_upsert_many),
+    (quote! { ::diesel::PgConnection }, pg_upsert_many),
+  ]
+  .into_iter()
+  .map(|(connection, upsert_many)| {
+    quote! {
+      impl ::turbo_diesel::prelude::DbModelUpsert<#connection> for #ident {
+        fn upsert_query(
+          conn: &mut #connection,
+          item: Self,
+        ) -> ::std::result::Result<Self, ::diesel::result::Error> {
+          use ::diesel::prelude::*;
+          let table = #table_name::table;
+          ::diesel::insert_into(table)
+            .values(&item)
+            .on_conflict(::diesel::Table::primary_key(&table))
+            .do_update()
+            .set(&item)
+            .get_result(conn)
+        }
+
+        fn create_or_ignore_query(
+          conn: &mut #connection,
+          item: Self,
+        ) -> ::std::result::Result<Option<Self>, ::diesel::result::Error> {
+          use ::diesel::prelude::*;
+          ::diesel::insert_into(#table_name::table)
+            .values(&item)
+            .on_conflict_do_nothing()
+            .get_result(conn)
+            .optional()
+        }
+
+        fn upsert_many_query(
+          conn: &mut #connection,
+          items: Vec<Self>,
+        ) -> ::std::result::Result<Vec<Self>, ::diesel::result::Error> {
+          #upsert_many
+        }
+      }
+    }
+  });
+  Ok(quote! { #(#impls)* })
+}
@@ -0,0 +1,158 @@
+use diesel::{
+  associations::HasTable,
+  internal::derives::multiconnection::DieselReserveSpecialization,
+  query_builder,
+  query_dsl::methods::{BoxedDsl, FindDsl},
+};
+use diesel_async::{
+  pooled_connection::{
+    bb8::{Pool, PooledConnection, RunError},
+    AsyncDieselConnectionManager, PoolableConnection,
+  },
+  AsyncConnection, RunQueryDsl,
+};
+
+use crate::prelude::{
+  apply_where, FilterableColumns, GenericFilter, Result, TurboDieselError,
+};
+
+/// A Database driver running the queries with `diesel-async`.
+/// The bounds on `C` are the ones bb8 needs to check the connections.
+/// The queries are driven by the async runtime
+/// instead of the blocking thread pool of `DbDriver`.
+pub struct AsyncDbDriver<C>
+where
+  C: AsyncConnection + PoolableConnection + 'static,
+  diesel::dsl::BareSelect<
+    diesel::dsl::AsExprOf<i32, diesel::sql_types::Integer>,
+  >: diesel_async::methods::ExecuteDsl<C>,
+  diesel::query_builder::SqlQuery:
+    query_builder::QueryFragment<<C as AsyncConnection>::Backend>,
+{
+  pool: Pool<C>,
+}
+
+/// Implement `Clone` for `AsyncDbDriver`.
+impl<C> Clone for AsyncDbDriver<C>
+where
+  C: AsyncConnection + PoolableConnection + 'static,
+  diesel::dsl::BareSelect<
+    diesel::dsl::AsExprOf<i32, diesel::sql_types::Integer>,
+  >: diesel_async::methods::ExecuteDsl<C>,
+  diesel::query_builder::SqlQuery:
+    query_builder::QueryFragment<<C as AsyncConnection>::Backend>,
+{
+  fn clone(&self) -> Self {
+    Self {
+      pool: self.pool.clone(),
+    }
+  }
+}
+
+impl<C> AsyncDbDriver<C>
+where
+  C: AsyncConnection + PoolableConnection + 'static,
+  diesel::dsl::BareSelect<
+    diesel::dsl::AsExprOf<i32, diesel::sql_types::Integer>,
+  >: diesel_async::methods::ExecuteDsl<C>,
+  diesel::query_builder::SqlQuery:
+    query_builder::QueryFragment<<C as AsyncConnection>::Backend>,
+{
+  /// Create a new async database driver.
+  pub async fn new(db_url: &str) -> Result<Self> {
+    let manager = AsyncDieselConnectionManager::<C>::new(db_url);
+    let pool = Pool::builder()
+      .build(manager)
+      .await
+      .map_err(|err| TurboDieselError::Connection(err.to_string()))?;
+    Ok(Self { pool })
+  }
+
+  /// Get a connection from the pool,
+  /// `TurboDieselError::PoolTimeout` when the pool is saturated.
+  pub async fn get_conn(&self) -> Result<PooledConnection<'_, C>> {
+    self.pool.get().await.map_err(|err| match err {
+      RunError::TimedOut => TurboDieselError::PoolTimeout,
+      RunError::User(err) => TurboDieselError::Connection(err.to_string()),
+    })
+  }
+
+  /// Insert a row and return it.
+  pub async fn create<I>(&self, item: &I) -> Result<I>
+  where
+    I: HasTable + diesel::Insertable<I::Table> + Clone + Send + 'static,
+    I::Table: diesel::Table,
+    query_builder::InsertStatement<
+      I::Table,
+      <I as diesel::Insertable<I::Table>>::Values,
+    >: diesel_async::methods::LoadQuery<'static, C, I> + 'static,
+  {
+    let mut conn = self.get_conn().await?;
+    let item = diesel::insert_into(<I as HasTable>::table())
+      .values(item.clone())
+      .get_result(&mut *conn)
+      .await?;
+    Ok(item)
+  }
+
+  /// Delete the row with the primary key `pk`, return how many were deleted.
+  pub async fn del_by_pk<I, Pk>(&self, pk: &Pk) -> Result<usize>
+  where
+    Pk: ToOwned + ?Sized,
+    I: HasTable,
+    I::Table: FindDsl<<Pk as ToOwned>::Owned> + HasTable<Table = I::Table>,
+    diesel::helper_types::Find<I::Table, <Pk as ToOwned>::Owned>:
+      query_builder::IntoUpdateTarget,
+    query_builder::DeleteStatement<
+      <diesel::helper_types::Find<I::Table, <Pk as ToOwned>::Owned> as HasTable>::Table,
+      <diesel::helper_types::Find<I::Table, <Pk as ToOwned>::Owned> as query_builder::IntoUpdateTarget>::WhereClause,
+    >: diesel_async::methods::ExecuteDsl<C>,
+  {
+    let mut conn = self.get_conn().await?;
+    let count =
+      diesel::delete(<I::Table as HasTable>::table().find(pk.to_owned()))
+        .execute(&mut *conn)
+        .await?;
+    Ok(count)
+  }
+
+  /// Delete the rows matching the where clause of the filter,
+  /// return how many were deleted.
+  /// Like `DbModelDelBy::del_by` a where clause true for every row is refused.
+  pub async fn del_by<I>(&self, filter: &GenericFilter) -> Result<usize>
+  where
+    I: HasTable + FilterableColumns<C::Backend>,
+    I::Table:
+      query_builder::IntoUpdateTarget + HasTable<Table = I::Table> + 'static,
+    query_builder::DeleteStatement<
+      I::Table,
+      <I::Table as query_builder::IntoUpdateTarget>::WhereClause,
+    >: BoxedDsl<
+      'static,
+      C::Backend,
+      Output = query_builder::BoxedDeleteStatement<
+        'static,
+        C::Backend,
+        I::Table,
+      >,
+    >,
+    query_builder::BoxedDeleteStatement<'static, C::Backend, I::Table>:
+      diesel_async::methods::ExecuteDsl<C>,
+    C::Backend: DieselReserveSpecialization + 'static,
+  {
+    if !filter.has_conditions() {
+      return Err(
+        diesel::result::Error::QueryBuilderError(
+          "Refusing to delete without a where clause, use del_all instead"
+            .into(),
+        )
+        .into(),
+      );
+    }
+    let query = diesel::delete(<I::Table as HasTable>::table()).into_boxed();
+    let query = apply_where::<I, _, _>(query, filter)?;
+    let mut conn = self.get_conn().await?;
+    let count = query.execute(&mut *conn).await?;
+    Ok(count)
+  }
+}
@@ -0,0 +1,726 @@
+use std::any::TypeId;
+
+use diesel::{
+  prelude::*,
+  associations::HasTable,
+  backend::Backend,
+  expression::{is_aggregate, ValidGrouping},
+  pg::Pg,
+  query_builder::{AstPass, QueryFragment, QueryId},
+  sql_types::{
+    is_nullable, Array, BigInt, Bool, Double, Float, Integer, Jsonb, Nullable,
+    SingleValue, SmallInt, SqlType, Text, Timestamp,
+  },
+  sqlite::Sqlite,
+};
+
+use crate::error::TurboDieselError;
+use crate::filter::{GenericClause, GenericValue};
+use crate::query::{constant_predicate, BoxedPredicate};
+
+/// Map the column names used in a `GenericFilter` to the diesel columns
+/// of the model table.
+/// Diesel can't resolve a column from a `&str` at runtime,
+/// so this is what turns the string keys of `r#where` into typed columns.
+/// It's usually implemented with `#[derive(FilterableColumns)]`.
+pub trait FilterableColumns<DB>: HasTable
+where
+  DB: Backend,
+{
+  /// Names of the columns that can be used in a filter
+  const COLUMNS: &'static [&'static str];
+
+  /// Call the visitor with the column named `column`.
+  /// Unknown names end up in `Error::QueryBuilderError("unknown column ...")`.
+  fn visit_column<V>(
+    column: &str,
+    visitor: V,
+  ) -> Result<V::Output, diesel::result::Error>
+  where
+    V: ColumnVisitor<Self::Table, DB>;
+
+  /// Build the predicate of `clause` applied on the column named `column`.
+  fn predicate(
+    column: &str,
+    clause: &GenericClause,
+  ) -> Result<BoxedPredicate<Self::Table, DB>, diesel::result::Error>
+  where
+    Self::Table: 'static,
+    DB: 'static,
+  {
+    check_backend::<DB>(clause)?;
+    Self::visit_column(column, PredicateVisitor(clause))
+  }
+}
+
+/// Something to do with a column resolved by `FilterableColumns`.
+pub trait ColumnVisitor<T, DB>
+where
+  DB: Backend,
+{
+  type Output;
+
+  fn visit<C>(self, column: C) -> Result<Self::Output, diesel::result::Error>
+  where
+    C: FilterColumn<T, DB>;
+}
+
+/// A column of the table `T`, or an expression acting like one,
+/// whose type knows how to apply a `GenericClause` on the backend `DB`.
+pub trait FilterColumn<T, DB>:
+  Expression<SqlType: FilterSqlType<DB>>
+  + SelectableExpression<T>
+  + ValidGrouping<(), IsAggregate = is_aggregate::No>
+  + QueryFragment<DB>
+  + QueryId
+  + Copy
+  + Send
+  + 'static
+where
+  DB: Backend,
+{
+}
+
+impl<T, DB, C> FilterColumn<T, DB> for C
+where
+  DB: Backend,
+  C: Expression<SqlType: FilterSqlType<DB>>
+    + SelectableExpression<T>
+    + ValidGrouping<(), IsAggregate = is_aggregate::No>
+    + QueryFragment<DB>
+    + QueryId
+    + Copy
+    + Send
+    + 'static,
+{
+}
+
+/// A SQL type a `GenericClause` can be applied on for the backend `DB`.
+pub trait FilterSqlType<DB>: SqlType + SingleValue
+where
+  DB: Backend,
+{
+  /// Whether a column of the type can order a keyset page,
+  /// its value being compared again from the text of the cursor.
+  /// Floats lose precision as text and booleans read back differently
+  /// on every backend, so they can't.
+  const KEYSET_KEY: bool = true;
+
+  fn predicate<T, C>(
+    column: C,
+    clause: &GenericClause,
+  ) -> Result<BoxedPredicate<T, DB>, diesel::result::Error>
+  where
+    T: Table + 'static,
+    C: FilterColumn<T, DB, SqlType = Self>;
+
+  /// Bind `value` converted to the type, the value set by
+  /// `DbModelUpdate::set_field`. The types without a conversion,
+  /// the arrays say, refuse every value.
+  fn bind(
+    value: &GenericValue,
+  ) -> Result<BoxedValue<DB>, diesel::result::Error> {
+    Err(diesel::result::Error::QueryBuilderError(
+      format!("cannot set {value:?} on {}", std::any::type_name::<Self>())
+        .into(),
+    ))
+  }
+}
+
+/// A value bound for a column, see `FilterSqlType::bind`.
+pub type BoxedValue<DB> = Box<dyn QueryFragment<DB> + Send>;
+
+/// Build the predicate of a clause applied on a column.
+struct PredicateVisitor<'a>(&'a GenericClause);
+
+impl<T, DB> ColumnVisitor<T, DB> for PredicateVisitor<'_>
+where
+  T: Table + 'static,
+  DB: Backend,
+{
+  type Output = BoxedPredicate<T, DB>;
+
+  fn visit<C>(self, column: C) -> Result<Self::Output, diesel::result::Error>
+  where
+    C: FilterColumn<T, DB>,
+  {
+    <C::SqlType as FilterSqlType<DB>>::predicate(column, self.0)
+  }
+}
+
+/// Nullable columns behave like their inner type,
+/// `IsNull` and `IsNotNull` still render against the column itself.
+impl<ST, DB> FilterSqlType<DB> for Nullable<ST>
+where
+  DB: Backend,
+  ST: FilterSqlType<DB> + SqlType<IsNull = is_nullable::NotNull>,
+{
+  const KEYSET_KEY: bool = ST::KEYSET_KEY;
+
+  fn predicate<T, C>(
+    column: C,
+    clause: &GenericClause,
+  ) -> Result<BoxedPredicate<T, DB>, diesel::result::Error>
+  where
+    T: Table + 'static,
+    C: FilterColumn<T, DB, SqlType = Self>,
+  {
+    ST::predicate(column.assume_not_null(), clause)
+  }
+
+  fn bind(
+    value: &GenericValue,
+  ) -> Result<BoxedValue<DB>, diesel::result::Error> {
+    match value {
+      GenericValue::Null => Ok(Box::new(NullValue)),
+      value => ST::bind(value),
+    }
+  }
+}
+
+/// The `NULL` set on a nullable column.
+struct NullValue;
+
+impl<DB> QueryFragment<DB> for NullValue
+where
+  DB: Backend,
+{
+  fn walk_ast<'b>(&'b self, mut out: AstPass<'_, 'b, DB>) -> QueryResult<()> {
+    out.push_sql("NULL");
+    Ok(())
+  }
+}
+
+/// Build the predicate of the clauses every SQL type supports,
+/// followed by the extra arms specific to the type.
+macro_rules! predicate {
+  ($column:ident, $clause:ident, $value:ty $(, $pat:pat => $arm:expr)* $(,)?) => {{
+    let predicate: BoxedPredicate<T, _> = match $clause {
+      // `= NULL` never matches, compare with `IS NULL` instead
+      GenericClause::Eq(GenericValue::Null) => Box::new($column.is_null()),
+      GenericClause::Ne(GenericValue::Null) => Box::new($column.is_not_null()),
+      GenericClause::Eq(value) => Box::new($column.eq(convert::<$value>(value)?)),
+      GenericClause::Ne(value) => Box::new($column.ne(convert::<$value>(value)?)),
+      GenericClause::Gt(value) => Box::new($column.gt(convert::<$value>(value)?)),
+      GenericClause::Lt(value) => Box::new($column.lt(convert::<$value>(value)?)),
+      GenericClause::Ge(value) => Box::new($column.ge(convert::<$value>(value)?)),
+      GenericClause::Le(value) => Box::new($column.le(convert::<$value>(value)?)),
+      GenericClause::Between(lower, upper) => Box::new(
+        $column.between(convert::<$value>(lower)?, convert::<$value>(upper)?),
+      ),
+      GenericClause::NotBetween(lower, upper) => Box::new(
+        $column.not_between(convert::<$value>(lower)?, convert::<$value>(upper)?),
+      ),
+      // An empty list matches no row for `IN` and every row for `NOT IN`
+      GenericClause::In(values) if values.is_empty() => {
+        constant_predicate(false)
+      }
+      GenericClause::NotIn(values) if values.is_empty() => {
+        constant_predicate(true)
+      }
+      GenericClause::In(values) => {
+        Box::new($column.eq_any(convert_all::<$value>(values)?))
+      }
+      GenericClause::NotIn(values) => {
+        Box::new($column.ne_all(convert_all::<$value>(values)?))
+      }
+      GenericClause::IsNull => Box::new($column.is_null()),
+      GenericClause::IsNotNull => Box::new($column.is_not_null()),
+      GenericClause::ExistsRelated(table, foreign_key) => {
+        Box::new(ExistsRelated {
+          column: $column,
+          table: table.clone(),
+          foreign_key: foreign_key.clone(),
+        })
+      }
+      $($pat => $arm,)*
+      #[allow(unreachable_patterns)]
+      _ => return Err(unsupported_clause::<Self>($clause)),
+    };
+    Ok(predicate)
+  }};
+}
+
+/// Implement `FilterSqlType` for the scalar types of a backend,
+/// the extra arms are the text clauses specific to the backend.
+/// `keyset: false` marks a scalar that can't order a keyset page.
+macro_rules! impl_filter_sql_type {
+  (
+    @scalar $backend:ty, $sql_type:ty => $value:ty
+    $(, keyset: $keyset:literal)?
+  ) => {
+    impl FilterSqlType<$backend> for $sql_type {
+      $(const KEYSET_KEY: bool = $keyset;)?
+
+      fn predicate<T, C>(
+        column: C,
+        clause: &GenericClause,
+      ) -> Result<BoxedPredicate<T, $backend>, diesel::result::Error>
+      where
+        T: Table + 'static,
+        C: FilterColumn<T, $backend, SqlType = Self>,
+      {
+        predicate!(column, clause, $value)
+      }
+
+      fn bind(
+        value: &GenericValue,
+      ) -> Result<BoxedValue<$backend>, diesel::result::Error> {
+        Ok(Box::new(convert::<$value>(value)?.into_sql::<Self>()))
+      }
+    }
+  };
+  ($backend:ty, |$column:ident| $($pat:pat => $arm:expr),* $(,)?) => {
+    impl FilterSqlType<$backend> for Text {
+      fn predicate<T, C>(
+        $column: C,
+        clause: &GenericClause,
+      ) -> Result<BoxedPredicate<T, $backend>, diesel::result::Error>
+      where
+        T: Table + 'static,
+        C: FilterColumn<T, $backend, SqlType = Self>,
+      {
+        predicate!($column, clause, String,
+          GenericClause::Like(value) => Box::new($column.like(value.clone())),
+          GenericClause::NotLike(value) => {
+            Box::new($column.not_like(value.clone()))
+          },
+          GenericClause::StartsWith(value) => Box::new(
+            $column.like(format!("{}%", escape_like(value))).escape('\\'),
+          ),
+          GenericClause::EndsWith(value) => Box::new(
+            $column.like(format!("%{}", escape_like(value))).escape('\\'),
+          ),
+          GenericClause::ContainsText(value) => Box::new(
+            $column.like(format!("%{}%", escape_like(value))).escape('\\'),
+          ),
+          $($pat => $arm,)*
+        )
+      }
+
+      fn bind(
+        value: &GenericValue,
+      ) -> Result<BoxedValue<$backend>, diesel::result::Error> {
+        Ok(Box::new(convert::<String>(value)?.into_sql::<Self>()))
+      }
+    }
+
+    impl_filter_sql_type!(@scalar $backend, SmallInt => i16);
+    impl_filter_sql_type!(@scalar $backend, Integer => i32);
+    impl_filter_sql_type!(@scalar $backend, BigInt => i64);
+    impl_filter_sql_type!(@scalar $backend, Float => f32, keyset: false);
+    impl_filter_sql_type!(@scalar $backend, Double => f64, keyset: false);
+    impl_filter_sql_type!(@scalar $backend, Bool => bool, keyset: false);
+  };
+}
+
+sql_function!(fn lower(x: Text) -> Text);
+
+// SQLite has no ILIKE, lowering both sides keeps the clause case insensitive
+// even with `PRAGMA case_sensitive_like`.
+// Without the ICU extension only ASCII letters are lowered.
+impl_filter_sql_type!(Sqlite, |column|
+  GenericClause::ILike(value) => {
+    Box::new(lower(column).like(lower(value.clone())))
+  },
+);
+// SQLite stores the timestamps as text, compared as such
+impl_filter_sql_type!(@scalar Sqlite, Timestamp => String);
+impl_filter_sql_type!(Pg, |column|
+  GenericClause::ILike(value) => Box::new(column.ilike(value.clone())),
+  GenericClause::FullText(query, config) => Box::new(FullTextMatch {
+    column,
+    query: query.clone(),
+    config: config.clone(),
+  }),
+);
+// The timestamps of Postgres are parsed with chrono,
+// `2024-01-31T12:00:00` for a `Timestamp`
+// and `2024-01-31T12:00:00Z` with an offset for a `Timestamptz`
+#[cfg(feature = "chrono")]
+impl_filter_sql_type!(@scalar Pg, Timestamp => chrono::NaiveDateTime);
+#[cfg(feature = "chrono")]
+impl_filter_sql_type!(
+  @scalar Pg,
+  diesel::sql_types::Timestamptz => chrono::DateTime<chrono::Utc>
+);
+// A `uuid::Uuid` compares like the other scalars, parsed from the text
+#[cfg(feature = "uuid")]
+impl_filter_sql_type!(@scalar Pg, diesel::sql_types::Uuid => uuid::Uuid);
+
+/// The predicate of `GenericClause::FullText`.
+/// Diesel has no text search types, the SQL is written here,
+/// the configuration being cast to `regconfig` on both sides.
+/// An expression index on the same `to_tsvector` of the column,
+/// with the same configuration, keeps it fast.
+struct FullTextMatch<C> {
+  column: C,
+  query: String,
+  config: Option<String>,
+}
+
+impl<C> Expression for FullTextMatch<C> {
+  type SqlType = Bool;
+}
+
+impl<C, QS> AppearsOnTable<QS> for FullTextMatch<C> where C: AppearsOnTable<QS> {}
+
+impl<C, QS> SelectableExpression<QS> for FullTextMatch<C> where
+  C: SelectableExpression<QS>
+{
+}
+
+impl<C, GB> ValidGrouping<GB> for FullTextMatch<C>
+where
+  C: ValidGrouping<GB>,
+{
+  type IsAggregate = C::IsAggregate;
+}
+
+impl<C> QueryFragment<Pg> for FullTextMatch<C>
+where
+  C: QueryFragment<Pg>,
+{
+  fn walk_ast<'b>(&'b self, mut out: AstPass<'_, 'b, Pg>) -> QueryResult<()> {
+    out.push_sql("(to_tsvector(");
+    self.walk_config(out.reborrow())?;
+    self.column.walk_ast(out.reborrow())?;
+    out.push_sql(") @@ plainto_tsquery(");
+    self.walk_config(out.reborrow())?;
+    out.push_bind_param::<Text, _>(&self.query)?;
+    out.push_sql("))");
+    Ok(())
+  }
+}
+
+impl<C> FullTextMatch<C> {
+  fn walk_config<'b>(
+    &'b self,
+    mut out: AstPass<'_, 'b, Pg>,
+  ) -> QueryResult<()> {
+    if let Some(config) = &self.config {
+      out.push_sql("CAST(");
+      out.push_bind_param::<Text, _>(config)?;
+      out.push_sql(" AS regconfig), ");
+    }
+    Ok(())
+  }
+}
+// MySQL has no ILIKE either, its default collations already ignore the case
+// but lowering keeps the clause case insensitive with a binary collation.
+#[cfg(feature = "mysql")]
+impl_filter_sql_type!(diesel::mysql::Mysql, |column|
+  GenericClause::ILike(value) => {
+    Box::new(lower(column).like(lower(value.clone())))
+  },
+);
+
+impl FilterSqlType<Pg> for Jsonb {
+  const KEYSET_KEY: bool = false;
+
+  fn predicate<T, C>(
+    column: C,
+    clause: &GenericClause,
+  ) -> Result<BoxedPredicate<T, Pg>, diesel::result::Error>
+  where
+    T: Table + 'static,
+    C: FilterColumn<T, Pg, SqlType = Self>,
+  {
+    predicate!(column, clause, serde_json::Value,
+      GenericClause::JsonContains(value) => {
+        Box::new(column.contains(value.clone()))
+      },
+      GenericClause::HasKey(key) => Box::new(column.has_key(key.clone())),
+      GenericClause::JsonPathEq(path, serde_json::Value::String(value)) => {
+        let text = column.retrieve_by_path_as_text(path.clone());
+        Box::new(text.eq(value.clone()))
+      },
+      GenericClause::JsonPathEq(path, value) => Box::new(
+        column.retrieve_by_path_as_object(path.clone()).eq(value.clone()),
+      ),
+      GenericClause::JsonPathExists(path) => Box::new(
+        column.retrieve_by_path_as_object(path.clone()).is_not_null(),
+      ),
+    )
+  }
+
+  fn bind(
+    value: &GenericValue,
+  ) -> Result<BoxedValue<Pg>, diesel::result::Error> {
+    Ok(Box::new(
+      convert::<serde_json::Value>(value)?.into_sql::<Self>(),
+    ))
+  }
+}
+
+/// The number of columns of a tuple of columns,
+/// like the `AllColumns` of a table.
+pub trait ColumnCount {
+  const COUNT: usize;
+}
+
+/// Implement `ColumnCount` for the tuples up to the 32 columns of a table.
+macro_rules! impl_column_count {
+  ($first:ident $(, $rest:ident)*) => {
+    impl<$first $(, $rest)*> ColumnCount for ($first, $($rest,)*) {
+      const COUNT: usize = 1 $(+ impl_column_count!(@one $rest))*;
+    }
+
+    impl_column_count!($($rest),*);
+  };
+  () => {};
+  (@one $column:ident) => {
+    1
+  };
+}
+
+impl_column_count!(
+  C1, C2, C3, C4, C5, C6, C7, C8, C9, C10, C11, C12, C13, C14, C15, C16, C17,
+  C18, C19, C20, C21, C22, C23, C24, C25, C26, C27, C28, C29, C30, C31, C32
+);
+
+/// The predicate of `GenericClause::ExistsRelated`, a subquery correlated
+/// with the column. Diesel checks the tables of a subquery at compile time
+/// and the related table is only known at runtime, the SQL is written here.
+struct ExistsRelated<C> {
+  column: C,
+  table: String,
+  foreign_key: String,
+}
+
+impl<C> Expression for ExistsRelated<C> {
+  type SqlType = Bool;
+}
+
+impl<C, QS> AppearsOnTable<QS> for ExistsRelated<C> where C: AppearsOnTable<QS> {}
+
+impl<C, QS> SelectableExpression<QS> for ExistsRelated<C> where
+  C: SelectableExpression<QS>
+{
+}
+
+impl<C, GB> ValidGrouping<GB> for ExistsRelated<C>
+where
+  C: ValidGrouping<GB>,
+{
+  type IsAggregate = C::IsAggregate;
+}
+
+impl<C, DB> QueryFragment<DB> for ExistsRelated<C>
+where
+  DB: Backend,
+  C: QueryFragment<DB>,
+{
+  fn walk_ast<'b>(&'b self, mut out: AstPass<'_, 'b, DB>) -> QueryResult<()> {
+    out.push_sql("EXISTS (SELECT 1 FROM ");
+    out.push_identifier(&self.table)?;
+    out.push_sql(" WHERE ");
+    out.push_identifier(&self.table)?;
+    out.push_sql(".");
+    out.push_identifier(&self.foreign_key)?;
+    out.push_sql(" = ");
+    self.column.walk_ast(out.reborrow())?;
+    out.push_sql(")");
+    Ok(())
+  }
+}
+
+/// Implement `FilterSqlType` for the Postgres arrays of each type,
+/// with elements nullable or not as diesel maps `text[]` to
+/// `Array<Nullable<Text>>`. Only the array and null clauses apply.
+macro_rules! impl_array_filter_sql_type {
+  ($($sql_type:ty => $value:ty),* $(,)?) => {
+    $(
+      impl_array_filter_sql_type!(@one $sql_type => $value);
+      impl_array_filter_sql_type!(@one Nullable<$sql_type> => $value);
+    )*
+  };
+  (@one $sql_type:ty => $value:ty) => {
+    impl FilterSqlType<Pg> for Array<$sql_type> {
+      const KEYSET_KEY: bool = false;
+
+      fn predicate<T, C>(
+        column: C,
+        clause: &GenericClause,
+      ) -> Result<BoxedPredicate<T, Pg>, diesel::result::Error>
+      where
+        T: Table + 'static,
+        C: FilterColumn<T, Pg, SqlType = Self>,
+      {
+        let predicate: BoxedPredicate<T, Pg> = match clause {
+          GenericClause::ArrayContains(values) => {
+            Box::new(column.contains(convert_all::<$value>(values)?))
+          }
+          GenericClause::ArrayOverlaps(values) => {
+            Box::new(column.overlaps_with(convert_all::<$value>(values)?))
+          }
+          GenericClause::IsNull => Box::new(column.is_null()),
+          GenericClause::IsNotNull => Box::new(column.is_not_null()),
+          _ => return Err(unsupported_clause::<Self>(clause)),
+        };
+        Ok(predicate)
+      }
+    }
+  };
+}
+
+impl_array_filter_sql_type!(
+  Text => String,
+  SmallInt => i16,
+  Integer => i32,
+  BigInt => i64,
+  Float => f32,
+  Double => f64,
+  Bool => bool,
+);
+
+/// A value bound for a column, converted from the operand of a clause.
+trait FromGenericValue: Sized {
+  fn from_generic(value: &GenericValue) -> Result<Self, String>;
+}
+
+/// Implement `FromGenericValue` for each type,
+/// a text is parsed and the arms convert the other variants.
+macro_rules! impl_from_generic_value {
+  ($($value:ty),* => |$generic:ident| $arms:tt) => {
+    $(impl_from_generic_value!(@one $value, $generic, $arms);)*
+  };
+  (@one $value:ty, $generic:ident, { $($arms:tt)* }) => {
+    impl FromGenericValue for $value {
+      fn from_generic($generic: &GenericValue) -> Result<Self, String> {
+        match $generic {
+          GenericValue::Text(text) => {
+            text.parse().map_err(|err| format!("{err}"))
+          }
+          $($arms)*
+          #[allow(unreachable_patterns)]
+          _ => Err(format!("expected {}", std::any::type_name::<Self>())),
+        }
+      }
+    }
+  };
+}
+
+impl_from_generic_value!(i64 => |generic| {
+  GenericValue::Int(value) => Ok(*value),
+});
+impl_from_generic_value!(i16, i32 => |generic| {
+  GenericValue::Int(value) => {
+    Self::try_from(*value).map_err(|err| err.to_string())
+  }
+});
+impl_from_generic_value!(f32, f64 => |generic| {
+  GenericValue::Float(value) => Ok(*value as Self),
+  GenericValue::Int(value) => Ok(*value as Self),
+});
+impl_from_generic_value!(bool => |generic| {
+  GenericValue::Bool(value) => Ok(*value),
+});
+impl_from_generic_value!(String => |generic| {
+  GenericValue::Int(value) => Ok(value.to_string()),
+  GenericValue::Float(value) => Ok(value.to_string()),
+  GenericValue::Bool(value) => Ok(value.to_string()),
+});
+// The timestamps are only given as text
+#[cfg(feature = "chrono")]
+impl_from_generic_value!(
+  chrono::NaiveDateTime, chrono::DateTime<chrono::Utc> => |generic| {}
+);
+#[cfg(feature = "uuid")]
+impl_from_generic_value!(uuid::Uuid => |generic| {});
+impl_from_generic_value!(serde_json::Value => |generic| {
+  GenericValue::Json(value) => Ok(value.clone()),
+  GenericValue::Int(value) => Ok((*value).into()),
+  GenericValue::Float(value) => Ok((*value).into()),
+  GenericValue::Bool(value) => Ok((*value).into()),
+  GenericValue::Null => Ok(serde_json::Value::Null),
+});
+
+/// Convert the operand of a clause into the value bound for the column,
+/// a text operand is parsed.
+fn convert<V>(value: &GenericValue) -> Result<V, diesel::result::Error>
+where
+  V: FromGenericValue,
+{
+  V::from_generic(value).map_err(|err| {
+    diesel::result::Error::QueryBuilderError(
+      format!("invalid value {value:?}: {err}").into(),
+    )
+  })
+}
+
+fn convert_all<V>(
+  values: &[GenericValue],
+) -> Result<Vec<V>, diesel::result::Error>
+where
+  V: FromGenericValue,
+{
+  values.iter().map(convert).collect()
+}
+
+/// Escape the wildcards of a `LIKE` pattern, `\` being the escape character.
+fn escape_like(value: &str) -> String {
+  let mut escaped = String::with_capacity(value.len());
+  for c in value.chars() {
+    if matches!(c, '\\' | '%' | '_') {
+      escaped.push('\\');
+    }
+    escaped.push(c);
+  }
+  escaped
+}
+
+/// Refuse a clause of Postgres on another backend before looking up
+/// the column, `TurboDieselError::UnsupportedOnBackend` being clearer
+/// than the column type not supporting it.
+fn check_backend<DB: 'static>(
+  clause: &GenericClause,
+) -> Result<(), diesel::result::Error> {
+  if TypeId::of::<DB>() == TypeId::of::<Pg>() {
+    return Ok(());
+  }
+  let Some(clause) = clause.postgres_only() else {
+    return Ok(());
+  };
+  let backend = match TypeId::of::<DB>() {
+    id if id == TypeId::of::<Sqlite>() => "SQLite",
+    #[cfg(feature = "mysql")]
+    id if id == TypeId::of::<diesel::mysql::Mysql>() => "MySQL",
+    _ => std::any::type_name::<DB>(),
+  };
+  let err = TurboDieselError::UnsupportedOnBackend { clause, backend };
+  Err(diesel::result::Error::QueryBuilderError(Box::new(err)))
+}
+
+/// Error returned when a clause can't be applied on the SQL type `ST`.
+fn unsupported_clause<ST>(clause: &GenericClause) -> diesel::result::Error {
+  let hint = match clause {
+    GenericClause::JsonContains(_)
+    | GenericClause::HasKey(_)
+    | GenericClause::JsonPathEq(..)
+    | GenericClause::JsonPathExists(_) => {
+      ", json clauses need a Postgres jsonb column"
+    }
+    GenericClause::ArrayContains(_) | GenericClause::ArrayOverlaps(_) => {
+      ", array clauses need a Postgres array column"
+    }
+    GenericClause::FullText(..) => {
+      ", full text search needs a Postgres text column"
+    }
+    _ => "",
+  };
+  diesel::result::Error::QueryBuilderError(
+    format!(
+      "unsupported clause {clause:?} on {}{hint}",
+      std::any::type_name::<ST>()
+    )
+    .into(),
+  )
+}
+
+/// Error returned when a filter reference a column the model doesn't have.
+pub fn unknown_column(column: &str) -> diesel::result::Error {
+  diesel::result::Error::QueryBuilderError(
+    format!("unknown column {column}").into(),
+  )
+}
@@ -1,15 +1,372 @@
+use std::any::{Any, TypeId};
 use std::future::Future;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::Duration;
+
+use futures_util::Stream;
 
 use diesel::{
   prelude::*,
   associations::HasTable,
-  r2d2::{ConnectionManager, Pool, PooledConnection},
+  r2d2::{ConnectionManager, CustomizeConnection, Pool, PooledConnection},
   query_dsl, query_builder,
   connection::LoadConnection,
+  expression::TypedExpressionType,
+  internal::derives::multiconnection::Exists,
+  serialize::ToSql,
+  sql_types::{BigInt, HasSqlType, SingleValue},
 };
 
 use crate::prelude::*;
 
+/// Number of binds per statement of `create_many` and `upsert_many`
+/// when no chunk size is given, SQLite historical bind limit.
+/// A statement holds this many binds divided by the columns of the table rows,
+/// `read_stream` reads this many rows per query.
+pub const DEFAULT_CHUNK_SIZE: usize = 999;
+
+/// Number of rows returned by `read_by` when the filter has no limit.
+pub const DEFAULT_LIMIT: usize = 100;
+
+/// Settings of the connection pool used by `DbDriver::new_with_config`.
+/// Every `None` field keeps the default of r2d2.
+#[derive(Clone, Debug, Default)]
+pub struct PoolConfig {
+  /// Maximum number of connections, 10 by default.
+  pub max_size: Option<u32>,
+  /// Minimum number of idle connections, `max_size` by default.
+  pub min_idle: Option<u32>,
+  /// How long to wait for a connection, 30 seconds by default.
+  pub connection_timeout: Option<Duration>,
+  /// How long a connection can stay idle, 10 minutes by default.
+  pub idle_timeout: Option<Duration>,
+  /// How long a connection can live, 30 minutes by default.
+  pub max_lifetime: Option<Duration>,
+  /// Check a connection with a `SELECT 1` before handing it out,
+  /// replacing it when the database dropped it, after a failover
+  /// or an idle timeout of the server say, `true` by default.
+  /// It costs a round trip on every checkout,
+  /// turn it off when the connections can't go stale.
+  pub test_on_check_out: Option<bool>,
+  /// SQL run on every connection opened by the pool.
+  pub connection_customizer: Option<ConnectionCustomizer>,
+  /// Cancel the statements running longer, with a `TurboDieselError::Timeout`.
+  /// `SET statement_timeout` on Postgres, `max_execution_time` on MySQL
+  /// (selects only), on SQLite `PRAGMA busy_timeout` bounds the wait
+  /// for a locked database instead, a database still locked after it
+  /// is a `TurboDieselError::Query`.
+  /// Run after the statements of `connection_customizer`.
+  pub statement_timeout: Option<Duration>,
+  /// Limit of the reads whose filter has none, `DEFAULT_LIMIT` by default,
+  /// see `DbDriver::with_default_limit` to lift it.
+  pub default_limit: Option<usize>,
+  /// Pragmas of the SQLite connections, ignored on the other backends.
+  pub sqlite: Option<SqliteConfig>,
+  /// Largest offset of a read, no maximum by default,
+  /// see `DbDriver::with_max_offset`.
+  pub max_offset: Option<usize>,
+}
+
+/// Pragmas run on every SQLite connection opened by the pool,
+/// after `statement_timeout`. Concurrent writers fail with
+/// `database is locked` as soon as another one holds the lock,
+/// a `busy_timeout` and the WAL journal make them wait in turn instead.
+#[derive(Clone, Debug, Default)]
+pub struct SqliteConfig {
+  /// `PRAGMA busy_timeout`, how long to wait for a locked database
+  /// before failing with a `TurboDieselError::Query`.
+  pub busy_timeout: Option<Duration>,
+  /// `PRAGMA journal_mode = WAL`, the readers no longer block the writer.
+  /// The mode is kept in the database file, it stays on for every connection.
+  pub wal: bool,
+  /// `PRAGMA synchronous`, `Normal` is safe with the WAL journal
+  /// and saves a sync of every commit.
+  pub synchronous: Option<SqliteSynchronous>,
+}
+
+/// The values of `PRAGMA synchronous`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SqliteSynchronous {
+  Off,
+  Normal,
+  Full,
+  Extra,
+}
+
+impl SqliteConfig {
+  /// The pragmas to run, the journal mode first.
+  fn statements(&self) -> Vec<String> {
+    let mut statements = Vec::new();
+    if self.wal {
+      statements.push("PRAGMA journal_mode = WAL".to_owned());
+    }
+    if let Some(synchronous) = self.synchronous {
+      let mode = match synchronous {
+        SqliteSynchronous::Off => "OFF",
+        SqliteSynchronous::Normal => "NORMAL",
+        SqliteSynchronous::Full => "FULL",
+        SqliteSynchronous::Extra => "EXTRA",
+      };
+      statements.push(format!("PRAGMA synchronous = {mode}"));
+    }
+    if let Some(timeout) = self.busy_timeout {
+      let millis = timeout.as_millis();
+      statements.push(format!("PRAGMA busy_timeout = {millis}"));
+    }
+    statements
+  }
+}
+
+/// Statements run on every connection opened by the pool,
+/// before it is handed out, like `SET search_path TO tenant`
+/// on Postgres or `PRAGMA busy_timeout = 5000` on SQLite.
+/// A failing statement fails the checkout of the connection.
+#[derive(Clone, Debug, Default)]
+pub struct ConnectionCustomizer {
+  statements: Vec<String>,
+}
+
+impl ConnectionCustomizer {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Add a statement, run in order of addition.
+  /// It's sent as is, without bind parameters.
+  pub fn sql(mut self, sql: &str) -> Self {
+    self.statements.push(sql.to_owned());
+    self
+  }
+}
+
+impl<C> CustomizeConnection<C, diesel::r2d2::Error> for ConnectionCustomizer
+where
+  C: Connection,
+{
+  fn on_acquire(&self, conn: &mut C) -> Result<(), diesel::r2d2::Error> {
+    for sql in &self.statements {
+      conn
+        .batch_execute(sql)
+        .map_err(diesel::r2d2::Error::QueryError)?;
+    }
+    Ok(())
+  }
+}
+
+/// The statement setting the timeout of a connection, see `PoolConfig`.
+fn statement_timeout_sql<DB: 'static>(timeout: Duration) -> String {
+  let millis = timeout.as_millis();
+  #[cfg(feature = "mysql")]
+  if TypeId::of::<DB>() == TypeId::of::<diesel::mysql::Mysql>() {
+    return format!("SET SESSION max_execution_time = {millis}");
+  }
+  if TypeId::of::<DB>() == TypeId::of::<diesel::sqlite::Sqlite>() {
+    format!("PRAGMA busy_timeout = {millis}")
+  } else {
+    format!("SET statement_timeout = {millis}")
+  }
+}
+
+/// The pool `pool` failed to hand out a connection: every connection
+/// it may open is open and in use, the pool is saturated, otherwise
+/// it couldn't open a new one and the database is unreachable.
+fn pool_error<M>(
+  pool: &Pool<M>,
+  err: diesel::r2d2::PoolError,
+) -> TurboDieselError
+where
+  M: diesel::r2d2::ManageConnection,
+{
+  let state = pool.state();
+  if state.connections >= pool.max_size() && state.idle_connections == 0 {
+    TurboDieselError::PoolTimeout
+  } else {
+    TurboDieselError::Connection(err.to_string())
+  }
+}
+
+/// Get a connection from `pool`, recording the wait and the state
+/// of the pool with the `metrics` feature under the label `pool`.
+#[cfg_attr(not(feature = "metrics"), allow(unused_variables))]
+fn checkout<D>(
+  pool: &Pool<ConnectionManager<D>>,
+  label: &'static str,
+) -> Result<PooledConnection<ConnectionManager<D>>>
+where
+  D: diesel::r2d2::R2D2Connection + 'static,
+{
+  #[cfg(feature = "metrics")]
+  let start = std::time::Instant::now();
+  let conn = pool.get();
+  #[cfg(feature = "metrics")]
+  {
+    metrics::histogram!("turbo_diesel_checkout_seconds", "pool" => label)
+      .record(start.elapsed().as_secs_f64());
+    let state = pool.state();
+    let in_use = state.connections - state.idle_connections;
+    metrics::gauge!("turbo_diesel_connections_in_use", "pool" => label)
+      .set(in_use as f64);
+    metrics::gauge!("turbo_diesel_connections_idle", "pool" => label)
+      .set(state.idle_connections as f64);
+  }
+  conn.map_err(|err| pool_error(pool, err))
+}
+
+/// Run `f` in a transaction on `schema`, as is without one.
+fn in_schema<C, R>(
+  conn: &mut C,
+  schema: Option<&str>,
+  f: impl FnOnce(&mut C) -> QueryResult<R>,
+) -> QueryResult<R>
+where
+  C: Connection + 'static,
+{
+  if schema.is_none() {
+    return f(conn);
+  }
+  conn.transaction(|conn| {
+    set_search_path(conn, schema)?;
+    f(conn)
+  })
+}
+
+/// Set the search path of the current transaction to `schema`,
+/// nothing without one.
+fn set_search_path<C>(conn: &mut C, schema: Option<&str>) -> QueryResult<()>
+where
+  C: Connection + 'static,
+{
+  let Some(schema) = schema else {
+    return Ok(());
+  };
+  if TypeId::of::<C::Backend>() != TypeId::of::<diesel::pg::Pg>() {
+    return Err(diesel::result::Error::QueryBuilderError(
+      "with_schema is only supported on Postgres".into(),
+    ));
+  }
+  let schema = schema.replace('"', "\"\"");
+  conn.batch_execute(&format!("SET LOCAL search_path TO \"{schema}\""))
+}
+
+sql_function!(fn pg_backend_pid() -> diesel::sql_types::Integer);
+sql_function! {
+  fn pg_cancel_backend(
+    pid: diesel::sql_types::Integer,
+  ) -> diesel::sql_types::Bool;
+}
+
+/// The query of an `execute_cancellable`, shared with its cancellation.
+#[derive(Default)]
+struct Cancellable {
+  state: Mutex<CancelState>,
+  /// Notified when the cancel request of `pg_cancel_backend` is done
+  cancel_done: Condvar,
+}
+
+#[derive(Default)]
+struct CancelState {
+  /// The caller stopped waiting, the query no longer has to run
+  cancelled: bool,
+  /// The process of the Postgres connection running the query
+  pid: Option<i32>,
+  /// A cancel request for `pid` is running, the connection must not
+  /// go back to the pool, and to another query, before it's done
+  cancelling: bool,
+}
+
+impl Cancellable {
+  /// The lock is only held to read or write the state, never across a query.
+  fn lock(&self) -> std::sync::MutexGuard<'_, CancelState> {
+    self.state.lock().unwrap_or_else(|err| err.into_inner())
+  }
+}
+
+/// The process of a Postgres connection, `None` on the other backends.
+fn backend_pid(conn: &mut dyn Any) -> Option<i32> {
+  let conn = conn.downcast_mut::<diesel::PgConnection>()?;
+  diesel::select(pg_backend_pid()).get_result(conn).ok()
+}
+
+/// Cancel the statement running on the Postgres process `pid`
+/// from another connection, nothing on the other backends.
+fn cancel_backend(conn: &mut dyn Any, pid: i32) {
+  if let Some(conn) = conn.downcast_mut::<diesel::PgConnection>() {
+    // The query fails with its own error once cancelled
+    let _ = diesel::select(pg_cancel_backend(pid)).get_result::<bool>(conn);
+  }
+}
+
+/// A page of rows returned by `paginate`.
+#[derive(Clone, Debug)]
+pub struct Page<T> {
+  /// Rows of the page
+  pub items: Vec<T>,
+  /// Number of rows matching the filter, ignoring the limit and offset
+  pub total: i64,
+  /// Limit applied to the rows, `usize::MAX` when there is none
+  pub limit: usize,
+  /// Offset applied to the rows
+  pub offset: usize,
+}
+
+/// A page of rows returned by `paginate_keyset`.
+#[derive(Clone, Debug)]
+pub struct KeysetPage<T> {
+  /// Rows of the page
+  pub items: Vec<T>,
+  /// Cursor of the next page, `None` on the last one
+  pub next: Option<Cursor>,
+}
+
+/// Isolation level of a transaction, see `DbDriver::transaction_with_isolation`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IsolationLevel {
+  /// Each statement sees the rows committed before it started
+  ReadCommitted,
+  /// Every statement sees the rows committed before the transaction started
+  RepeatableRead,
+  /// As if the transactions ran one after the other
+  Serializable,
+}
+
+impl IsolationLevel {
+  /// The level as written in `SET TRANSACTION ISOLATION LEVEL`.
+  pub fn as_sql(&self) -> &'static str {
+    match self {
+      Self::ReadCommitted => "READ COMMITTED",
+      Self::RepeatableRead => "REPEATABLE READ",
+      Self::Serializable => "SERIALIZABLE",
+    }
+  }
+}
+
+/// The explicit go ahead of `update_all` and `del_all`,
+/// which touch every row of the table.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Confirm {
+  /// Yes, every row
+  Yes,
+}
+
+/// A snapshot of the connection pool, see `DbDriver::pool_state`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PoolState {
+  /// Number of connections opened by the pool.
+  pub connections: u32,
+  /// Number of opened connections waiting to be used.
+  pub idle_connections: u32,
+}
+
+impl From<diesel::r2d2::State> for PoolState {
+  fn from(state: diesel::r2d2::State) -> Self {
+    Self {
+      connections: state.connections,
+      idle_connections: state.idle_connections,
+    }
+  }
+}
+
 /// A Database driver.
 /// Will get a connection from the pool and execute queries.
 /// Support for multiple database types is provided by the `diesel` crate.
@@ -19,6 +376,18 @@ where
   D: diesel::r2d2::R2D2Connection + 'static,
 {
   pool: Pool<ConnectionManager<D>>,
+  /// The pools of the read replicas, see `with_replicas`
+  replicas: Arc<[Pool<ConnectionManager<D>>]>,
+  /// The replica of the next read, shared by the clones
+  next_replica: Arc<AtomicUsize>,
+  default_limit: Option<usize>,
+  max_offset: Option<usize>,
+  /// The schema of every operation, see `with_schema`
+  schema: Option<Arc<str>>,
+  #[cfg(feature = "tracing")]
+  slow_query_threshold: Option<Duration>,
+  /// Set by `shutdown`, shared by the clones
+  closed: Arc<AtomicBool>,
 }
 
 /// Implement `Clone` for `DbDriver`.
@@ -29,6 +398,14 @@ where
   fn clone(&self) -> Self {
     Self {
       pool: self.pool.clone(),
+      replicas: self.replicas.clone(),
+      next_replica: self.next_replica.clone(),
+      default_limit: self.default_limit,
+      max_offset: self.max_offset,
+      schema: self.schema.clone(),
+      #[cfg(feature = "tracing")]
+      slow_query_threshold: self.slow_query_threshold,
+      closed: self.closed.clone(),
     }
   }
 }
@@ -37,30 +414,504 @@ impl<D> DbDriver<D>
 where
   D: diesel::r2d2::R2D2Connection + Connection + LoadConnection + 'static,
 {
-  /// Create a new database driver.
-  pub fn new(db_url: &str) -> Result<Self, std::io::Error> {
+  /// Create a new database driver with the default pool configuration.
+  pub fn new(db_url: &str) -> Result<Self> {
+    Self::new_with_config(db_url, PoolConfig::default())
+  }
+
+  /// Create a new database driver with a custom pool configuration.
+  pub fn new_with_config(db_url: &str, config: PoolConfig) -> Result<Self> {
     let manager = ConnectionManager::<D>::new(db_url);
-    let pool = Pool::builder().build(manager).map_err(|err| {
-      std::io::Error::new(std::io::ErrorKind::NotConnected, err)
-    })?;
-    Ok(Self { pool })
-  }
-
-  /// Get a connection from the pool.
-  pub fn get_conn(
-    &self,
-  ) -> Result<PooledConnection<ConnectionManager<D>>, std::io::Error>
-where {
-    self.pool.get().map_err(|err| {
-      std::io::Error::new(
-        std::io::ErrorKind::NotConnected,
-        format!("Failed to get connection: {}", err),
-      )
+    let mut builder = Pool::builder();
+    if let Some(max_size) = config.max_size {
+      builder = builder.max_size(max_size);
+    }
+    if let Some(min_idle) = config.min_idle {
+      builder = builder.min_idle(Some(min_idle));
+    }
+    if let Some(connection_timeout) = config.connection_timeout {
+      builder = builder.connection_timeout(connection_timeout);
+    }
+    if let Some(idle_timeout) = config.idle_timeout {
+      builder = builder.idle_timeout(Some(idle_timeout));
+    }
+    if let Some(max_lifetime) = config.max_lifetime {
+      builder = builder.max_lifetime(Some(max_lifetime));
+    }
+    if let Some(test_on_check_out) = config.test_on_check_out {
+      builder = builder.test_on_check_out(test_on_check_out);
+    }
+    let mut customizer = config.connection_customizer.unwrap_or_default();
+    if let Some(timeout) = config.statement_timeout {
+      customizer =
+        customizer.sql(&statement_timeout_sql::<D::Backend>(timeout));
+    }
+    let is_sqlite =
+      TypeId::of::<D::Backend>() == TypeId::of::<diesel::sqlite::Sqlite>();
+    if let Some(sqlite) = config.sqlite.filter(|_| is_sqlite) {
+      for sql in sqlite.statements() {
+        customizer = customizer.sql(&sql);
+      }
+    }
+    if !customizer.statements.is_empty() {
+      builder = builder.connection_customizer(Box::new(customizer));
+    }
+    let pool = builder
+      .build(manager)
+      .map_err(|err| TurboDieselError::Connection(err.to_string()))?;
+    let default_limit = config.default_limit.unwrap_or(DEFAULT_LIMIT);
+    Ok(
+      Self::from_pool(pool)
+        .with_default_limit(Some(default_limit))
+        .with_max_offset(config.max_offset),
+    )
+  }
+
+  /// Create a new database driver using an existing pool,
+  /// to share it with code using diesel directly.
+  pub fn from_pool(pool: Pool<ConnectionManager<D>>) -> Self {
+    Self {
+      pool,
+      replicas: Arc::from(Vec::new()),
+      next_replica: Arc::new(AtomicUsize::new(0)),
+      default_limit: Some(DEFAULT_LIMIT),
+      max_offset: None,
+      schema: None,
+      #[cfg(feature = "tracing")]
+      slow_query_threshold: None,
+      closed: Arc::new(AtomicBool::new(false)),
+    }
+  }
+
+  /// Send the reads of the model traits (`read_by`, `read_by_pk`,
+  /// `count_by`, `exists_by`, `paginate` and the like) to `replicas`,
+  /// taking them in turn, the writes, `execute`, the raw SQL
+  /// and the transactions staying on the primary pool.
+  ///
+  /// A replica lags behind the primary: a row read right after
+  /// it was written may be missing or stale there.
+  /// Read it inside `transaction`, or with a driver without replicas,
+  /// when the caller must see its own writes.
+  pub fn with_replicas(
+    mut self,
+    replicas: Vec<Pool<ConnectionManager<D>>>,
+  ) -> Self {
+    self.replicas = replicas.into();
+    self
+  }
+
+  /// A driver sharing the pools of this one whose operations run
+  /// on the Postgres schema `schema`, for a schema per tenant.
+  ///
+  /// Each operation runs in a transaction starting with
+  /// `SET LOCAL search_path`, so the connection goes back to the pool
+  /// with its own search path. `execute` gives the connection as is.
+  /// The other backends fail with a `QueryBuilderError`.
+  pub fn with_schema(&self, schema: &str) -> Self {
+    let mut db = self.clone();
+    db.schema = Some(schema.into());
+    db
+  }
+
+  /// Set the limit used by `read_by` when the filter has none,
+  /// `None` to return every row.
+  pub fn with_default_limit(mut self, limit: Option<usize>) -> Self {
+    self.default_limit = limit;
+    self
+  }
+
+  /// The limit used by `read_by` when the filter has none.
+  pub fn default_limit(&self) -> Option<usize> {
+    self.default_limit
+  }
+
+  /// Cap the offset of the reads of a page (`read_by`, `read_by_as`,
+  /// `read_by_into` and `paginate`) at `max` rows, `None` for no maximum.
+  /// A larger offset is clamped to `max` and an offset without a limit,
+  /// its own or the default one, fails with `TurboDieselError::InvalidFilter`:
+  /// the database reads every skipped row, a deep page is a full scan.
+  pub fn with_max_offset(mut self, max: Option<usize>) -> Self {
+    self.max_offset = max;
+    self
+  }
+
+  /// The largest offset of a read, see `with_max_offset`.
+  pub fn max_offset(&self) -> Option<usize> {
+    self.max_offset
+  }
+
+  /// The limit of a read of `filter`, its own or the default one,
+  /// its offset checked against `max_offset`.
+  pub(crate) fn page_limit(
+    &self,
+    filter: &mut GenericFilter,
+  ) -> Result<Option<usize>> {
+    let limit = filter.limit.or(self.default_limit);
+    self.cap_offset(filter, limit)?;
+    Ok(limit)
+  }
+
+  /// Clamp the offset of `filter`, read with `limit`, to `max_offset`.
+  /// Under a maximum an offset needs a limit, a limit too large
+  /// for the backend, the one of `GenericFilter::unlimited` say,
+  /// being none at all.
+  pub(crate) fn cap_offset(
+    &self,
+    filter: &mut GenericFilter,
+    limit: Option<usize>,
+  ) -> Result<()> {
+    let (Some(max), Some(offset)) = (self.max_offset, filter.offset) else {
+      return Ok(());
+    };
+    let unlimited = limit.is_none_or(|limit| i64::try_from(limit).is_err());
+    if offset > 0 && unlimited {
+      return Err(TurboDieselError::InvalidFilter(
+        "an offset needs a limit".to_owned(),
+      ));
+    }
+    filter.offset = Some(offset.min(max));
+    Ok(())
+  }
+
+  /// Log a warning with `tracing` when a query takes longer than `threshold`,
+  /// `None` to disable it (the default).
+  #[cfg(feature = "tracing")]
+  pub fn with_slow_query_threshold(
+    mut self,
+    threshold: Option<Duration>,
+  ) -> Self {
+    self.slow_query_threshold = threshold;
+    self
+  }
+
+  /// Get a connection from the pool,
+  /// a `TurboDieselError::PoolTimeout` when it stays saturated
+  /// for the `connection_timeout` and a `TurboDieselError::Connection`
+  /// once the driver is shut down or when the database can't be reached.
+  pub fn get_conn(&self) -> Result<PooledConnection<ConnectionManager<D>>> {
+    self.check_open()?;
+    checkout(&self.pool, "primary")
+  }
+
+  /// Get a connection from the next replica for a read,
+  /// from the primary pool when the driver has no replica.
+  pub fn get_read_conn(
+    &self,
+  ) -> Result<PooledConnection<ConnectionManager<D>>> {
+    if self.replicas.is_empty() {
+      return self.get_conn();
+    }
+    self.check_open()?;
+    let index =
+      self.next_replica.fetch_add(1, Ordering::Relaxed) % self.replicas.len();
+    checkout(&self.replicas[index], "replica")
+  }
+
+  fn check_open(&self) -> Result<()> {
+    if self.closed.load(Ordering::Acquire) {
+      return Err(TurboDieselError::Connection(
+        "the driver is shut down".to_owned(),
+      ));
+    }
+    Ok(())
+  }
+
+  /// Shut the driver down before the process exits.
+  ///
+  /// The driver and its clones stop giving connections right away,
+  /// the queries not started yet fail with `TurboDieselError::Connection`.
+  /// Then it waits for the connections in use, on the primary
+  /// and the replicas, to be given back so the running queries finish,
+  /// at most `timeout`.
+  /// r2d2 can't close a connection in use, and closes the idle ones
+  /// when the last clone of the driver is dropped.
+  /// A connection still in use after `timeout`, kept by a caller
+  /// of `get_conn` say, is a `TurboDieselError::Connection`.
+  pub async fn shutdown(self, timeout: Duration) -> Result<()> {
+    self.closed.store(true, Ordering::Release);
+    crate::runtime::spawn_blocking(move || {
+      let deadline = std::time::Instant::now() + timeout;
+      loop {
+        let in_use = std::iter::once(&self.pool)
+          .chain(self.replicas.iter())
+          .map(|pool| {
+            let state = pool.state();
+            state.connections - state.idle_connections
+          })
+          .sum::<u32>();
+        if in_use == 0 {
+          return Ok(());
+        }
+        if std::time::Instant::now() >= deadline {
+          return Err(TurboDieselError::Connection(format!(
+            "{in_use} connections still in use after the shutdown timeout"
+          )));
+        }
+        std::thread::sleep(Duration::from_millis(10));
+      }
+    })
+    .await?
+  }
+
+  /// Check the database is reachable
+  /// by running `SELECT 1` on a connection from the pool.
+  pub async fn ping(&self) -> Result<()> {
+    self
+      .execute(|mut conn| diesel::r2d2::R2D2Connection::ping(&mut *conn))
+      .await
+  }
+
+  /// Open `n` connections before serving traffic, on the primary
+  /// and on every replica, so the first requests don't wait for them.
+  ///
+  /// The connections are held together then given back to the pool,
+  /// `pool_state().idle_connections` is at least `n` once it returns.
+  /// `n` is capped by the `max_size` of each pool.
+  /// They're closed again after the `idle_timeout` of the pool
+  /// unless `PoolConfig::min_idle` keeps them.
+  pub async fn warmup(&self, n: u32) -> Result<()> {
+    self.check_open()?;
+    let pools = std::iter::once(self.pool.clone())
+      .chain(self.replicas.iter().cloned())
+      .collect::<Vec<_>>();
+    crate::runtime::spawn_blocking(move || {
+      for pool in pools {
+        let size = n.min(pool.max_size());
+        let conns = (0..size)
+          .map(|_| pool.get().map_err(|err| pool_error(&pool, err)))
+          .collect::<Result<Vec<_>, _>>()?;
+        drop(conns);
+      }
+      Ok(())
     })
+    .await?
+  }
+
+  /// Run a raw SQL statement and return the number of affected rows,
+  /// for what the generic filter can't express.
+  /// The statement is sent as is: never format user input into `sql`,
+  /// pass it in `binds` instead, see `raw_sql_query`.
+  pub async fn execute_sql(
+    &self,
+    sql: &str,
+    binds: Vec<GenericValue>,
+  ) -> Result<usize>
+  where
+    <D as Connection>::Backend: HasSqlType<BigInt>
+      + HasSqlType<diesel::sql_types::Double>
+      + HasSqlType<diesel::sql_types::Bool>
+      + HasSqlType<diesel::sql_types::Text>,
+    i64: ToSql<BigInt, <D as Connection>::Backend>,
+    f64: ToSql<diesel::sql_types::Double, <D as Connection>::Backend>,
+    bool: ToSql<diesel::sql_types::Bool, <D as Connection>::Backend>,
+    String: ToSql<diesel::sql_types::Text, <D as Connection>::Backend>,
+    Option<String>: ToSql<
+      diesel::sql_types::Nullable<diesel::sql_types::Text>,
+      <D as Connection>::Backend,
+    >,
+    RawSqlQuery<<D as Connection>::Backend>: query_builder::QueryId
+      + query_builder::QueryFragment<<D as Connection>::Backend>,
+  {
+    let sql = sql.to_owned();
+    self
+      .execute_op("execute_sql", move |conn| {
+        raw_sql_query(&sql, binds).execute(conn)
+      })
+      .await
+  }
+
+  /// Run a raw SQL query and read the rows as `T`,
+  /// a struct deriving `QueryableByName`.
+  /// Like `execute_sql` the values must be given in `binds`,
+  /// never formatted into `sql`.
+  pub async fn query_sql<T>(
+    &self,
+    sql: &str,
+    binds: Vec<GenericValue>,
+  ) -> Result<Vec<T>>
+  where
+    T: Send + 'static,
+    <D as Connection>::Backend: HasSqlType<BigInt>
+      + HasSqlType<diesel::sql_types::Double>
+      + HasSqlType<diesel::sql_types::Bool>
+      + HasSqlType<diesel::sql_types::Text>,
+    i64: ToSql<BigInt, <D as Connection>::Backend>,
+    f64: ToSql<diesel::sql_types::Double, <D as Connection>::Backend>,
+    bool: ToSql<diesel::sql_types::Bool, <D as Connection>::Backend>,
+    String: ToSql<diesel::sql_types::Text, <D as Connection>::Backend>,
+    Option<String>: ToSql<
+      diesel::sql_types::Nullable<diesel::sql_types::Text>,
+      <D as Connection>::Backend,
+    >,
+    RawSqlQuery<<D as Connection>::Backend>:
+      query_dsl::LoadQuery<'static, D, T>,
+  {
+    let sql = sql.to_owned();
+    self
+      .execute_op("query_sql", move |conn| {
+        raw_sql_query(&sql, binds).load(conn)
+      })
+      .await
+  }
+
+  /// The current state of the primary pool,
+  /// every connection being used means the pool is saturated.
+  pub fn pool_state(&self) -> PoolState {
+    self.pool.state().into()
   }
 
   /// Execute a function with a connection from the pool.
-  pub async fn execute<F, R>(&self, f: F) -> Result<R, diesel::result::Error>
+  /// The connection is given as is, without the schema of `with_schema`,
+  /// use `transaction` on a driver with a schema.
+  pub async fn execute<F, R>(&self, f: F) -> Result<R>
+  where
+    F: FnOnce(
+        PooledConnection<ConnectionManager<D>>,
+      ) -> Result<R, diesel::result::Error>
+      + Send
+      + 'static,
+    R: Send + 'static,
+  {
+    self.run_op("execute", false, f).await
+  }
+
+  /// `execute` giving up when `cancel` completes first,
+  /// with `TurboDieselError::Cancelled`: the future of a cancellation token,
+  /// of a timeout or of the client disconnecting say.
+  /// On Postgres the running statement is cancelled with `pg_cancel_backend`
+  /// from a connection of the pool taken without waiting, none being idle
+  /// it runs to the end, and its connection goes back to the pool
+  /// once it stopped. The caller doesn't wait for the cancel request.
+  /// Finding the process of the connection costs a round trip
+  /// before `f` runs.
+  /// The other backends keep running the statement until it ends,
+  /// only the caller stops waiting. A query that didn't start yet
+  /// doesn't run on any backend.
+  pub async fn execute_cancellable<F, R, C>(&self, cancel: C, f: F) -> Result<R>
+  where
+    F: FnOnce(
+        &mut PooledConnection<ConnectionManager<D>>,
+      ) -> Result<R, diesel::result::Error>
+      + Send
+      + 'static,
+    R: Send + 'static,
+    C: Future<Output = ()>,
+  {
+    let state = Arc::new(Cancellable::default());
+    let running = state.clone();
+    let query = self.run_op("execute_cancellable", false, move |mut conn| {
+      let cancelled = || {
+        diesel::result::Error::QueryBuilderError(Box::new(
+          TurboDieselError::Cancelled,
+        ))
+      };
+      if running.lock().cancelled {
+        return Err(cancelled());
+      }
+      let pid = backend_pid(&mut *conn);
+      {
+        let mut state = running.lock();
+        if state.cancelled {
+          return Err(cancelled());
+        }
+        state.pid = pid;
+      }
+      let res = f(&mut conn);
+      // Keep the connection until the cancellation can't reach it anymore
+      let mut state = running.lock();
+      state.pid = None;
+      while state.cancelling {
+        state = running
+          .cancel_done
+          .wait(state)
+          .unwrap_or_else(|err| err.into_inner());
+      }
+      res
+    });
+    let query = std::pin::pin!(query);
+    let cancel = std::pin::pin!(cancel);
+    if let futures_util::future::Either::Left((res, _)) =
+      futures_util::future::select(query, cancel).await
+    {
+      return res;
+    }
+    let pid = {
+      let mut state = state.lock();
+      state.cancelled = true;
+      state.cancelling = state.pid.is_some();
+      state.pid
+    };
+    if let Some(pid) = pid {
+      // A saturated pool has no connection to spare,
+      // the statement then runs to the end
+      let pool = self.pool.clone();
+      crate::runtime::spawn_blocking_detached(move || {
+        if let Some(mut conn) = pool.try_get() {
+          cancel_backend(&mut *conn, pid);
+        }
+        state.lock().cancelling = false;
+        state.cancel_done.notify_all();
+      });
+    }
+    Err(TurboDieselError::Cancelled)
+  }
+
+  /// `execute` recording the operation of the model traits
+  /// in the span of the `tracing` feature,
+  /// in a transaction on the schema of `with_schema` when there is one.
+  pub(crate) async fn execute_op<F, R>(
+    &self,
+    operation: &'static str,
+    f: F,
+  ) -> Result<R>
+  where
+    F: FnOnce(
+        &mut PooledConnection<ConnectionManager<D>>,
+      ) -> Result<R, diesel::result::Error>
+      + Send
+      + 'static,
+    R: Send + 'static,
+  {
+    let schema = self.schema.clone();
+    self
+      .run_op(operation, false, move |mut conn| {
+        in_schema(&mut conn, schema.as_deref(), f)
+      })
+      .await
+  }
+
+  /// `execute_op` with a connection from a replica, see `get_read_conn`.
+  pub(crate) async fn read_op<F, R>(
+    &self,
+    operation: &'static str,
+    f: F,
+  ) -> Result<R>
+  where
+    F: FnOnce(
+        &mut PooledConnection<ConnectionManager<D>>,
+      ) -> Result<R, diesel::result::Error>
+      + Send
+      + 'static,
+    R: Send + 'static,
+  {
+    let schema = self.schema.clone();
+    self
+      .run_op(operation, true, move |mut conn| {
+        in_schema(&mut conn, schema.as_deref(), f)
+      })
+      .await
+  }
+
+  #[cfg_attr(
+    not(any(feature = "tracing", feature = "metrics")),
+    allow(unused_variables)
+  )]
+  async fn run_op<F, R>(
+    &self,
+    operation: &'static str,
+    read: bool,
+    f: F,
+  ) -> Result<R>
   where
     F: FnOnce(
         PooledConnection<ConnectionManager<D>>,
@@ -70,18 +921,190 @@ where {
     R: Send + 'static,
   {
     let self_ptr = self.clone();
-    ntex::rt::spawn_blocking(move || {
-      let conn = self_ptr
-        .get_conn()
-        .map_err(|_| diesel::result::Error::BrokenTransactionManager)?;
-      f(conn)
+    #[cfg(feature = "tracing")]
+    let span = tracing::info_span!(
+      "turbo_diesel.execute",
+      operation,
+      elapsed_ms = tracing::field::Empty,
+    );
+    crate::runtime::spawn_blocking(move || {
+      #[cfg(feature = "tracing")]
+      let _enter = span.enter();
+      #[cfg(feature = "tracing")]
+      let start = std::time::Instant::now();
+      let conn = if read {
+        self_ptr.get_read_conn()
+      } else {
+        self_ptr.get_conn()
+      };
+      let res = conn.and_then(|conn| Ok(f(conn)?));
+      #[cfg(feature = "tracing")]
+      {
+        let elapsed = start.elapsed();
+        span.record("elapsed_ms", elapsed.as_secs_f64() * 1000.0);
+        if self_ptr
+          .slow_query_threshold
+          .is_some_and(|threshold| elapsed > threshold)
+        {
+          tracing::warn!(?elapsed, "slow query");
+        }
+      }
+      #[cfg(feature = "metrics")]
+      {
+        metrics::counter!(
+          "turbo_diesel_queries_total",
+          "operation" => operation,
+        )
+        .increment(1);
+        if let Err(err) = &res {
+          metrics::counter!(
+            "turbo_diesel_query_errors_total",
+            "operation" => operation,
+            "kind" => err.kind(),
+          )
+          .increment(1);
+        }
+      }
+      res
     })
-    .await
-    .map_err(|_| diesel::result::Error::BrokenTransactionManager)?
+    .await?
+  }
+
+  /// Run a function inside a transaction with a connection from the pool.
+  /// The transaction is committed when `f` returns `Ok`
+  /// and rolled back when it returns `Err`.
+  pub async fn transaction<F, R>(&self, f: F) -> Result<R>
+  where
+    F: FnOnce(
+        &mut PooledConnection<ConnectionManager<D>>,
+      ) -> Result<R, diesel::result::Error>
+      + Send
+      + 'static,
+    R: Send + 'static,
+  {
+    self.transaction_op("transaction", f).await
+  }
+
+  /// Run a function inside a transaction like `transaction`
+  /// with the isolation level `level`.
+  /// Postgres sets it as the first statement of the transaction
+  /// and MySQL right before it, as MySQL can't change it inside.
+  /// SQLite ignores the level, its transactions are always serializable.
+  pub async fn transaction_with_isolation<F, R>(
+    &self,
+    level: IsolationLevel,
+    f: F,
+  ) -> Result<R>
+  where
+    F: FnOnce(
+        &mut PooledConnection<ConnectionManager<D>>,
+      ) -> Result<R, diesel::result::Error>
+      + Send
+      + 'static,
+    R: Send + 'static,
+  {
+    let schema = self.schema.clone();
+    self
+      .run_op("transaction_with_isolation", false, move |mut conn| {
+        let schema = schema.as_deref();
+        let backend = TypeId::of::<<D as Connection>::Backend>();
+        if backend == TypeId::of::<diesel::sqlite::Sqlite>() {
+          return conn.transaction(|conn| {
+            set_search_path(conn, schema)?;
+            f(conn)
+          });
+        }
+        let statement =
+          format!("SET TRANSACTION ISOLATION LEVEL {}", level.as_sql());
+        #[cfg(feature = "mysql")]
+        if backend == TypeId::of::<diesel::mysql::Mysql>() {
+          conn.batch_execute(&statement)?;
+          return conn.transaction(|conn| {
+            set_search_path(conn, schema)?;
+            f(conn)
+          });
+        }
+        conn.transaction(|conn| {
+          conn.batch_execute(&statement)?;
+          set_search_path(conn, schema)?;
+          f(conn)
+        })
+      })
+      .await
+  }
+
+  /// Run a function inside a transaction like `transaction`,
+  /// running it again in a new transaction when it fails with an error
+  /// worth retrying, see `is_retryable`, at most `max_retries` more times.
+  /// `backoff` is waited before the first retry and doubled for each next one,
+  /// the connection being kept meanwhile.
+  /// `f` must be safe to run several times.
+  pub async fn transaction_with_retry<F, R>(
+    &self,
+    max_retries: u32,
+    backoff: Option<Duration>,
+    f: F,
+  ) -> Result<R>
+  where
+    F: Fn(
+        &mut PooledConnection<ConnectionManager<D>>,
+      ) -> Result<R, diesel::result::Error>
+      + Send
+      + 'static,
+    R: Send + 'static,
+  {
+    let schema = self.schema.clone();
+    self
+      .run_op("transaction_with_retry", false, move |mut conn| {
+        let mut retries = 0;
+        let mut backoff = backoff;
+        loop {
+          let res = conn.transaction(|conn| {
+            set_search_path(conn, schema.as_deref())?;
+            f(conn)
+          });
+          match res {
+            Err(err) if retries < max_retries && is_retryable(&err) => {
+              retries += 1;
+              if let Some(delay) = backoff {
+                std::thread::sleep(delay);
+                backoff = delay.checked_mul(2);
+              }
+            }
+            res => return res,
+          }
+        }
+      })
+      .await
+  }
+
+  /// `transaction` recording the operation of the model traits.
+  pub(crate) async fn transaction_op<F, R>(
+    &self,
+    operation: &'static str,
+    f: F,
+  ) -> Result<R>
+  where
+    F: FnOnce(
+        &mut PooledConnection<ConnectionManager<D>>,
+      ) -> Result<R, diesel::result::Error>
+      + Send
+      + 'static,
+    R: Send + 'static,
+  {
+    let schema = self.schema.clone();
+    self
+      .run_op(operation, false, move |mut conn| {
+        conn.transaction(|conn| {
+          set_search_path(conn, schema.as_deref())?;
+          f(conn)
+        })
+      })
+      .await
   }
 
   /// Handle the DbModelCreate
-  pub async fn create<I>(&self, item: &I) -> Result<I, diesel::result::Error>
+  pub async fn create<I>(&self, item: &I) -> Result<I, TurboDieselError>
   where
     I: DbModelCreate + Send + Clone + Sync + 'static,
     I: HasTable + diesel::Insertable<I::Table>,
@@ -94,68 +1117,2633 @@ where {
     I::create(self, item).await
   }
 
-  pub async fn del_by_pk<I, Pk>(&self, pk: &Pk) -> Result<(), diesel::result::Error>
+  /// Handle the DbModelCreate without reading the row back
+  pub async fn create_fast<I>(
+    &self,
+    item: &I,
+  ) -> Result<usize, TurboDieselError>
   where
-    Pk: Sync + ToOwned + std::fmt::Display + ?Sized,
-    <Pk as ToOwned>::Owned: Send + 'static,
-    I: Sized + HasTable + DbModelDelByPk,
-    I::Table: query_dsl::methods::FindDsl<<Pk as ToOwned>::Owned> + HasTable<Table = I::Table>,
-    diesel::helper_types::Find<I::Table, <Pk as ToOwned>::Owned>: query_builder::IntoUpdateTarget,
-    query_builder::DeleteStatement<
-      <diesel::helper_types::Find<I::Table, <Pk as ToOwned>::Owned> as HasTable>::Table,
-      <diesel::helper_types::Find<I::Table, <Pk as ToOwned>::Owned> as query_builder::IntoUpdateTarget>::WhereClause,
-    >: query_builder::QueryFragment<<D as Connection>::Backend> + query_builder::QueryId,
+    I: DbModelCreate + Send + Clone + Sync + 'static,
+    I: HasTable + diesel::Insertable<I::Table>,
+    I::Table: diesel::Table,
+    query_builder::InsertStatement<
+      I::Table,
+      <I as diesel::Insertable<I::Table>>::Values,
+    >: query_dsl::methods::ExecuteDsl<D>,
+  {
+    I::create_fast(self, item).await
+  }
+
+  /// Handle the DbModelCreate without `RETURNING`
+  pub async fn create_and_find<I>(
+    &self,
+    item: &I,
+  ) -> Result<I, TurboDieselError>
+  where
+    I: DbModelCreate + Send + Clone + Sync + 'static,
+    I: HasTable + diesel::Insertable<I::Table>,
+    for<'a> &'a I: Identifiable<Table = I::Table>,
+    I::Table: diesel::Table,
+    query_builder::InsertStatement<
+      I::Table,
+      <I as diesel::Insertable<I::Table>>::Values,
+    >: query_dsl::methods::ExecuteDsl<D>,
+    for<'a> I::Table: query_dsl::methods::FindDsl<<&'a I as Identifiable>::Id>,
+    for<'a> diesel::helper_types::Find<I::Table, <&'a I as Identifiable>::Id>:
+      query_dsl::methods::LimitDsl + RunQueryDsl<D>,
+    for<'a> diesel::helper_types::Limit<
+      diesel::helper_types::Find<I::Table, <&'a I as Identifiable>::Id>,
+    >: query_dsl::LoadQuery<'a, D, I>,
+  {
+    I::create_and_find(self, item).await
+  }
+
+  /// Handle the DbModelCreate for many items
+  pub async fn create_many<I>(
+    &self,
+    items: Vec<I>,
+    chunk_size: Option<usize>,
+  ) -> Result<Vec<I>, TurboDieselError>
+  where
+    D: InsertMany<I>,
+    I: DbModelCreate + Sized + Send + HasTable + 'static,
+    <I::Table as diesel::Table>::AllColumns: ColumnCount,
+  {
+    I::create_many(self, items, chunk_size).await
+  }
+
+  /// Handle the DbModelUpsert
+  pub async fn upsert<I>(&self, item: &I) -> Result<I, TurboDieselError>
+  where
+    I: DbModelUpsert<D> + Send + Clone + Sync + 'static,
+  {
+    I::upsert(self, item).await
+  }
+
+  /// Handle the DbModelUpsert for many items
+  pub async fn upsert_many<I>(
+    &self,
+    items: Vec<I>,
+    chunk_size: Option<usize>,
+  ) -> Result<Vec<I>, TurboDieselError>
+  where
+    I: DbModelUpsert<D> + Send + HasTable + 'static,
+    <I::Table as diesel::Table>::AllColumns: ColumnCount,
+  {
+    I::upsert_many(self, items, chunk_size).await
+  }
+
+  /// Handle the DbModelUpsert skipping the conflicting rows
+  pub async fn create_or_ignore<I>(
+    &self,
+    item: &I,
+  ) -> Result<Option<I>, TurboDieselError>
+  where
+    I: DbModelUpsert<D> + Send + Clone + Sync + 'static,
+  {
+    I::create_or_ignore(self, item).await
+  }
+
+  /// Handle the DbModelUpsert reading the row matching the filter
+  /// or creating it
+  pub async fn find_or_create<I>(
+    &self,
+    filter: &GenericFilter,
+    item: I,
+  ) -> Result<(I, bool), TurboDieselError>
+  where
+    I: DbModelUpsert<D> + Sized + Send + HasTable + DbModelRead + 'static,
+    I::Table: query_dsl::methods::BoxedDsl<
+      'static,
+      <D as Connection>::Backend,
+      Output = BoxedSelect<I::Table, <D as Connection>::Backend>,
+    >,
+    I: FilterableColumns<<D as Connection>::Backend>,
+    <D as diesel::Connection>::Backend:
+      diesel::internal::derives::multiconnection::DieselReserveSpecialization,
+    i64: ToSql<BigInt, <D as Connection>::Backend>,
+    BoxedSelect<I::Table, <D as Connection>::Backend>:
+      query_dsl::methods::ThenOrderDsl<
+        <I::Table as diesel::Table>::PrimaryKey,
+        Output = BoxedSelect<I::Table, <D as Connection>::Backend>,
+      >,
+    BoxedSelect<I::Table, <D as Connection>::Backend>:
+      query_dsl::LoadQuery<'static, D, I>,
+  {
+    I::find_or_create(self, filter, item).await
+  }
+
+  /// Handle the DbModelReadByPk
+  pub async fn read_by_pk<I, Pk>(
+    &self,
+    pk: &Pk,
+  ) -> Result<Option<I>, TurboDieselError>
+  where
+    Pk: Sync + ToOwned + ?Sized,
+    <Pk as ToOwned>::Owned: Send + 'static,
+    I: Sized + Send + HasTable + DbModelReadByPk + 'static,
+    I::Table: query_dsl::methods::FindDsl<<Pk as ToOwned>::Owned>
+      + HasTable<Table = I::Table>,
+    diesel::helper_types::Find<I::Table, <Pk as ToOwned>::Owned>:
+      query_dsl::methods::LimitDsl + RunQueryDsl<D>,
+    diesel::helper_types::Limit<
+      diesel::helper_types::Find<I::Table, <Pk as ToOwned>::Owned>,
+    >: query_dsl::LoadQuery<'static, D, I>,
+  {
+    I::read_by_pk(self, pk).await
+  }
+
+  /// Handle the DbModelReadByPk, failing when there is no row
+  pub async fn read_by_pk_strict<I, Pk>(
+    &self,
+    pk: &Pk,
+  ) -> Result<I, TurboDieselError>
+  where
+    Pk: Sync + ToOwned + ?Sized,
+    <Pk as ToOwned>::Owned: Send + 'static,
+    I: Sized + Send + HasTable + DbModelReadByPk + 'static,
+    I::Table: query_dsl::methods::FindDsl<<Pk as ToOwned>::Owned>
+      + HasTable<Table = I::Table>,
+    diesel::helper_types::Find<I::Table, <Pk as ToOwned>::Owned>:
+      query_dsl::methods::LimitDsl + RunQueryDsl<D>,
+    diesel::helper_types::Limit<
+      diesel::helper_types::Find<I::Table, <Pk as ToOwned>::Owned>,
+    >: query_dsl::LoadQuery<'static, D, I>,
+  {
+    I::read_by_pk_strict(self, pk).await
+  }
+
+  /// Handle the DbModelRead
+  pub async fn read_by<I>(
+    &self,
+    filter: &GenericFilter,
+  ) -> Result<Vec<I>, TurboDieselError>
+  where
+    I: Sized + Send + HasTable + DbModelRead + 'static,
+    I::Table: query_dsl::methods::BoxedDsl<
+      'static,
+      <D as Connection>::Backend,
+      Output = BoxedSelect<I::Table, <D as Connection>::Backend>,
+    >,
+    I: FilterableColumns<<D as Connection>::Backend>,
+    <D as diesel::Connection>::Backend:
+      diesel::internal::derives::multiconnection::DieselReserveSpecialization,
+    i64: ToSql<BigInt, <D as Connection>::Backend>,
+    BoxedSelect<I::Table, <D as Connection>::Backend>:
+      query_dsl::LoadQuery<'static, D, I>,
+  {
+    I::read_by(self, filter).await
+  }
+
+  /// Handle the DbModelRead for some columns
+  pub async fn read_by_as<I, T, ST>(
+    &self,
+    filter: &GenericFilter,
+    columns: &[&str],
+  ) -> Result<Vec<T>, TurboDieselError>
+  where
+    I: Sized + HasTable + DbModelRead + 'static,
+    I::Table: query_dsl::methods::BoxedDsl<
+      'static,
+      <D as Connection>::Backend,
+      Output = BoxedSelect<I::Table, <D as Connection>::Backend>,
+    >,
+    I: FilterableColumns<<D as Connection>::Backend>,
+    <D as diesel::Connection>::Backend:
+      diesel::internal::derives::multiconnection::DieselReserveSpecialization,
+    i64: ToSql<BigInt, <D as Connection>::Backend>,
+    T: Send + 'static,
+    ST: TypedExpressionType + 'static,
+    BoxedSelectAs<I::Table, ST, <D as Connection>::Backend>:
+      query_dsl::LoadQuery<'static, D, T>,
+  {
+    I::read_by_as(self, filter, columns).await
+  }
+
+  /// Handle the DbModelRead for a single value
+  pub async fn read_scalar<I, T, ST>(
+    &self,
+    filter: &GenericFilter,
+    column: &str,
+  ) -> Result<Option<T>, TurboDieselError>
+  where
+    I: Sized + HasTable + DbModelRead + 'static,
+    I::Table: query_dsl::methods::BoxedDsl<
+      'static,
+      <D as Connection>::Backend,
+      Output = BoxedSelect<I::Table, <D as Connection>::Backend>,
+    >,
+    I: FilterableColumns<<D as Connection>::Backend>,
+    <D as diesel::Connection>::Backend:
+      diesel::internal::derives::multiconnection::DieselReserveSpecialization,
+    i64: ToSql<BigInt, <D as Connection>::Backend>,
+    T: Send + 'static,
+    ST: TypedExpressionType + 'static,
+    BoxedSelectAs<I::Table, ST, <D as Connection>::Backend>:
+      query_dsl::LoadQuery<'static, D, T>,
+  {
+    I::read_scalar(self, filter, column).await
+  }
+
+  /// Handle the DbModelRead into another struct
+  pub async fn read_by_into<I, R>(
+    &self,
+    filter: &GenericFilter,
+  ) -> Result<Vec<R>, TurboDieselError>
+  where
+    I: Sized + HasTable + DbModelRead + 'static,
+    I::Table: query_dsl::methods::BoxedDsl<
+      'static,
+      <D as Connection>::Backend,
+      Output = BoxedSelect<I::Table, <D as Connection>::Backend>,
+    >,
+    I: FilterableColumns<<D as Connection>::Backend>,
+    <D as diesel::Connection>::Backend:
+      diesel::internal::derives::multiconnection::DieselReserveSpecialization,
+    i64: ToSql<BigInt, <D as Connection>::Backend>,
+    R: Selectable<<D as Connection>::Backend> + Send + 'static,
+    R::SelectExpression:
+      query_builder::QueryFragment<<D as Connection>::Backend> + Send + 'static,
+    diesel::dsl::AsSelect<R, <D as Connection>::Backend>: TypedExpressionType,
+    BoxedSelectInto<I::Table, R, <D as Connection>::Backend>:
+      query_dsl::LoadQuery<'static, D, R>,
+  {
+    I::read_by_into(self, filter).await
+  }
+
+  /// Render the select of `read_by` for the filter without running it,
+  /// binds included, to debug a filter.
+  /// No connection is taken, a driver on a pool built with `build_unchecked`
+  /// renders it without a database, to check the SQL of a filter in a test.
+  pub fn explain_sql<I>(
+    &self,
+    filter: &GenericFilter,
+  ) -> Result<String, TurboDieselError>
+  where
+    I: Sized + HasTable + DbModelRead + 'static,
+    I::Table: query_dsl::methods::BoxedDsl<
+      'static,
+      <D as Connection>::Backend,
+      Output = BoxedSelect<I::Table, <D as Connection>::Backend>,
+    >,
+    I: FilterableColumns<<D as Connection>::Backend>,
+    <D as diesel::Connection>::Backend:
+      diesel::internal::derives::multiconnection::DieselReserveSpecialization
+        + Default,
+    <<D as Connection>::Backend as diesel::backend::Backend>::QueryBuilder:
+      Default,
+    i64: ToSql<BigInt, <D as Connection>::Backend>,
+    BoxedSelect<I::Table, <D as Connection>::Backend>:
+      query_builder::QueryFragment<<D as Connection>::Backend>,
+  {
+    let mut filter = filter.clone();
+    let limit = self.page_limit(&mut filter)?;
+    let query = gen_page_query::<D, I>(&filter, limit)?;
+    Ok(diesel::debug_query::<<D as Connection>::Backend, _>(&query).to_string())
+  }
+
+  /// Render the delete of `del_by` for the filter without running it.
+  pub fn explain_del_sql<I>(
+    &self,
+    filter: &GenericFilter,
+  ) -> Result<String, TurboDieselError>
+  where
+    I: Sized + HasTable + DbModelDelBy,
+    I: FilterableColumns<<D as Connection>::Backend>,
+    <D as diesel::Connection>::Backend:
+      diesel::internal::derives::multiconnection::DieselReserveSpecialization
+        + Default,
+    <<D as Connection>::Backend as diesel::backend::Backend>::QueryBuilder:
+      Default,
+    query_builder::BoxedDeleteStatement<
+      'static,
+      <D as Connection>::Backend,
+      I::Table,
+    >: query_builder::QueryFragment<<D as Connection>::Backend>,
+  {
+    let query = I::gen_del_query::<D>(filter)?;
+    Ok(diesel::debug_query::<<D as Connection>::Backend, _>(&query).to_string())
+  }
+
+  /// Build the select of `read_by` for the filter without running it,
+  /// the SQL and the operands of the filter apart, for a query builder say,
+  /// see `build_sql`.
+  pub fn build_read_sql<I>(
+    &self,
+    filter: &GenericFilter,
+  ) -> Result<(String, Vec<SqlValue>), TurboDieselError>
+  where
+    I: Sized + HasTable + DbModelRead + 'static,
+    I::Table: query_dsl::methods::BoxedDsl<
+      'static,
+      <D as Connection>::Backend,
+      Output = BoxedSelect<I::Table, <D as Connection>::Backend>,
+    >,
+    I: FilterableColumns<<D as Connection>::Backend>,
+    <D as diesel::Connection>::Backend:
+      diesel::internal::derives::multiconnection::DieselReserveSpecialization
+        + Default,
+    <<D as Connection>::Backend as diesel::backend::Backend>::QueryBuilder:
+      Default,
+    i64: ToSql<BigInt, <D as Connection>::Backend>,
+    BoxedSelect<I::Table, <D as Connection>::Backend>:
+      query_builder::QueryFragment<<D as Connection>::Backend>,
+  {
+    let mut filter = filter.clone();
+    let limit = self.page_limit(&mut filter)?;
+    let query = gen_page_query::<D, I>(&filter, limit)?;
+    Ok(build_sql(&query, &filter)?)
+  }
+
+  /// Build the delete of `del_by` for the filter without running it,
+  /// to review it before, refused without a where clause like `del_by`.
+  pub fn build_del_sql<I>(
+    &self,
+    filter: &GenericFilter,
+  ) -> Result<(String, Vec<SqlValue>), TurboDieselError>
+  where
+    I: Sized + HasTable + DbModelDelBy,
+    I: FilterableColumns<<D as Connection>::Backend>,
+    <D as diesel::Connection>::Backend:
+      diesel::internal::derives::multiconnection::DieselReserveSpecialization
+        + Default,
+    <<D as Connection>::Backend as diesel::backend::Backend>::QueryBuilder:
+      Default,
+    query_builder::BoxedDeleteStatement<
+      'static,
+      <D as Connection>::Backend,
+      I::Table,
+    >: query_builder::QueryFragment<<D as Connection>::Backend>,
+  {
+    if !filter.has_conditions() {
+      return Err(
+        diesel::result::Error::QueryBuilderError(
+          "Refusing to delete without a where clause, use del_all instead"
+            .into(),
+        )
+        .into(),
+      );
+    }
+    let query = I::gen_del_query::<D>(filter)?;
+    Ok(build_sql(&query, filter)?)
+  }
+
+  /// Build the update of `update_by` for the filter without running it,
+  /// refused without a where clause like `update_by`.
+  pub fn build_update_sql<I, C>(
+    &self,
+    filter: &GenericFilter,
+    changeset: C,
+  ) -> Result<(String, Vec<SqlValue>), TurboDieselError>
+  where
+    C: AsChangeset<Target = I::Table>,
+    I: Sized + HasTable + FilterableColumns<<D as Connection>::Backend>,
+    I::Table:
+      query_builder::IntoUpdateTarget + HasTable<Table = I::Table> + 'static,
+    query_builder::UpdateStatement<
+      I::Table,
+      <I::Table as query_builder::IntoUpdateTarget>::WhereClause,
+    >: query_dsl::methods::BoxedDsl<
+      'static,
+      <D as Connection>::Backend,
+      Output = query_builder::BoxedUpdateStatement<
+        'static,
+        <D as Connection>::Backend,
+        I::Table,
+      >,
+    >,
+    query_builder::BoxedUpdateStatement<
+      'static,
+      <D as Connection>::Backend,
+      I::Table,
+      C::Changeset,
+    >: query_builder::AsQuery
+      + query_builder::QueryFragment<<D as Connection>::Backend>,
+    <D as diesel::Connection>::Backend:
+      diesel::internal::derives::multiconnection::DieselReserveSpecialization
+        + Default,
+    <<D as Connection>::Backend as diesel::backend::Backend>::QueryBuilder:
+      Default,
+  {
+    if !filter.has_conditions() {
+      return Err(
+        diesel::result::Error::QueryBuilderError(
+          "Refusing to update without a where clause, use update_all instead"
+            .into(),
+        )
+        .into(),
+      );
+    }
+    let query = diesel::update(<I::Table as HasTable>::table()).into_boxed();
+    let query = apply_where::<I, _, _>(query, filter)?.set(changeset);
+    Ok(build_sql(&query, filter)?)
+  }
+
+  /// Handle the DbModelRead with keyset pagination
+  pub async fn paginate_keyset<I>(
+    &self,
+    filter: &GenericFilter,
+    after: Option<Cursor>,
+    page_size: usize,
+  ) -> Result<KeysetPage<I>, TurboDieselError>
+  where
+    I: Sized + Send + HasTable + DbModelRead + 'static,
+    I::Table: query_dsl::methods::BoxedDsl<
+      'static,
+      <D as Connection>::Backend,
+      Output = BoxedSelect<I::Table, <D as Connection>::Backend>,
+    >,
+    I: FilterableColumns<<D as Connection>::Backend>,
+    <D as diesel::Connection>::Backend:
+      diesel::internal::derives::multiconnection::DieselReserveSpecialization,
+    i64: ToSql<BigInt, <D as Connection>::Backend>,
+    <I::Table as diesel::Table>::PrimaryKey: Column,
+    <I::Table as diesel::Table>::AllColumns:
+      query_builder::QueryFragment<<D as Connection>::Backend> + Send + 'static,
+    KeysetSqlType<I::Table>: TypedExpressionType,
+    KeysetSelect<I::Table, <D as Connection>::Backend>:
+      query_dsl::LoadQuery<'static, D, (I, String)>,
+  {
+    I::paginate_keyset(self, filter, after, page_size).await
+  }
+
+  /// Handle the DbModelRead streaming the rows by chunks
+  pub fn read_stream<I>(
+    &self,
+    filter: &GenericFilter,
+    chunk_size: Option<usize>,
+  ) -> impl Stream<Item = Result<I, TurboDieselError>> + Send + use<I, D>
+  where
+    I: Sized + Send + HasTable + DbModelRead + 'static,
+    I::Table: query_dsl::methods::BoxedDsl<
+      'static,
+      <D as Connection>::Backend,
+      Output = BoxedSelect<I::Table, <D as Connection>::Backend>,
+    >,
+    I: FilterableColumns<<D as Connection>::Backend>,
+    <D as diesel::Connection>::Backend:
+      diesel::internal::derives::multiconnection::DieselReserveSpecialization,
+    i64: ToSql<BigInt, <D as Connection>::Backend>,
+    <I::Table as diesel::Table>::PrimaryKey: Column,
+    <I::Table as diesel::Table>::AllColumns:
+      query_builder::QueryFragment<<D as Connection>::Backend> + Send + 'static,
+    KeysetSqlType<I::Table>: TypedExpressionType,
+    KeysetSelect<I::Table, <D as Connection>::Backend>:
+      query_dsl::LoadQuery<'static, D, (I, String)>,
+  {
+    I::read_stream(self, filter, chunk_size)
+  }
+
+  /// Handle the DbModelRead for a single row
+  pub async fn read_one<I>(
+    &self,
+    filter: &GenericFilter,
+  ) -> Result<Option<I>, TurboDieselError>
+  where
+    I: Sized + Send + HasTable + DbModelRead + 'static,
+    I::Table: query_dsl::methods::BoxedDsl<
+      'static,
+      <D as Connection>::Backend,
+      Output = BoxedSelect<I::Table, <D as Connection>::Backend>,
+    >,
+    I: FilterableColumns<<D as Connection>::Backend>,
+    <D as diesel::Connection>::Backend:
+      diesel::internal::derives::multiconnection::DieselReserveSpecialization,
+    i64: ToSql<BigInt, <D as Connection>::Backend>,
+    BoxedSelect<I::Table, <D as Connection>::Backend>:
+      query_dsl::methods::ThenOrderDsl<
+        <I::Table as diesel::Table>::PrimaryKey,
+        Output = BoxedSelect<I::Table, <D as Connection>::Backend>,
+      >,
+    BoxedSelect<I::Table, <D as Connection>::Backend>:
+      query_dsl::LoadQuery<'static, D, I>,
+  {
+    I::read_one(self, filter).await
+  }
+
+  /// Handle the DbModelCount
+  pub async fn count_by<I>(
+    &self,
+    filter: &GenericFilter,
+  ) -> Result<i64, TurboDieselError>
+  where
+    I: Sized + Send + HasTable + DbModelCount + 'static,
+    I::Table: query_dsl::methods::BoxedDsl<
+      'static,
+      <D as Connection>::Backend,
+      Output = BoxedSelect<I::Table, <D as Connection>::Backend>,
+    >,
+    I: FilterableColumns<<D as Connection>::Backend>,
+    <D as diesel::Connection>::Backend:
+      diesel::internal::derives::multiconnection::DieselReserveSpecialization,
+    BoxedSelect<I::Table, <D as Connection>::Backend>:
+      query_dsl::methods::SelectDsl<diesel::dsl::CountStar>,
+    diesel::dsl::Select<
+      BoxedSelect<I::Table, <D as Connection>::Backend>,
+      diesel::dsl::CountStar,
+    >: query_dsl::LoadQuery<'static, D, i64>,
+  {
+    I::count_by(self, filter).await
+  }
+
+  /// Handle the DbModelCount for an aggregate
+  pub async fn aggregate<I, R>(
+    &self,
+    filter: &GenericFilter,
+    function: AggregateFn,
+    column: &str,
+  ) -> Result<Option<R>, TurboDieselError>
+  where
+    I: Sized + Send + HasTable + DbModelCount + 'static,
+    I::Table: query_dsl::methods::BoxedDsl<
+      'static,
+      <D as Connection>::Backend,
+      Output = BoxedSelect<I::Table, <D as Connection>::Backend>,
+    >,
+    I: FilterableColumns<<D as Connection>::Backend>,
+    <D as diesel::Connection>::Backend:
+      diesel::internal::derives::multiconnection::DieselReserveSpecialization,
+    R: AggregateValue + Send + 'static,
+    diesel::sql_types::Nullable<R::SqlType>: TypedExpressionType,
+    AggregateSelect<I::Table, R, <D as Connection>::Backend>:
+      query_dsl::LoadQuery<'static, D, Option<R>>,
+  {
+    I::aggregate(self, filter, function, column).await
+  }
+
+  /// Handle the DbModelCount for the non null values of a column
+  pub async fn count_by_column<I>(
+    &self,
+    filter: &GenericFilter,
+    column: &str,
+  ) -> Result<i64, TurboDieselError>
+  where
+    I: Sized + Send + HasTable + DbModelCount + 'static,
+    I::Table: query_dsl::methods::BoxedDsl<
+      'static,
+      <D as Connection>::Backend,
+      Output = BoxedSelect<I::Table, <D as Connection>::Backend>,
+    >,
+    I: FilterableColumns<<D as Connection>::Backend>,
+    <D as diesel::Connection>::Backend:
+      diesel::internal::derives::multiconnection::DieselReserveSpecialization,
+    AggregateSelect<I::Table, i64, <D as Connection>::Backend>:
+      query_dsl::LoadQuery<'static, D, Option<i64>>,
+  {
+    I::count_by_column(self, filter, column).await
+  }
+
+  /// Handle the DbModelCount for the distinct values of a column
+  pub async fn count_distinct<I>(
+    &self,
+    filter: &GenericFilter,
+    column: &str,
+  ) -> Result<i64, TurboDieselError>
+  where
+    I: Sized + Send + HasTable + DbModelCount + 'static,
+    I::Table: query_dsl::methods::BoxedDsl<
+      'static,
+      <D as Connection>::Backend,
+      Output = BoxedSelect<I::Table, <D as Connection>::Backend>,
+    >,
+    I: FilterableColumns<<D as Connection>::Backend>,
+    <D as diesel::Connection>::Backend:
+      diesel::internal::derives::multiconnection::DieselReserveSpecialization,
+    AggregateSelect<I::Table, i64, <D as Connection>::Backend>:
+      query_dsl::LoadQuery<'static, D, Option<i64>>,
+  {
+    I::count_distinct(self, filter, column).await
+  }
+
+  /// Handle the DbModelCount for the counts per value of a column
+  pub async fn group_count<I>(
+    &self,
+    filter: &GenericFilter,
+    column: &str,
+  ) -> Result<Vec<(GenericValue, i64)>, TurboDieselError>
+  where
+    I: Sized + Send + HasTable + DbModelCount + 'static,
+    I::Table: query_dsl::methods::BoxedDsl<
+      'static,
+      <D as Connection>::Backend,
+      Output = BoxedSelect<I::Table, <D as Connection>::Backend>,
+    >,
+    I: FilterableColumns<<D as Connection>::Backend>,
+    <D as diesel::Connection>::Backend:
+      diesel::internal::derives::multiconnection::DieselReserveSpecialization,
+    GroupCountQuery<I::Table, <D as Connection>::Backend>:
+      query_dsl::LoadQuery<'static, D, (Option<String>, i64)>,
+  {
+    I::group_count(self, filter, column).await
+  }
+
+  /// Handle the DbModelCount for a page of rows
+  pub async fn paginate<I>(
+    &self,
+    filter: &GenericFilter,
+  ) -> Result<Page<I>, TurboDieselError>
+  where
+    I: Sized + Send + HasTable + DbModelCount + 'static,
+    I::Table: query_dsl::methods::BoxedDsl<
+      'static,
+      <D as Connection>::Backend,
+      Output = BoxedSelect<I::Table, <D as Connection>::Backend>,
+    >,
+    I: FilterableColumns<<D as Connection>::Backend>,
+    <D as diesel::Connection>::Backend:
+      diesel::internal::derives::multiconnection::DieselReserveSpecialization,
+    i64: ToSql<BigInt, <D as Connection>::Backend>,
+    BoxedSelect<I::Table, <D as Connection>::Backend>:
+      query_dsl::LoadQuery<'static, D, I>
+        + query_dsl::methods::SelectDsl<diesel::dsl::CountStar>,
+    diesel::dsl::Select<
+      BoxedSelect<I::Table, <D as Connection>::Backend>,
+      diesel::dsl::CountStar,
+    >: query_dsl::LoadQuery<'static, D, i64>,
+  {
+    I::paginate(self, filter).await
+  }
+
+  /// Handle the DbModelExists
+  pub async fn exists_by<I>(
+    &self,
+    filter: &GenericFilter,
+  ) -> Result<bool, TurboDieselError>
+  where
+    I: Sized + Send + HasTable + DbModelExists + 'static,
+    I::Table: query_dsl::methods::BoxedDsl<
+      'static,
+      <D as Connection>::Backend,
+      Output = BoxedSelect<I::Table, <D as Connection>::Backend>,
+    >,
+    I: FilterableColumns<<D as Connection>::Backend>,
+    <D as diesel::Connection>::Backend:
+      diesel::internal::derives::multiconnection::DieselReserveSpecialization,
+    diesel::dsl::BareSelect<
+      Exists<BoxedSelect<I::Table, <D as Connection>::Backend>>,
+    >: query_dsl::LoadQuery<'static, D, bool>,
+  {
+    I::exists_by(self, filter).await
+  }
+
+  /// Handle the DbModelUpdate
+  pub async fn update_by_pk<I, Pk, C>(
+    &self,
+    pk: &Pk,
+    changeset: C,
+  ) -> Result<I, TurboDieselError>
+  where
+    Pk: Sync + ToOwned + ?Sized,
+    <Pk as ToOwned>::Owned: Send + 'static,
+    C: AsChangeset<Target = I::Table> + Send + 'static,
+    I: Sized + Send + HasTable + DbModelUpdate + 'static,
+    I::Table: query_dsl::methods::FindDsl<<Pk as ToOwned>::Owned>
+      + HasTable<Table = I::Table>,
+    diesel::helper_types::Find<I::Table, <Pk as ToOwned>::Owned>:
+      query_builder::IntoUpdateTarget + HasTable<Table = I::Table>,
+    diesel::helper_types::Update<
+      diesel::helper_types::Find<I::Table, <Pk as ToOwned>::Owned>,
+      C,
+    >: query_builder::AsQuery + query_dsl::LoadQuery<'static, D, I>,
+  {
+    I::update_by_pk(self, pk, changeset).await
+  }
+
+  /// Handle the DbModelUpdate adding to a numeric column
+  pub async fn increment<I, Pk>(
+    &self,
+    pk: &Pk,
+    column: &str,
+    delta: i64,
+  ) -> Result<I, TurboDieselError>
+  where
+    Pk: Sync + ToOwned + ?Sized,
+    <Pk as ToOwned>::Owned: Send + 'static,
+    I: Sized
+      + Send
+      + HasTable
+      + DbModelUpdate
+      + FilterableColumns<<D as Connection>::Backend>
+      + 'static,
+    I::Table: query_dsl::methods::FindDsl<<Pk as ToOwned>::Owned>
+      + HasTable<Table = I::Table>,
+    diesel::helper_types::Find<I::Table, <Pk as ToOwned>::Owned>:
+      query_builder::IntoUpdateTarget + HasTable<Table = I::Table>,
+    diesel::helper_types::Update<
+      diesel::helper_types::Find<I::Table, <Pk as ToOwned>::Owned>,
+      Increment<I::Table, <D as Connection>::Backend>,
+    >: query_builder::AsQuery + query_dsl::LoadQuery<'static, D, I>,
+  {
+    I::increment(self, pk, column, delta).await
+  }
+
+  /// Handle the DbModelUpdate for the rows matching a filter
+  pub async fn update_by<I, C>(
+    &self,
+    filter: &GenericFilter,
+    changeset: C,
+  ) -> Result<usize, TurboDieselError>
+  where
+    C: AsChangeset<Target = I::Table> + Send + 'static,
+    I: Sized + HasTable + DbModelUpdate,
+    I: FilterableColumns<<D as Connection>::Backend>,
+    I::Table:
+      query_builder::IntoUpdateTarget + HasTable<Table = I::Table> + 'static,
+    query_builder::UpdateStatement<
+      I::Table,
+      <I::Table as query_builder::IntoUpdateTarget>::WhereClause,
+    >: query_dsl::methods::BoxedDsl<
+      'static,
+      <D as Connection>::Backend,
+      Output = query_builder::BoxedUpdateStatement<
+        'static,
+        <D as Connection>::Backend,
+        I::Table,
+      >,
+    >,
+    query_builder::BoxedUpdateStatement<
+      'static,
+      <D as Connection>::Backend,
+      I::Table,
+      C::Changeset,
+    >: query_builder::AsQuery
+      + query_builder::QueryFragment<<D as Connection>::Backend>
+      + query_builder::QueryId,
+    <D as diesel::Connection>::Backend:
+      diesel::internal::derives::multiconnection::DieselReserveSpecialization,
+  {
+    I::update_by(self, filter, changeset).await
+  }
+
+  /// Handle the DbModelUpdate returning the updated rows
+  pub async fn update_by_returning<I, C>(
+    &self,
+    filter: &GenericFilter,
+    changeset: C,
+  ) -> Result<Vec<I>, TurboDieselError>
+  where
+    C: AsChangeset<Target = I::Table> + Send + 'static,
+    I: Sized + Send + HasTable + DbModelUpdate + 'static,
+    I: FilterableColumns<<D as Connection>::Backend>,
+    I::Table:
+      query_builder::IntoUpdateTarget + HasTable<Table = I::Table> + 'static,
+    query_builder::UpdateStatement<
+      I::Table,
+      <I::Table as query_builder::IntoUpdateTarget>::WhereClause,
+    >: query_dsl::methods::BoxedDsl<
+      'static,
+      <D as Connection>::Backend,
+      Output = query_builder::BoxedUpdateStatement<
+        'static,
+        <D as Connection>::Backend,
+        I::Table,
+      >,
+    >,
+    query_builder::BoxedUpdateStatement<
+      'static,
+      <D as Connection>::Backend,
+      I::Table,
+      C::Changeset,
+    >: query_builder::AsQuery + query_dsl::LoadQuery<'static, D, I>,
+    <D as diesel::Connection>::Backend:
+      diesel::internal::derives::multiconnection::DieselReserveSpecialization,
+  {
+    I::update_by_returning(self, filter, changeset).await
+  }
+
+  /// Handle the DbModelUpdate setting a column on the rows matching a filter
+  pub async fn set_field<I>(
+    &self,
+    filter: &GenericFilter,
+    column: &str,
+    value: GenericValue,
+  ) -> Result<usize, TurboDieselError>
+  where
+    I: Sized + HasTable + DbModelUpdate,
+    I: FilterableColumns<<D as Connection>::Backend>,
+    I::Table:
+      query_builder::IntoUpdateTarget + HasTable<Table = I::Table> + 'static,
+    query_builder::UpdateStatement<
+      I::Table,
+      <I::Table as query_builder::IntoUpdateTarget>::WhereClause,
+    >: query_dsl::methods::BoxedDsl<
+      'static,
+      <D as Connection>::Backend,
+      Output = query_builder::BoxedUpdateStatement<
+        'static,
+        <D as Connection>::Backend,
+        I::Table,
+      >,
+    >,
+    query_builder::BoxedUpdateStatement<
+      'static,
+      <D as Connection>::Backend,
+      I::Table,
+      SetField<I::Table, <D as Connection>::Backend>,
+    >: query_builder::AsQuery
+      + query_builder::QueryFragment<<D as Connection>::Backend>
+      + query_builder::QueryId,
+    <D as diesel::Connection>::Backend:
+      diesel::internal::derives::multiconnection::DieselReserveSpecialization,
+  {
+    I::set_field(self, filter, column, value).await
+  }
+
+  /// Update every row of the table and return how many were updated.
+  /// Use it instead of `update_by` when you really mean to update every row,
+  /// `Confirm::Yes` says so.
+  pub async fn update_all<I, C>(
+    &self,
+    changeset: C,
+    confirm: Confirm,
+  ) -> Result<usize, TurboDieselError>
+  where
+    C: AsChangeset<Target = I::Table> + Send + 'static,
+    I: Sized + HasTable + DbModelUpdate,
+    I::Table:
+      query_builder::IntoUpdateTarget + HasTable<Table = I::Table> + 'static,
+    query_builder::UpdateStatement<
+      I::Table,
+      <I::Table as query_builder::IntoUpdateTarget>::WhereClause,
+      C::Changeset,
+    >: query_builder::AsQuery
+      + query_builder::QueryFragment<<D as Connection>::Backend>
+      + query_builder::QueryId,
+  {
+    I::update_all(self, changeset, confirm).await
+  }
+
+  pub async fn del_by_pk<I, Pk>(
+    &self,
+    pk: &Pk,
+  ) -> Result<usize, TurboDieselError>
+  where
+    Pk: Sync + ToOwned + ?Sized,
+    <Pk as ToOwned>::Owned: Send + 'static,
+    I: Sized + HasTable + DbModelDelByPk,
+    I::Table: query_dsl::methods::FindDsl<<Pk as ToOwned>::Owned> + HasTable<Table = I::Table>,
+    diesel::helper_types::Find<I::Table, <Pk as ToOwned>::Owned>: query_builder::IntoUpdateTarget,
+    query_builder::DeleteStatement<
+      <diesel::helper_types::Find<I::Table, <Pk as ToOwned>::Owned> as HasTable>::Table,
+      <diesel::helper_types::Find<I::Table, <Pk as ToOwned>::Owned> as query_builder::IntoUpdateTarget>::WhereClause,
+    >: query_builder::QueryFragment<<D as Connection>::Backend> + query_builder::QueryId,
+  {
+    I::del_by_pk(self, pk).await
+  }
+
+  /// Handle the DbModelDelByPk, failing when there was no row
+  pub async fn del_by_pk_strict<I, Pk>(
+    &self,
+    pk: &Pk,
+  ) -> Result<(), TurboDieselError>
+  where
+    Pk: Sync + ToOwned + ?Sized,
+    <Pk as ToOwned>::Owned: Send + 'static,
+    I: Sized + HasTable + DbModelDelByPk,
+    I::Table: query_dsl::methods::FindDsl<<Pk as ToOwned>::Owned>
+      + HasTable<Table = I::Table>,
+    diesel::helper_types::Find<I::Table, <Pk as ToOwned>::Owned>:
+      query_builder::IntoUpdateTarget,
+    query_builder::DeleteStatement<
+      <diesel::helper_types::Find<I::Table, <Pk as ToOwned>::Owned> as HasTable>::Table,
+      <diesel::helper_types::Find<I::Table, <Pk as ToOwned>::Owned> as query_builder::IntoUpdateTarget>::WhereClause,
+    >: query_builder::QueryFragment<<D as Connection>::Backend>
+      + query_builder::QueryId,
+  {
+    I::del_by_pk_strict(self, pk).await
+  }
+
+  /// Handle the DbModelDelByPk for many primary keys
+  pub async fn del_by_pks<I, Pk>(
+    &self,
+    pks: &[Pk],
+  ) -> Result<usize, TurboDieselError>
+  where
+    Pk: Sync + ToOwned,
+    <Pk as ToOwned>::Owned: Send + 'static,
+    I: Sized + HasTable + DbModelDelByPk,
+    I::Table: query_dsl::methods::FilterDsl<
+        diesel::dsl::EqAny<
+          <I::Table as diesel::Table>::PrimaryKey,
+          Vec<<Pk as ToOwned>::Owned>,
+        >,
+      > + HasTable<Table = I::Table>,
+    <<I::Table as diesel::Table>::PrimaryKey as Expression>::SqlType:
+      diesel::sql_types::SingleValue,
+    Vec<<Pk as ToOwned>::Owned>: diesel::internal::derives::multiconnection::array_comparison::AsInExpression<
+      <<I::Table as diesel::Table>::PrimaryKey as Expression>::SqlType,
+    >,
+    diesel::dsl::Filter<
+      I::Table,
+      diesel::dsl::EqAny<
+        <I::Table as diesel::Table>::PrimaryKey,
+        Vec<<Pk as ToOwned>::Owned>,
+      >,
+    >: query_builder::IntoUpdateTarget,
+    query_builder::DeleteStatement<
+      <diesel::dsl::Filter<
+        I::Table,
+        diesel::dsl::EqAny<
+          <I::Table as diesel::Table>::PrimaryKey,
+          Vec<<Pk as ToOwned>::Owned>,
+        >,
+      > as HasTable>::Table,
+      <diesel::dsl::Filter<
+        I::Table,
+        diesel::dsl::EqAny<
+          <I::Table as diesel::Table>::PrimaryKey,
+          Vec<<Pk as ToOwned>::Owned>,
+        >,
+      > as query_builder::IntoUpdateTarget>::WhereClause,
+    >: query_builder::QueryFragment<<D as Connection>::Backend>
+      + query_builder::QueryId,
+  {
+    I::del_by_pks(self, pks).await
+  }
+
+  pub async fn del_by<I>(
+    &self,
+    filter: &GenericFilter,
+  ) -> Result<usize, TurboDieselError>
+  where
+    I: Sized + HasTable + DbModelDelBy,
+    I: FilterableColumns<<D as Connection>::Backend>,
+    <I as HasTable>::Table: query_builder::QueryId + 'static,
+    <<I as HasTable>::Table as diesel::QuerySource>::FromClause:
+      diesel::query_builder::QueryFragment<<D as Connection>::Backend>,
+    <<I as HasTable>::Table as diesel::QuerySource>::FromClause:
+      diesel::query_builder::QueryFragment<<D as Connection>::Backend>,
+    <D as diesel::Connection>::Backend:
+      diesel::internal::derives::multiconnection::DieselReserveSpecialization,
+  {
+    I::del_by(self, filter).await
+  }
+
+  /// Handle the DbModelDelBy returning the deleted rows
+  pub async fn del_by_returning<I>(
+    &self,
+    filter: &GenericFilter,
+  ) -> Result<Vec<I>, TurboDieselError>
+  where
+    I: Sized + Send + HasTable + DbModelDelBy + 'static,
+    I: FilterableColumns<<D as Connection>::Backend>,
+    <I as HasTable>::Table: query_builder::QueryId + 'static,
+    query_builder::BoxedDeleteStatement<
+      'static,
+      <D as Connection>::Backend,
+      I::Table,
+    >: query_dsl::LoadQuery<'static, D, I>,
+    <D as diesel::Connection>::Backend:
+      diesel::internal::derives::multiconnection::DieselReserveSpecialization,
+  {
+    I::del_by_returning(self, filter).await
+  }
+
+  /// Handle the DbModelSoftDelete
+  pub async fn soft_del_by_pk<I, Pk>(
+    &self,
+    pk: &Pk,
+  ) -> Result<usize, TurboDieselError>
+  where
+    Pk: Sync + ToOwned + ?Sized,
+    <Pk as ToOwned>::Owned: Send + 'static,
+    I: Sized + DbModelSoftDelete,
+    I::Table: query_dsl::methods::FindDsl<<Pk as ToOwned>::Owned>
+      + HasTable<Table = I::Table>,
+    diesel::helper_types::Find<I::Table, <Pk as ToOwned>::Owned>:
+      query_builder::IntoUpdateTarget + HasTable<Table = I::Table>,
+    diesel::dsl::now: diesel::expression::AsExpression<<I::DeletedAt as Expression>::SqlType>,
+    SoftDeleteChangeset<I>: AsChangeset<Target = I::Table>,
+    query_builder::UpdateStatement<
+      I::Table,
+      <diesel::helper_types::Find<I::Table, <Pk as ToOwned>::Owned> as query_builder::IntoUpdateTarget>::WhereClause,
+      <SoftDeleteChangeset<I> as AsChangeset>::Changeset,
+    >: query_builder::AsQuery
+      + query_builder::QueryFragment<<D as Connection>::Backend>
+      + query_builder::QueryId,
+  {
+    I::soft_del_by_pk(self, pk).await
+  }
+
+  /// Handle the DbModelSoftDelete with a filter
+  pub async fn soft_del_by<I>(
+    &self,
+    filter: &GenericFilter,
+  ) -> Result<usize, TurboDieselError>
+  where
+    I:
+      Sized + DbModelSoftDelete + FilterableColumns<<D as Connection>::Backend>,
+    I::Table:
+      query_builder::IntoUpdateTarget + HasTable<Table = I::Table> + 'static,
+    diesel::dsl::now:
+      diesel::expression::AsExpression<<I::DeletedAt as Expression>::SqlType>,
+    SoftDeleteChangeset<I>: AsChangeset<Target = I::Table>,
+    query_builder::UpdateStatement<
+      I::Table,
+      <I::Table as query_builder::IntoUpdateTarget>::WhereClause,
+    >: query_dsl::methods::BoxedDsl<
+      'static,
+      <D as Connection>::Backend,
+      Output = query_builder::BoxedUpdateStatement<
+        'static,
+        <D as Connection>::Backend,
+        I::Table,
+      >,
+    >,
+    query_builder::BoxedUpdateStatement<
+      'static,
+      <D as Connection>::Backend,
+      I::Table,
+      <SoftDeleteChangeset<I> as AsChangeset>::Changeset,
+    >: query_builder::AsQuery
+      + query_builder::QueryFragment<<D as Connection>::Backend>
+      + query_builder::QueryId,
+    <D as diesel::Connection>::Backend:
+      diesel::internal::derives::multiconnection::DieselReserveSpecialization,
+  {
+    I::soft_del_by(self, filter).await
+  }
+
+  /// Handle the DbModelSoftDelete restore
+  pub async fn restore_by_pk<I, Pk>(
+    &self,
+    pk: &Pk,
+  ) -> Result<usize, TurboDieselError>
+  where
+    Pk: Sync + ToOwned + ?Sized,
+    <Pk as ToOwned>::Owned: Send + 'static,
+    I: Sized + DbModelSoftDelete,
+    I::Table: query_dsl::methods::FindDsl<<Pk as ToOwned>::Owned>
+      + HasTable<Table = I::Table>,
+    diesel::helper_types::Find<I::Table, <Pk as ToOwned>::Owned>:
+      query_builder::IntoUpdateTarget + HasTable<Table = I::Table>,
+    diesel::expression::SqlLiteral<<I::DeletedAt as Expression>::SqlType>:
+      diesel::expression::AsExpression<
+        <I::DeletedAt as Expression>::SqlType,
+      >,
+    RestoreChangeset<I>: AsChangeset<Target = I::Table>,
+    query_builder::UpdateStatement<
+      I::Table,
+      <diesel::helper_types::Find<I::Table, <Pk as ToOwned>::Owned> as query_builder::IntoUpdateTarget>::WhereClause,
+      <RestoreChangeset<I> as AsChangeset>::Changeset,
+    >: query_builder::AsQuery
+      + query_builder::QueryFragment<<D as Connection>::Backend>
+      + query_builder::QueryId,
+  {
+    I::restore_by_pk(self, pk).await
+  }
+
+  /// Handle the Timestamped create
+  pub async fn create_timestamped<I, N>(
+    &self,
+    item: &N,
+  ) -> Result<I, TurboDieselError>
+  where
+    N: Clone + Send + Sync + 'static,
+    I: Sized + Send + Timestamped + 'static,
+    I::Table: diesel::Table,
+    diesel::dsl::now:
+      diesel::expression::AsExpression<<I::CreatedAt as Expression>::SqlType>,
+    diesel::dsl::now:
+      diesel::expression::AsExpression<<I::UpdatedAt as Expression>::SqlType>,
+    (N, CreateTimestamps<I>): diesel::Insertable<I::Table>,
+    query_builder::InsertStatement<
+      I::Table,
+      <(N, CreateTimestamps<I>) as diesel::Insertable<I::Table>>::Values,
+    >: query_dsl::LoadQuery<'static, D, I>,
+  {
+    I::create_timestamped(self, item).await
+  }
+
+  /// Handle the Timestamped update
+  pub async fn update_timestamped_by_pk<I, Pk, C>(
+    &self,
+    pk: &Pk,
+    changeset: C,
+  ) -> Result<I, TurboDieselError>
+  where
+    Pk: Sync + ToOwned + ?Sized,
+    <Pk as ToOwned>::Owned: Send + 'static,
+    C: AsChangeset<Target = I::Table> + Send + 'static,
+    I: Sized + Send + Timestamped + 'static,
+    I::Table: query_dsl::methods::FindDsl<<Pk as ToOwned>::Owned>
+      + HasTable<Table = I::Table>,
+    diesel::helper_types::Find<I::Table, <Pk as ToOwned>::Owned>:
+      query_builder::IntoUpdateTarget + HasTable<Table = I::Table>,
+    diesel::dsl::now:
+      diesel::expression::AsExpression<<I::UpdatedAt as Expression>::SqlType>,
+    (C, UpdateTimestamp<I>): AsChangeset<Target = I::Table>,
+    diesel::helper_types::Update<
+      diesel::helper_types::Find<I::Table, <Pk as ToOwned>::Owned>,
+      (C, UpdateTimestamp<I>),
+    >: query_builder::AsQuery + query_dsl::LoadQuery<'static, D, I>,
+  {
+    I::update_timestamped_by_pk(self, pk, changeset).await
+  }
+
+  /// Handle the Timestamped update for the rows matching a filter
+  pub async fn update_timestamped_by<I, C>(
+    &self,
+    filter: &GenericFilter,
+    changeset: C,
+  ) -> Result<usize, TurboDieselError>
+  where
+    C: AsChangeset<Target = I::Table> + Send + 'static,
+    I: Sized + Timestamped + FilterableColumns<<D as Connection>::Backend>,
+    I::Table:
+      query_builder::IntoUpdateTarget + HasTable<Table = I::Table> + 'static,
+    diesel::dsl::now:
+      diesel::expression::AsExpression<<I::UpdatedAt as Expression>::SqlType>,
+    (C, UpdateTimestamp<I>): AsChangeset<Target = I::Table>,
+    query_builder::UpdateStatement<
+      I::Table,
+      <I::Table as query_builder::IntoUpdateTarget>::WhereClause,
+    >: query_dsl::methods::BoxedDsl<
+      'static,
+      <D as Connection>::Backend,
+      Output = query_builder::BoxedUpdateStatement<
+        'static,
+        <D as Connection>::Backend,
+        I::Table,
+      >,
+    >,
+    query_builder::BoxedUpdateStatement<
+      'static,
+      <D as Connection>::Backend,
+      I::Table,
+      <(C, UpdateTimestamp<I>) as AsChangeset>::Changeset,
+    >: query_builder::AsQuery
+      + query_builder::QueryFragment<<D as Connection>::Backend>
+      + query_builder::QueryId,
+    <D as diesel::Connection>::Backend:
+      diesel::internal::derives::multiconnection::DieselReserveSpecialization,
+  {
+    I::update_timestamped_by(self, filter, changeset).await
+  }
+
+  /// Handle the DbModelVersioned
+  pub async fn update_versioned_by_pk<I, Pk, C>(
+    &self,
+    pk: &Pk,
+    expected: i32,
+    changeset: C,
+  ) -> Result<I, TurboDieselError>
+  where
+    Pk: Sync + ToOwned + ?Sized,
+    <Pk as ToOwned>::Owned: Send + 'static,
+    C: AsChangeset<Target = I::Table> + Send + 'static,
+    I: Sized + Send + DbModelVersioned + 'static,
+    I::Table: query_dsl::methods::FindDsl<<Pk as ToOwned>::Owned>
+      + HasTable<Table = I::Table>,
+    diesel::helper_types::Find<I::Table, <Pk as ToOwned>::Owned>:
+      query_builder::IntoUpdateTarget + HasTable<Table = I::Table>,
+    i32: ToSql<diesel::sql_types::Integer, <D as Connection>::Backend>,
+    diesel::expression::SqlLiteral<<I::Version as Expression>::SqlType>:
+      diesel::expression::AsExpression<<I::Version as Expression>::SqlType>,
+    (C, VersionChangeset<I>): AsChangeset<Target = I::Table>,
+    query_builder::UpdateStatement<
+      I::Table,
+      <diesel::helper_types::Find<I::Table, <Pk as ToOwned>::Owned> as query_builder::IntoUpdateTarget>::WhereClause,
+    >: query_dsl::methods::BoxedDsl<
+      'static,
+      <D as Connection>::Backend,
+      Output = query_builder::BoxedUpdateStatement<
+        'static,
+        <D as Connection>::Backend,
+        I::Table,
+      >,
+    >,
+    query_builder::BoxedUpdateStatement<
+      'static,
+      <D as Connection>::Backend,
+      I::Table,
+      <(C, VersionChangeset<I>) as AsChangeset>::Changeset,
+    >: query_builder::AsQuery + query_dsl::LoadQuery<'static, D, I>,
+    <D as diesel::Connection>::Backend:
+      diesel::internal::derives::multiconnection::DieselReserveSpecialization,
+  {
+    I::update_versioned_by_pk(self, pk, expected, changeset).await
+  }
+
+  /// Delete every row of the table and return how many were deleted.
+  /// Use it instead of `del_by` when you really mean to empty the table,
+  /// `Confirm::Yes` says so.
+  pub async fn del_all<I>(
+    &self,
+    confirm: Confirm,
+  ) -> Result<usize, TurboDieselError>
+  where
+    I: Sized + HasTable + DbModelDelBy,
+    <I as HasTable>::Table: query_builder::QueryId
+      + query_builder::IntoUpdateTarget
+      + HasTable<Table = I::Table>
+      + 'static,
+    query_builder::DeleteStatement<
+      <I::Table as HasTable>::Table,
+      <I::Table as query_builder::IntoUpdateTarget>::WhereClause,
+    >: query_builder::QueryFragment<<D as Connection>::Backend>
+      + query_builder::QueryId,
+  {
+    I::del_all(self, confirm).await
+  }
+}
+
+pub trait DbModelCreate {
+  fn create<D>(
+    db: &DbDriver<D>,
+    item: &Self,
+  ) -> impl Future<Output = Result<Self, TurboDieselError>> + Send
+  where
+    D: diesel::r2d2::R2D2Connection
+      + diesel::connection::LoadConnection
+      + 'static,
+    Self: Sized
+      + Send
+      + Clone
+      + Sync
+      + HasTable
+      + diesel::Insertable<Self::Table>
+      + 'static,
+    Self::Table: diesel::Table,
+    query_builder::InsertStatement<
+      Self::Table,
+      <Self as diesel::Insertable<Self::Table>>::Values,
+    >: query_dsl::LoadQuery<'static, D, Self>,
+  {
+    async {
+      let item = item.to_owned();
+      db.execute_op("create", move |conn| {
+        let item = diesel::insert_into(<Self as HasTable>::table())
+          .values(item)
+          .get_result(conn)?;
+        Ok::<_, diesel::result::Error>(item)
+      })
+      .await
+    }
+  }
+
+  /// Insert a row without reading it back and return the number of rows
+  /// inserted, saving the `RETURNING` clause of `create`.
+  /// It works on every backend, MySQL included.
+  fn create_fast<D>(
+    db: &DbDriver<D>,
+    item: &Self,
+  ) -> impl Future<Output = Result<usize, TurboDieselError>> + Send
+  where
+    D: diesel::r2d2::R2D2Connection
+      + diesel::connection::LoadConnection
+      + 'static,
+    Self: Sized
+      + Send
+      + Clone
+      + Sync
+      + HasTable
+      + diesel::Insertable<Self::Table>
+      + 'static,
+    Self::Table: diesel::Table,
+    query_builder::InsertStatement<
+      Self::Table,
+      <Self as diesel::Insertable<Self::Table>>::Values,
+    >: query_dsl::methods::ExecuteDsl<D>,
+  {
+    async {
+      let item = item.to_owned();
+      db.execute_op("create_fast", move |conn| {
+        diesel::insert_into(<Self as HasTable>::table())
+          .values(item)
+          .execute(conn)
+      })
+      .await
+    }
+  }
+
+  /// Insert a row then read it back by its primary key,
+  /// for the backends without `RETURNING` like MySQL.
+  /// Both queries run in a transaction.
+  /// The primary key is taken from `item`,
+  /// so it can't be left to the database to generate.
+  fn create_and_find<D>(
+    db: &DbDriver<D>,
+    item: &Self,
+  ) -> impl Future<Output = Result<Self, TurboDieselError>> + Send
+  where
+    D: diesel::r2d2::R2D2Connection
+      + diesel::connection::LoadConnection
+      + 'static,
+    Self: Sized
+      + Send
+      + Clone
+      + Sync
+      + HasTable
+      + diesel::Insertable<Self::Table>
+      + 'static,
+    for<'a> &'a Self: Identifiable<Table = Self::Table>,
+    Self::Table: diesel::Table,
+    query_builder::InsertStatement<
+      Self::Table,
+      <Self as diesel::Insertable<Self::Table>>::Values,
+    >: query_dsl::methods::ExecuteDsl<D>,
+    for<'a> Self::Table:
+      query_dsl::methods::FindDsl<<&'a Self as Identifiable>::Id>,
+    for<'a> diesel::helper_types::Find<Self::Table, <&'a Self as Identifiable>::Id>:
+      query_dsl::methods::LimitDsl + RunQueryDsl<D>,
+    for<'a> diesel::helper_types::Limit<
+      diesel::helper_types::Find<Self::Table, <&'a Self as Identifiable>::Id>,
+    >: query_dsl::LoadQuery<'a, D, Self>,
+  {
+    async {
+      let item = item.to_owned();
+      db.execute_op("create_and_find", move |conn| {
+        conn.transaction(|conn| {
+          diesel::insert_into(<Self as HasTable>::table())
+            .values(item.clone())
+            .execute(conn)?;
+          <Self as HasTable>::table().find(item.id()).first(conn)
+        })
+      })
+      .await
+    }
+  }
+
+  /// Insert many rows and return them.
+  /// The rows are sent by chunks of `chunk_size` rows inside a single
+  /// transaction, when `None` as many rows as keep a chunk under
+  /// `DEFAULT_CHUNK_SIZE` binds, one per column of the table.
+  /// See `InsertMany` for how a chunk is inserted on each backend.
+  fn create_many<D>(
+    db: &DbDriver<D>,
+    items: Vec<Self>,
+    chunk_size: Option<usize>,
+  ) -> impl Future<Output = Result<Vec<Self>, TurboDieselError>> + Send
+  where
+    D: diesel::r2d2::R2D2Connection
+      + diesel::connection::LoadConnection
+      + InsertMany<Self>
+      + 'static,
+    Self: Sized + Send + HasTable + 'static,
+    <Self::Table as diesel::Table>::AllColumns: ColumnCount,
+  {
+    async move {
+      if items.is_empty() {
+        return Ok(vec![]);
+      }
+      let columns = <<Self::Table as diesel::Table>::AllColumns>::COUNT;
+      let chunk_size =
+        chunk_size.unwrap_or(DEFAULT_CHUNK_SIZE / columns).max(1);
+      db.execute_op("create_many", move |conn| {
+        conn.transaction(|conn| {
+          let mut items = items;
+          let mut created = Vec::with_capacity(items.len());
+          while !items.is_empty() {
+            let rest = items.split_off(chunk_size.min(items.len()));
+            let chunk = std::mem::replace(&mut items, rest);
+            created.append(&mut conn.insert_many(chunk)?);
+          }
+          Ok::<_, diesel::result::Error>(created)
+        })
+      })
+      .await
+    }
+  }
+}
+
+/// Insert a chunk of rows of `create_many` and return them.
+/// Postgres inserts the chunk with a single statement.
+/// Diesel 2.1 only returns the rows of a single row insert on SQLite,
+/// so the rows are inserted one by one there.
+pub trait InsertMany<I>: Connection {
+  fn insert_many(&mut self, items: Vec<I>) -> QueryResult<Vec<I>>;
+}
+
+impl<I> InsertMany<I> for PgConnection
+where
+  I: HasTable,
+  I::Table: diesel::Table,
+  Vec<I>: diesel::Insertable<I::Table>,
+  query_builder::InsertStatement<
+    I::Table,
+    <Vec<I> as diesel::Insertable<I::Table>>::Values,
+  >: query_dsl::LoadQuery<'static, PgConnection, I>,
+{
+  fn insert_many(&mut self, items: Vec<I>) -> QueryResult<Vec<I>> {
+    diesel::insert_into(I::table())
+      .values(items)
+      .get_results(self)
+  }
+}
+
+impl<I> InsertMany<I> for SqliteConnection
+where
+  I: HasTable + diesel::Insertable<<I as HasTable>::Table>,
+  I::Table: diesel::Table,
+  query_builder::InsertStatement<
+    I::Table,
+    <I as diesel::Insertable<I::Table>>::Values,
+  >: query_dsl::LoadQuery<'static, SqliteConnection, I>,
+{
+  fn insert_many(&mut self, items: Vec<I>) -> QueryResult<Vec<I>> {
+    items
+      .into_iter()
+      .map(|item| {
+        diesel::insert_into(I::table()).values(item).get_result(self)
+      })
+      .collect()
+  }
+}
+
+/// Insert a row, or update it when its primary key already exists.
+/// Diesel only supports `ON CONFLICT` on SQLite and Postgres,
+/// so the trait is implemented per connection type,
+/// usually with `#[derive(DbModelUpsert)]`.
+pub trait DbModelUpsert<D>
+where
+  D: diesel::r2d2::R2D2Connection + Connection + LoadConnection + 'static,
+{
+  /// Run `INSERT ... ON CONFLICT (pk) DO UPDATE SET ...` for `item`.
+  fn upsert_query(
+    conn: &mut D,
+    item: Self,
+  ) -> Result<Self, diesel::result::Error>
+  where
+    Self: Sized;
+
+  /// Run `INSERT ... ON CONFLICT DO NOTHING` for `item`,
+  /// returning the row only when it was inserted.
+  fn create_or_ignore_query(
+    conn: &mut D,
+    item: Self,
+  ) -> Result<Option<Self>, diesel::result::Error>
+  where
+    Self: Sized;
+
+  /// Run `INSERT ... ON CONFLICT (pk) DO UPDATE SET col = excluded.col`
+  /// for `items` in one statement, each conflicting row taking the values
+  /// of its item.
+  fn upsert_many_query(
+    conn: &mut D,
+    items: Vec<Self>,
+  ) -> Result<Vec<Self>, diesel::result::Error>
+  where
+    Self: Sized;
+
+  fn upsert(
+    db: &DbDriver<D>,
+    item: &Self,
+  ) -> impl Future<Output = Result<Self, TurboDieselError>> + Send
+  where
+    Self: Sized + Send + Clone + Sync + 'static,
+  {
+    async {
+      let item = item.to_owned();
+      db.execute_op("upsert", move |conn| Self::upsert_query(conn, item))
+        .await
+    }
+  }
+
+  /// Upsert many rows, `chunk_size` rows per statement in a single
+  /// transaction, when `None` as many rows as keep a chunk under
+  /// `DEFAULT_CHUNK_SIZE` binds like `create_many`.
+  /// Postgres rejects a chunk holding the same primary key twice.
+  fn upsert_many(
+    db: &DbDriver<D>,
+    items: Vec<Self>,
+    chunk_size: Option<usize>,
+  ) -> impl Future<Output = Result<Vec<Self>, TurboDieselError>> + Send
+  where
+    Self: Sized + Send + HasTable + 'static,
+    <Self::Table as diesel::Table>::AllColumns: ColumnCount,
+  {
+    async move {
+      if items.is_empty() {
+        return Ok(vec![]);
+      }
+      let columns = <<Self::Table as diesel::Table>::AllColumns>::COUNT;
+      let chunk_size =
+        chunk_size.unwrap_or(DEFAULT_CHUNK_SIZE / columns).max(1);
+      db.execute_op("upsert_many", move |conn| {
+        conn.transaction(|conn| {
+          let mut items = items;
+          let mut upserted = Vec::with_capacity(items.len());
+          while !items.is_empty() {
+            let rest = items.split_off(chunk_size.min(items.len()));
+            let chunk = std::mem::replace(&mut items, rest);
+            upserted.append(&mut Self::upsert_many_query(conn, chunk)?);
+          }
+          Ok::<_, diesel::result::Error>(upserted)
+        })
+      })
+      .await
+    }
+  }
+
+  /// Insert a row unless it conflicts with an existing one,
+  /// `Ok(None)` meaning the row was skipped.
+  fn create_or_ignore(
+    db: &DbDriver<D>,
+    item: &Self,
+  ) -> impl Future<Output = Result<Option<Self>, TurboDieselError>> + Send
+  where
+    Self: Sized + Send + Clone + Sync + 'static,
+  {
+    async {
+      let item = item.to_owned();
+      db.execute_op("create_or_ignore", move |conn| {
+        Self::create_or_ignore_query(conn, item)
+      })
+      .await
+    }
+  }
+
+  /// Read the first row matching the filter, by primary key,
+  /// or insert `item` when none does, `true` meaning it was created.
+  /// The insert skips a conflict, with a row inserted concurrently say,
+  /// and the filter is run again inside the same transaction.
+  /// Fails with `TurboDieselError::Conflict` when `item` conflicts
+  /// with a row the filter doesn't match.
+  fn find_or_create(
+    db: &DbDriver<D>,
+    filter: &GenericFilter,
+    item: Self,
+  ) -> impl Future<Output = Result<(Self, bool), TurboDieselError>> + Send
+  where
+    Self: Sized + Send + HasTable + DbModelRead + 'static,
+    Self::Table: query_dsl::methods::BoxedDsl<
+      'static,
+      <D as Connection>::Backend,
+      Output = BoxedSelect<Self::Table, <D as Connection>::Backend>,
+    >,
+    Self: FilterableColumns<<D as Connection>::Backend>,
+    <D as diesel::Connection>::Backend:
+      diesel::internal::derives::multiconnection::DieselReserveSpecialization,
+    i64: ToSql<BigInt, <D as Connection>::Backend>,
+    BoxedSelect<Self::Table, <D as Connection>::Backend>:
+      query_dsl::methods::ThenOrderDsl<
+        <Self::Table as diesel::Table>::PrimaryKey,
+        Output = BoxedSelect<Self::Table, <D as Connection>::Backend>,
+      >,
+    BoxedSelect<Self::Table, <D as Connection>::Backend>:
+      query_dsl::LoadQuery<'static, D, Self>,
+  {
+    async {
+      let filter = filter.clone();
+      let found = db
+        .execute_op("find_or_create", move |conn| {
+          conn.transaction(|conn| {
+            let find = |conn: &mut D| {
+              let table = <Self as HasTable>::table();
+              Self::gen_read_query::<D>(&filter)?
+                .then_order_by(diesel::Table::primary_key(&table))
+                .limit(1)
+                .get_result::<Self>(conn)
+                .optional()
+            };
+            if let Some(found) = find(conn)? {
+              return Ok(Some((found, false)));
+            }
+            if let Some(created) = Self::create_or_ignore_query(conn, item)? {
+              return Ok(Some((created, true)));
+            }
+            Ok(find(conn)?.map(|found| (found, false)))
+          })
+        })
+        .await?;
+      found.ok_or(TurboDieselError::Conflict)
+    }
+  }
+}
+
+pub trait DbModelReadByPk {
+  /// Read a row by its primary key.
+  /// A missing row yields `Ok(None)` instead of `Err(NotFound)`.
+  /// A composite primary key is given as a tuple,
+  /// in the order of `#[diesel(primary_key(...))]`.
+  fn read_by_pk<D, Pk>(
+    db: &DbDriver<D>,
+    pk: &Pk,
+  ) -> impl Future<Output = Result<Option<Self>, TurboDieselError>> + Send
+  where
+    D: diesel::r2d2::R2D2Connection + Connection + LoadConnection + 'static,
+    Pk: Sync + ToOwned + ?Sized,
+    <Pk as ToOwned>::Owned: Send + 'static,
+    Self: Sized + Send + HasTable + 'static,
+    Self::Table: query_dsl::methods::FindDsl<<Pk as ToOwned>::Owned>
+      + HasTable<Table = Self::Table>,
+    diesel::helper_types::Find<Self::Table, <Pk as ToOwned>::Owned>:
+      query_dsl::methods::LimitDsl + RunQueryDsl<D>,
+    diesel::helper_types::Limit<
+      diesel::helper_types::Find<Self::Table, <Pk as ToOwned>::Owned>,
+    >: query_dsl::LoadQuery<'static, D, Self>,
+  {
+    async {
+      let pk = pk.to_owned();
+      db.read_op("read_by_pk", move |conn| {
+        let item = <Self::Table as HasTable>::table()
+          .find(pk)
+          .first(conn)
+          .optional()?;
+        Ok::<_, diesel::result::Error>(item)
+      })
+      .await
+    }
+  }
+
+  /// `read_by_pk` returning `TurboDieselError::NotFound`
+  /// when there is no row with the primary key.
+  fn read_by_pk_strict<D, Pk>(
+    db: &DbDriver<D>,
+    pk: &Pk,
+  ) -> impl Future<Output = Result<Self, TurboDieselError>> + Send
+  where
+    D: diesel::r2d2::R2D2Connection + Connection + LoadConnection + 'static,
+    Pk: Sync + ToOwned + ?Sized,
+    <Pk as ToOwned>::Owned: Send + 'static,
+    Self: Sized + Send + HasTable + 'static,
+    Self::Table: query_dsl::methods::FindDsl<<Pk as ToOwned>::Owned>
+      + HasTable<Table = Self::Table>,
+    diesel::helper_types::Find<Self::Table, <Pk as ToOwned>::Owned>:
+      query_dsl::methods::LimitDsl + RunQueryDsl<D>,
+    diesel::helper_types::Limit<
+      diesel::helper_types::Find<Self::Table, <Pk as ToOwned>::Owned>,
+    >: query_dsl::LoadQuery<'static, D, Self>,
+  {
+    async {
+      Self::read_by_pk(db, pk)
+        .await?
+        .ok_or(TurboDieselError::NotFound)
+    }
+  }
+}
+
+pub trait DbModelRead {
+  /// Build the select statement with every clause of `filter.r#where` applied.
+  fn gen_read_query<D>(
+    filter: &GenericFilter,
+  ) -> Result<
+    BoxedSelect<Self::Table, <D as Connection>::Backend>,
+    diesel::result::Error,
+  >
+  where
+    D: diesel::r2d2::R2D2Connection + Connection + LoadConnection + 'static,
+    Self: HasTable + FilterableColumns<<D as Connection>::Backend>,
+    Self::Table: query_dsl::methods::BoxedDsl<
+      'static,
+      <D as Connection>::Backend,
+      Output = BoxedSelect<Self::Table, <D as Connection>::Backend>,
+    >,
+    <D as diesel::Connection>::Backend:
+      diesel::internal::derives::multiconnection::DieselReserveSpecialization;
+
+  /// Read the rows matching the filter, honoring its order, limit and offset.
+  /// Without a limit in the filter the default limit of the driver is used,
+  /// `DEFAULT_LIMIT` unless changed, see `GenericFilter::unlimited`
+  /// to get every row.
+  fn read_by<D>(
+    db: &DbDriver<D>,
+    filter: &GenericFilter,
+  ) -> impl Future<Output = Result<Vec<Self>, TurboDieselError>> + Send
+  where
+    D: diesel::r2d2::R2D2Connection + Connection + LoadConnection + 'static,
+    Self: Sized + Send + HasTable + 'static,
+    Self::Table: query_dsl::methods::BoxedDsl<
+      'static,
+      <D as Connection>::Backend,
+      Output = BoxedSelect<Self::Table, <D as Connection>::Backend>,
+    >,
+    Self: FilterableColumns<<D as Connection>::Backend>,
+    <D as diesel::Connection>::Backend:
+      diesel::internal::derives::multiconnection::DieselReserveSpecialization,
+    i64: ToSql<BigInt, <D as Connection>::Backend>,
+    BoxedSelect<Self::Table, <D as Connection>::Backend>:
+      query_dsl::LoadQuery<'static, D, Self>,
+  {
+    async {
+      let mut filter = filter.clone();
+      let limit = db.page_limit(&mut filter)?;
+      db.read_op("read_by", move |conn| {
+        let items =
+          gen_page_query::<D, Self>(&filter, limit)?.load::<Self>(conn)?;
+        Ok::<_, diesel::result::Error>(items)
+      })
+      .await
+    }
+  }
+
+  /// Read only the columns named `columns` of the rows matching the filter,
+  /// like `read_by` otherwise.
+  /// `T` is loaded from the SQL type `ST` of the tuple of columns,
+  /// `(Text,)` to read one text column into `(String,)`,
+  /// it must match the columns in number and type.
+  fn read_by_as<D, T, ST>(
+    db: &DbDriver<D>,
+    filter: &GenericFilter,
+    columns: &[&str],
+  ) -> impl Future<Output = Result<Vec<T>, TurboDieselError>> + Send
+  where
+    D: diesel::r2d2::R2D2Connection + Connection + LoadConnection + 'static,
+    Self: Sized + HasTable + 'static,
+    Self::Table: query_dsl::methods::BoxedDsl<
+      'static,
+      <D as Connection>::Backend,
+      Output = BoxedSelect<Self::Table, <D as Connection>::Backend>,
+    >,
+    Self: FilterableColumns<<D as Connection>::Backend>,
+    <D as diesel::Connection>::Backend:
+      diesel::internal::derives::multiconnection::DieselReserveSpecialization,
+    i64: ToSql<BigInt, <D as Connection>::Backend>,
+    T: Send + 'static,
+    ST: TypedExpressionType + 'static,
+    BoxedSelectAs<Self::Table, ST, <D as Connection>::Backend>:
+      query_dsl::LoadQuery<'static, D, T>,
+  {
+    let columns = columns.iter().map(|c| c.to_string()).collect::<Vec<_>>();
+    async {
+      let mut filter = filter.clone();
+      let limit = db.page_limit(&mut filter)?;
+      db.read_op("read_by_as", move |conn| {
+        let columns = columns.iter().map(String::as_str).collect::<Vec<_>>();
+        let query = gen_page_query::<D, Self>(&filter, limit)?;
+        let items = select_columns::<Self, _, ST>(query, &columns, &filter)?
+          .load::<T>(conn)?;
+        Ok::<_, diesel::result::Error>(items)
+      })
+      .await
+    }
+  }
+
+  /// Read the column named `column` of the first row matching the filter,
+  /// in its order, `None` when no row matches.
+  /// `T` is loaded from the SQL type `ST` of the column, `Text` to read
+  /// a text column into `String`, without a struct for a single value.
+  /// A nullable column is read into an `Option` of its own,
+  /// `Nullable<Text>` into `Option<String>`.
+  /// Use `aggregate` for a `MAX` or a `SUM` over the rows.
+  fn read_scalar<D, T, ST>(
+    db: &DbDriver<D>,
+    filter: &GenericFilter,
+    column: &str,
+  ) -> impl Future<Output = Result<Option<T>, TurboDieselError>> + Send
+  where
+    D: diesel::r2d2::R2D2Connection + Connection + LoadConnection + 'static,
+    Self: Sized + HasTable + 'static,
+    Self::Table: query_dsl::methods::BoxedDsl<
+      'static,
+      <D as Connection>::Backend,
+      Output = BoxedSelect<Self::Table, <D as Connection>::Backend>,
+    >,
+    Self: FilterableColumns<<D as Connection>::Backend>,
+    <D as diesel::Connection>::Backend:
+      diesel::internal::derives::multiconnection::DieselReserveSpecialization,
+    i64: ToSql<BigInt, <D as Connection>::Backend>,
+    T: Send + 'static,
+    ST: TypedExpressionType + 'static,
+    BoxedSelectAs<Self::Table, ST, <D as Connection>::Backend>:
+      query_dsl::LoadQuery<'static, D, T>,
+  {
+    let column = column.to_owned();
+    async move {
+      let mut filter = filter.clone();
+      db.cap_offset(&mut filter, Some(1))?;
+      db.read_op("read_scalar", move |conn| {
+        let query = gen_page_query::<D, Self>(&filter, Some(1))?;
+        let value = select_columns::<Self, _, ST>(query, &[&column], &filter)?
+          .get_result::<T>(conn)
+          .optional()?;
+        Ok::<_, diesel::result::Error>(value)
+      })
+      .await
+    }
+  }
+
+  /// Read the rows matching the filter into `R`, like `read_by` otherwise.
+  /// `R` derives `Queryable` and `Selectable` with
+  /// `#[diesel(table_name = ...)]` of the model: each field is read
+  /// from the column of the same name, or of `#[diesel(column_name = ...)]`,
+  /// and must have a type the column can be loaded as.
+  /// Only those columns are selected.
+  fn read_by_into<D, R>(
+    db: &DbDriver<D>,
+    filter: &GenericFilter,
+  ) -> impl Future<Output = Result<Vec<R>, TurboDieselError>> + Send
+  where
+    D: diesel::r2d2::R2D2Connection + Connection + LoadConnection + 'static,
+    Self: Sized + HasTable + 'static,
+    Self::Table: query_dsl::methods::BoxedDsl<
+      'static,
+      <D as Connection>::Backend,
+      Output = BoxedSelect<Self::Table, <D as Connection>::Backend>,
+    >,
+    Self: FilterableColumns<<D as Connection>::Backend>,
+    <D as diesel::Connection>::Backend:
+      diesel::internal::derives::multiconnection::DieselReserveSpecialization,
+    i64: ToSql<BigInt, <D as Connection>::Backend>,
+    R: Selectable<<D as Connection>::Backend> + Send + 'static,
+    R::SelectExpression:
+      query_builder::QueryFragment<<D as Connection>::Backend> + Send + 'static,
+    diesel::dsl::AsSelect<R, <D as Connection>::Backend>: TypedExpressionType,
+    BoxedSelectInto<Self::Table, R, <D as Connection>::Backend>:
+      query_dsl::LoadQuery<'static, D, R>,
+  {
+    async {
+      let mut filter = filter.clone();
+      let limit = db.page_limit(&mut filter)?;
+      db.read_op("read_by_into", move |conn| {
+        let query = gen_page_query::<D, Self>(&filter, limit)?;
+        let items =
+          select_into::<Self, _, R>(query, &filter)?.load::<R>(conn)?;
+        Ok::<_, diesel::result::Error>(items)
+      })
+      .await
+    }
+  }
+
+  /// Read the page of at most `page_size` rows after the cursor `after`,
+  /// the first page without one.
+  /// The rows are ordered by the columns of `filter.order`, each in its
+  /// direction, then by the primary key in the direction of the last one,
+  /// by the primary key alone when the filter has no order.
+  /// Unlike an offset, the cursor stays fast on large tables
+  /// and doesn't skip or repeat rows inserted between two pages,
+  /// as long as the order columns are indexed.
+  /// A nullable order column is a `QueryBuilderError`,
+  /// no row would compare after a null one.
+  /// The limit and offset of the filter are ignored,
+  /// `distinct_on` is a `QueryBuilderError`.
+  fn paginate_keyset<D>(
+    db: &DbDriver<D>,
+    filter: &GenericFilter,
+    after: Option<Cursor>,
+    page_size: usize,
+  ) -> impl Future<Output = Result<KeysetPage<Self>, TurboDieselError>> + Send
+  where
+    D: diesel::r2d2::R2D2Connection + Connection + LoadConnection + 'static,
+    Self: Sized + Send + HasTable + 'static,
+    Self::Table: query_dsl::methods::BoxedDsl<
+      'static,
+      <D as Connection>::Backend,
+      Output = BoxedSelect<Self::Table, <D as Connection>::Backend>,
+    >,
+    Self: FilterableColumns<<D as Connection>::Backend>,
+    <D as diesel::Connection>::Backend:
+      diesel::internal::derives::multiconnection::DieselReserveSpecialization,
+    i64: ToSql<BigInt, <D as Connection>::Backend>,
+    <Self::Table as diesel::Table>::PrimaryKey: Column,
+    <Self::Table as diesel::Table>::AllColumns:
+      query_builder::QueryFragment<<D as Connection>::Backend> + Send + 'static,
+    KeysetSqlType<Self::Table>: TypedExpressionType,
+    KeysetSelect<Self::Table, <D as Connection>::Backend>:
+      query_dsl::LoadQuery<'static, D, (Self, String)>,
+  {
+    async move {
+      let filter = filter.clone();
+      db.read_op("paginate_keyset", move |conn| {
+        if filter.distinct_on.as_ref().is_some_and(|d| !d.is_empty()) {
+          return Err(diesel::result::Error::QueryBuilderError(
+            "distinct_on can't be used with keyset pagination".into(),
+          ));
+        }
+        let pk = <<Self::Table as diesel::Table>::PrimaryKey as Column>::NAME;
+        let mut keys = filter
+          .order
+          .iter()
+          .flatten()
+          .map(|(column, direction)| (column.as_str(), *direction))
+          .collect::<Vec<_>>();
+        if !keys.iter().any(|(column, _)| *column == pk) {
+          let direction = keys
+            .last()
+            .map_or(OrderDirection::Asc, |(_, direction)| *direction);
+          keys.push((pk, direction));
+        }
+        let query = Self::gen_read_query::<D>(&filter)?;
+        let query = keyset_query::<Self, _, _>(
+          query,
+          <Self::Table as diesel::Table>::all_columns(),
+          &keys,
+          after.as_ref(),
+        )?;
+        // One more row tells whether there is a next page
+        let limit =
+          i64::try_from(page_size.saturating_add(1)).unwrap_or(i64::MAX);
+        let mut rows = query.limit(limit).load::<(Self, String)>(conn)?;
+        let next = if rows.len() > page_size {
+          rows.truncate(page_size);
+          rows
+            .last()
+            .map(|(_, keys)| Cursor::from_keys(keys))
+            .transpose()?
+        } else {
+          None
+        };
+        let items = rows.into_iter().map(|(item, _)| item).collect();
+        Ok::<_, diesel::result::Error>(KeysetPage { items, next })
+      })
+      .await
+    }
+  }
+
+  /// Stream every row matching the filter, reading them by chunks
+  /// of `chunk_size` rows (`DEFAULT_CHUNK_SIZE` when `None`)
+  /// with `paginate_keyset`, so only one chunk is held in memory at a time.
+  /// Each chunk is its own query, the rows changed between two of them
+  /// are seen the way the keyset pagination sees them.
+  /// The limit and offset of the filter are ignored,
+  /// `distinct_on` is a `QueryBuilderError`.
+  fn read_stream<D>(
+    db: &DbDriver<D>,
+    filter: &GenericFilter,
+    chunk_size: Option<usize>,
+  ) -> impl Stream<Item = Result<Self, TurboDieselError>> + Send + use<Self, D>
+  where
+    D: diesel::r2d2::R2D2Connection + Connection + LoadConnection + 'static,
+    Self: Sized + Send + HasTable + 'static,
+    Self::Table: query_dsl::methods::BoxedDsl<
+      'static,
+      <D as Connection>::Backend,
+      Output = BoxedSelect<Self::Table, <D as Connection>::Backend>,
+    >,
+    Self: FilterableColumns<<D as Connection>::Backend>,
+    <D as diesel::Connection>::Backend:
+      diesel::internal::derives::multiconnection::DieselReserveSpecialization,
+    i64: ToSql<BigInt, <D as Connection>::Backend>,
+    <Self::Table as diesel::Table>::PrimaryKey: Column,
+    <Self::Table as diesel::Table>::AllColumns:
+      query_builder::QueryFragment<<D as Connection>::Backend> + Send + 'static,
+    KeysetSqlType<Self::Table>: TypedExpressionType,
+    KeysetSelect<Self::Table, <D as Connection>::Backend>:
+      query_dsl::LoadQuery<'static, D, (Self, String)>,
+  {
+    let chunk_size = chunk_size.unwrap_or(DEFAULT_CHUNK_SIZE).max(1);
+    // The rows left of the current chunk and the cursor of the next one,
+    // `Some(None)` before the first chunk and `None` after the last
+    let state = (
+      db.clone(),
+      filter.clone(),
+      Vec::new().into_iter(),
+      Some(None),
+    );
+    futures_util::stream::try_unfold(
+      state,
+      move |(db, filter, mut rows, mut next)| async move {
+        loop {
+          if let Some(item) = rows.next() {
+            return Ok(Some((item, (db, filter, rows, next))));
+          }
+          let Some(after) = next else {
+            return Ok(None);
+          };
+          let page =
+            Self::paginate_keyset(&db, &filter, after, chunk_size).await?;
+          rows = page.items.into_iter();
+          next = page.next.map(Some);
+        }
+      },
+    )
+  }
+
+  /// Read the first row matching the filter, its limit is ignored.
+  /// Rows are ordered by primary key unless the filter has an order.
+  fn read_one<D>(
+    db: &DbDriver<D>,
+    filter: &GenericFilter,
+  ) -> impl Future<Output = Result<Option<Self>, TurboDieselError>> + Send
+  where
+    D: diesel::r2d2::R2D2Connection + Connection + LoadConnection + 'static,
+    Self: Sized + Send + HasTable + 'static,
+    Self::Table: query_dsl::methods::BoxedDsl<
+      'static,
+      <D as Connection>::Backend,
+      Output = BoxedSelect<Self::Table, <D as Connection>::Backend>,
+    >,
+    Self: FilterableColumns<<D as Connection>::Backend>,
+    <D as diesel::Connection>::Backend:
+      diesel::internal::derives::multiconnection::DieselReserveSpecialization,
+    i64: ToSql<BigInt, <D as Connection>::Backend>,
+    BoxedSelect<Self::Table, <D as Connection>::Backend>:
+      query_dsl::methods::ThenOrderDsl<
+        <Self::Table as diesel::Table>::PrimaryKey,
+        Output = BoxedSelect<Self::Table, <D as Connection>::Backend>,
+      >,
+    BoxedSelect<Self::Table, <D as Connection>::Backend>:
+      query_dsl::LoadQuery<'static, D, Self>,
+  {
+    async {
+      let mut filter = filter.clone();
+      db.cap_offset(&mut filter, Some(1))?;
+      db.read_op("read_one", move |conn| {
+        let query = Self::gen_read_query::<D>(&filter)?;
+        let ordered = filter.order.as_ref().is_some_and(|o| !o.is_empty())
+          || filter.distinct_on.as_ref().is_some_and(|d| !d.is_empty());
+        let mut query = if ordered {
+          apply_order::<Self, _>(query, &filter)?
+        } else {
+          let table = <Self as HasTable>::table();
+          query.then_order_by(diesel::Table::primary_key(&table))
+        };
+        if let Some(offset) = filter.offset {
+          query = query.offset(offset as i64);
+        }
+        let item = query.limit(1).get_result::<Self>(conn).optional()?;
+        Ok::<_, diesel::result::Error>(item)
+      })
+      .await
+    }
+  }
+
+  /// Read the rows matching the where clauses of the filter
+  /// with `SELECT ... FOR UPDATE`, its order, limit and offset are ignored.
+  /// The rows are locked until the end of the transaction,
+  /// so call it on the connection given by `DbDriver::transaction`.
+  /// Not available on SQLite which has no row lock.
+  fn read_for_update<C>(
+    conn: &mut C,
+    filter: &GenericFilter,
+  ) -> Result<Vec<Self>, diesel::result::Error>
+  where
+    C: Connection + LoadConnection,
+    Self: Sized + HasTable + FilterableColumns<<C as Connection>::Backend>,
+    Self::Table:
+      query_builder::AsQuery<Query = TableSelect<Self::Table>> + 'static,
+    <C as Connection>::Backend:
+      diesel::internal::derives::multiconnection::DieselReserveSpecialization
+        + 'static,
+    diesel::dsl::ForUpdate<
+      diesel::dsl::Filter<
+        TableSelect<Self::Table>,
+        BoxedPredicate<Self::Table, <C as Connection>::Backend>,
+      >,
+    >: query_dsl::LoadQuery<'static, C, Self>,
+  {
+    let predicate = filter_predicate::<Self, _>(filter)?;
+    query_builder::AsQuery::as_query(<Self as HasTable>::table())
+      .filter(predicate)
+      .for_update()
+      .load(conn)
+  }
+}
+
+/// The read query of the filter with its order, `limit` and offset applied.
+fn gen_page_query<D, I>(
+  filter: &GenericFilter,
+  limit: Option<usize>,
+) -> Result<
+  BoxedSelect<I::Table, <D as Connection>::Backend>,
+  diesel::result::Error,
+>
+where
+  D: diesel::r2d2::R2D2Connection + Connection + LoadConnection + 'static,
+  I: DbModelRead + HasTable + FilterableColumns<<D as Connection>::Backend>,
+  I::Table: 'static,
+  I::Table: query_dsl::methods::BoxedDsl<
+    'static,
+    <D as Connection>::Backend,
+    Output = BoxedSelect<I::Table, <D as Connection>::Backend>,
+  >,
+  <D as diesel::Connection>::Backend:
+    diesel::internal::derives::multiconnection::DieselReserveSpecialization,
+  i64: ToSql<BigInt, <D as Connection>::Backend>,
+{
+  let query = I::gen_read_query::<D>(filter)?;
+  let mut query = apply_order::<I, _>(query, filter)?;
+  // A limit too large for the backend means no limit at all
+  if let Some(limit) = limit.and_then(|limit| i64::try_from(limit).ok()) {
+    query = query.limit(limit);
+  }
+  if let Some(offset) = filter.offset {
+    query = query.offset(offset as i64);
+  }
+  Ok(query)
+}
+
+pub trait DbModelCount: DbModelRead {
+  /// Count the rows matching the filter, its limit and offset are ignored.
+  /// It's a `COUNT(*)`, see `count_by_column` for an index only scan.
+  fn count_by<D>(
+    db: &DbDriver<D>,
+    filter: &GenericFilter,
+  ) -> impl Future<Output = Result<i64, TurboDieselError>> + Send
+  where
+    D: diesel::r2d2::R2D2Connection + Connection + LoadConnection + 'static,
+    Self: Sized + Send + HasTable + 'static,
+    Self::Table: query_dsl::methods::BoxedDsl<
+      'static,
+      <D as Connection>::Backend,
+      Output = BoxedSelect<Self::Table, <D as Connection>::Backend>,
+    >,
+    Self: FilterableColumns<<D as Connection>::Backend>,
+    <D as diesel::Connection>::Backend:
+      diesel::internal::derives::multiconnection::DieselReserveSpecialization,
+    BoxedSelect<Self::Table, <D as Connection>::Backend>:
+      query_dsl::methods::SelectDsl<diesel::dsl::CountStar>,
+    diesel::dsl::Select<
+      BoxedSelect<Self::Table, <D as Connection>::Backend>,
+      diesel::dsl::CountStar,
+    >: query_dsl::LoadQuery<'static, D, i64>,
+  {
+    async {
+      let filter = filter.clone();
+      db.read_op("count_by", move |conn| {
+        let count = Self::gen_read_query::<D>(&filter)?
+          .count()
+          .get_result(conn)?;
+        Ok::<_, diesel::result::Error>(count)
+      })
+      .await
+    }
+  }
+
+  /// Apply an aggregate function on the column named `column`
+  /// of the rows matching the filter, its order, limit and offset are ignored.
+  /// The result is cast to the SQL type of `R`, `i64` or `f64` for a sum
+  /// or an average and the type of the column for a min or a max,
+  /// `None` when no row matches.
+  fn aggregate<D, R>(
+    db: &DbDriver<D>,
+    filter: &GenericFilter,
+    function: AggregateFn,
+    column: &str,
+  ) -> impl Future<Output = Result<Option<R>, TurboDieselError>> + Send
+  where
+    D: diesel::r2d2::R2D2Connection + Connection + LoadConnection + 'static,
+    Self: Sized + Send + HasTable + 'static,
+    Self::Table: query_dsl::methods::BoxedDsl<
+      'static,
+      <D as Connection>::Backend,
+      Output = BoxedSelect<Self::Table, <D as Connection>::Backend>,
+    >,
+    Self: FilterableColumns<<D as Connection>::Backend>,
+    <D as diesel::Connection>::Backend:
+      diesel::internal::derives::multiconnection::DieselReserveSpecialization,
+    R: AggregateValue + Send + 'static,
+    diesel::sql_types::Nullable<R::SqlType>: TypedExpressionType,
+    AggregateSelect<Self::Table, R, <D as Connection>::Backend>:
+      query_dsl::LoadQuery<'static, D, Option<R>>,
+  {
+    let column = column.to_owned();
+    async move {
+      let filter = filter.clone();
+      db.read_op("aggregate", move |conn| {
+        let query = Self::gen_read_query::<D>(&filter)?;
+        let value = select_aggregate::<Self, _, R>(query, function, &column)?
+          .get_result(conn)?;
+        Ok::<_, diesel::result::Error>(value)
+      })
+      .await
+    }
+  }
+
+  /// Count the rows matching the filter whose column named `column`
+  /// isn't null, `COUNT(column)` instead of the `COUNT(*)` of `count_by`.
+  /// On Postgres an index on the column can answer it with an index only
+  /// scan, without reading the table. The null values aren't counted,
+  /// so it's `count_by` only on a column that is never null.
+  fn count_by_column<D>(
+    db: &DbDriver<D>,
+    filter: &GenericFilter,
+    column: &str,
+  ) -> impl Future<Output = Result<i64, TurboDieselError>> + Send
+  where
+    D: diesel::r2d2::R2D2Connection + Connection + LoadConnection + 'static,
+    Self: Sized + Send + HasTable + 'static,
+    Self::Table: query_dsl::methods::BoxedDsl<
+      'static,
+      <D as Connection>::Backend,
+      Output = BoxedSelect<Self::Table, <D as Connection>::Backend>,
+    >,
+    Self: FilterableColumns<<D as Connection>::Backend>,
+    <D as diesel::Connection>::Backend:
+      diesel::internal::derives::multiconnection::DieselReserveSpecialization,
+    AggregateSelect<Self::Table, i64, <D as Connection>::Backend>:
+      query_dsl::LoadQuery<'static, D, Option<i64>>,
+  {
+    let count =
+      Self::aggregate::<D, i64>(db, filter, AggregateFn::Count, column);
+    async move { Ok(count.await?.unwrap_or(0)) }
+  }
+
+  /// Count the distinct values of the column named `column`
+  /// in the rows matching the filter, nulls left out.
+  fn count_distinct<D>(
+    db: &DbDriver<D>,
+    filter: &GenericFilter,
+    column: &str,
+  ) -> impl Future<Output = Result<i64, TurboDieselError>> + Send
+  where
+    D: diesel::r2d2::R2D2Connection + Connection + LoadConnection + 'static,
+    Self: Sized + Send + HasTable + 'static,
+    Self::Table: query_dsl::methods::BoxedDsl<
+      'static,
+      <D as Connection>::Backend,
+      Output = BoxedSelect<Self::Table, <D as Connection>::Backend>,
+    >,
+    Self: FilterableColumns<<D as Connection>::Backend>,
+    <D as diesel::Connection>::Backend:
+      diesel::internal::derives::multiconnection::DieselReserveSpecialization,
+    AggregateSelect<Self::Table, i64, <D as Connection>::Backend>:
+      query_dsl::LoadQuery<'static, D, Option<i64>>,
+  {
+    let count =
+      Self::aggregate::<D, i64>(db, filter, AggregateFn::CountDistinct, column);
+    async move { Ok(count.await?.unwrap_or(0)) }
+  }
+
+  /// Count the rows matching the filter per value of the column named
+  /// `column`, `GROUP BY column`, its order, limit and offset are ignored.
+  /// The values are read as text, `GenericValue::Null` for the null ones,
+  /// and the largest groups come first.
+  fn group_count<D>(
+    db: &DbDriver<D>,
+    filter: &GenericFilter,
+    column: &str,
+  ) -> impl Future<Output = Result<Vec<(GenericValue, i64)>, TurboDieselError>> + Send
+  where
+    D: diesel::r2d2::R2D2Connection + Connection + LoadConnection + 'static,
+    Self: Sized + Send + HasTable + 'static,
+    Self::Table: query_dsl::methods::BoxedDsl<
+      'static,
+      <D as Connection>::Backend,
+      Output = BoxedSelect<Self::Table, <D as Connection>::Backend>,
+    >,
+    Self: FilterableColumns<<D as Connection>::Backend>,
+    <D as diesel::Connection>::Backend:
+      diesel::internal::derives::multiconnection::DieselReserveSpecialization,
+    GroupCountQuery<Self::Table, <D as Connection>::Backend>:
+      query_dsl::LoadQuery<'static, D, (Option<String>, i64)>,
+  {
+    let column = column.to_owned();
+    async move {
+      let filter = filter.clone();
+      db.read_op("group_count", move |conn| {
+        let query = Self::gen_read_query::<D>(&filter)?;
+        let groups = group_count_query::<Self, _>(query, &column)?
+          .load::<(Option<String>, i64)>(conn)?
+          .into_iter()
+          .map(|(value, count)| {
+            (value.map_or(GenericValue::Null, GenericValue::Text), count)
+          })
+          .collect();
+        Ok::<_, diesel::result::Error>(groups)
+      })
+      .await
+    }
+  }
+
+  /// Read a page of the rows matching the filter along with their total count,
+  /// both queries run in a transaction to agree with each other.
+  /// The limit is the same as `read_by`.
+  fn paginate<D>(
+    db: &DbDriver<D>,
+    filter: &GenericFilter,
+  ) -> impl Future<Output = Result<Page<Self>, TurboDieselError>> + Send
+  where
+    D: diesel::r2d2::R2D2Connection + Connection + LoadConnection + 'static,
+    Self: Sized + Send + HasTable + 'static,
+    Self::Table: query_dsl::methods::BoxedDsl<
+      'static,
+      <D as Connection>::Backend,
+      Output = BoxedSelect<Self::Table, <D as Connection>::Backend>,
+    >,
+    Self: FilterableColumns<<D as Connection>::Backend>,
+    <D as diesel::Connection>::Backend:
+      diesel::internal::derives::multiconnection::DieselReserveSpecialization,
+    i64: ToSql<BigInt, <D as Connection>::Backend>,
+    BoxedSelect<Self::Table, <D as Connection>::Backend>:
+      query_dsl::LoadQuery<'static, D, Self>
+        + query_dsl::methods::SelectDsl<diesel::dsl::CountStar>,
+    diesel::dsl::Select<
+      BoxedSelect<Self::Table, <D as Connection>::Backend>,
+      diesel::dsl::CountStar,
+    >: query_dsl::LoadQuery<'static, D, i64>,
+  {
+    async {
+      let mut filter = filter.clone();
+      let limit = db.page_limit(&mut filter)?;
+      let page = db
+        .read_op("paginate", move |conn| {
+          conn.transaction(|conn| {
+            let items =
+              gen_page_query::<D, Self>(&filter, limit)?.load::<Self>(conn)?;
+            let total = Self::gen_read_query::<D>(&filter)?
+              .count()
+              .get_result(conn)?;
+            Ok::<_, diesel::result::Error>(Page {
+              items,
+              total,
+              limit: limit.unwrap_or(usize::MAX),
+              offset: filter.offset.unwrap_or(0),
+            })
+          })
+        })
+        .await?;
+      Ok(page)
+    }
+  }
+}
+
+pub trait DbModelExists: DbModelRead {
+  /// Check whether a row matches the filter, its limit and offset are ignored.
+  /// The database stops at the first match with `SELECT EXISTS (...)`.
+  fn exists_by<D>(
+    db: &DbDriver<D>,
+    filter: &GenericFilter,
+  ) -> impl Future<Output = Result<bool, TurboDieselError>> + Send
+  where
+    D: diesel::r2d2::R2D2Connection + Connection + LoadConnection + 'static,
+    Self: Sized + Send + HasTable + 'static,
+    Self::Table: query_dsl::methods::BoxedDsl<
+      'static,
+      <D as Connection>::Backend,
+      Output = BoxedSelect<Self::Table, <D as Connection>::Backend>,
+    >,
+    Self: FilterableColumns<<D as Connection>::Backend>,
+    <D as diesel::Connection>::Backend:
+      diesel::internal::derives::multiconnection::DieselReserveSpecialization,
+    diesel::dsl::BareSelect<
+      Exists<BoxedSelect<Self::Table, <D as Connection>::Backend>>,
+    >: query_dsl::LoadQuery<'static, D, bool>,
+  {
+    async {
+      let filter = filter.clone();
+      db.read_op("exists_by", move |conn| {
+        let query = Self::gen_read_query::<D>(&filter)?;
+        let exists =
+          diesel::select(diesel::dsl::exists(query)).get_result(conn)?;
+        Ok::<_, diesel::result::Error>(exists)
+      })
+      .await
+    }
+  }
+}
+
+pub trait DbModelUpdate {
+  /// Update the row with the primary key `pk` and return it.
+  ///
+  /// The bounds read as follow:
+  /// - `Pk` is borrowed by the caller and owned by the blocking task,
+  /// - `C` is any changeset targeting the model table,
+  ///   usually a struct deriving `AsChangeset`,
+  /// - `table().find(pk)` must be a valid update target,
+  /// - `update(table().find(pk)).set(changeset)` must return rows
+  ///   loadable as `Self` (`RETURNING` clause on the backend).
+  ///
+  /// A missing row is `TurboDieselError::NotFound`.
+  fn update_by_pk<D, Pk, C>(
+    db: &DbDriver<D>,
+    pk: &Pk,
+    changeset: C,
+  ) -> impl Future<Output = Result<Self, TurboDieselError>> + Send
+  where
+    D: diesel::r2d2::R2D2Connection + Connection + LoadConnection + 'static,
+    Pk: Sync + ToOwned + ?Sized,
+    <Pk as ToOwned>::Owned: Send + 'static,
+    C: AsChangeset<Target = Self::Table> + Send + 'static,
+    Self: Sized + Send + HasTable + 'static,
+    Self::Table: query_dsl::methods::FindDsl<<Pk as ToOwned>::Owned>
+      + HasTable<Table = Self::Table>,
+    diesel::helper_types::Find<Self::Table, <Pk as ToOwned>::Owned>:
+      query_builder::IntoUpdateTarget + HasTable<Table = Self::Table>,
+    diesel::helper_types::Update<
+      diesel::helper_types::Find<Self::Table, <Pk as ToOwned>::Owned>,
+      C,
+    >: query_builder::AsQuery + query_dsl::LoadQuery<'static, D, Self>,
+  {
+    async {
+      let pk = pk.to_owned();
+      db.execute_op("update_by_pk", move |conn| {
+        let item = diesel::update(<Self::Table as HasTable>::table().find(pk))
+          .set(changeset)
+          .get_result(conn)?;
+        Ok::<_, diesel::result::Error>(item)
+      })
+      .await
+    }
+  }
+
+  /// Add `delta` to the numeric column named `column` of the row
+  /// with the primary key `pk` and return the row, a negative `delta`
+  /// decrements it. It's a single `UPDATE ... SET column = column + delta`,
+  /// concurrent increments all count, unlike a read followed by an update.
+  /// A missing row is `TurboDieselError::NotFound`.
+  fn increment<D, Pk>(
+    db: &DbDriver<D>,
+    pk: &Pk,
+    column: &str,
+    delta: i64,
+  ) -> impl Future<Output = Result<Self, TurboDieselError>> + Send
+  where
+    D: diesel::r2d2::R2D2Connection + Connection + LoadConnection + 'static,
+    Pk: Sync + ToOwned + ?Sized,
+    <Pk as ToOwned>::Owned: Send + 'static,
+    Self: Sized
+      + Send
+      + HasTable
+      + FilterableColumns<<D as Connection>::Backend>
+      + 'static,
+    Self::Table: query_dsl::methods::FindDsl<<Pk as ToOwned>::Owned>
+      + HasTable<Table = Self::Table>,
+    diesel::helper_types::Find<Self::Table, <Pk as ToOwned>::Owned>:
+      query_builder::IntoUpdateTarget + HasTable<Table = Self::Table>,
+    diesel::helper_types::Update<
+      diesel::helper_types::Find<Self::Table, <Pk as ToOwned>::Owned>,
+      Increment<Self::Table, <D as Connection>::Backend>,
+    >: query_builder::AsQuery + query_dsl::LoadQuery<'static, D, Self>,
   {
-    I::del_by_pk(self, pk).await
+    let changeset = increment_column::<Self, _>(column, delta);
+    async move { Self::update_by_pk(db, pk, changeset?).await }
   }
 
-  pub async fn del_by<I>(
-    &self,
+  /// Update the rows matching the where clause of the filter
+  /// and return how many were updated.
+  /// A filter true for every row, see `GenericFilter::has_conditions`,
+  /// is refused with `Error::QueryBuilderError`
+  /// to avoid updating the whole table by mistake, use `update_all` for that.
+  fn update_by<D, C>(
+    db: &DbDriver<D>,
     filter: &GenericFilter,
-  ) -> Result<(), diesel::result::Error>
+    changeset: C,
+  ) -> impl Future<Output = Result<usize, TurboDieselError>> + Send
   where
-    I: Sized + HasTable + DbModelDelBy,
-    <I as HasTable>::Table: query_builder::QueryId + 'static,
-    <<I as HasTable>::Table as diesel::QuerySource>::FromClause:
-      diesel::query_builder::QueryFragment<<D as Connection>::Backend>,
-    <<I as HasTable>::Table as diesel::QuerySource>::FromClause:
-      diesel::query_builder::QueryFragment<<D as Connection>::Backend>,
+    D: diesel::r2d2::R2D2Connection + Connection + LoadConnection + 'static,
+    C: AsChangeset<Target = Self::Table> + Send + 'static,
+    Self: Sized + HasTable + FilterableColumns<<D as Connection>::Backend>,
+    Self::Table:
+      query_builder::IntoUpdateTarget + HasTable<Table = Self::Table> + 'static,
+    query_builder::UpdateStatement<
+      Self::Table,
+      <Self::Table as query_builder::IntoUpdateTarget>::WhereClause,
+    >: query_dsl::methods::BoxedDsl<
+      'static,
+      <D as Connection>::Backend,
+      Output = query_builder::BoxedUpdateStatement<
+        'static,
+        <D as Connection>::Backend,
+        Self::Table,
+      >,
+    >,
+    query_builder::BoxedUpdateStatement<
+      'static,
+      <D as Connection>::Backend,
+      Self::Table,
+      C::Changeset,
+    >: query_builder::AsQuery
+      + query_builder::QueryFragment<<D as Connection>::Backend>
+      + query_builder::QueryId,
     <D as diesel::Connection>::Backend:
       diesel::internal::derives::multiconnection::DieselReserveSpecialization,
   {
-    I::del_by(self, filter).await
+    async {
+      let filter = filter.clone();
+      if !filter.has_conditions() {
+        return Err(
+          diesel::result::Error::QueryBuilderError(
+            "Refusing to update without a where clause, use update_all instead"
+              .into(),
+          )
+          .into(),
+        );
+      }
+      db.execute_op("update_by", move |conn| {
+        let query =
+          diesel::update(<Self::Table as HasTable>::table()).into_boxed();
+        let count = apply_where::<Self, _, _>(query, &filter)?
+          .set(changeset)
+          .execute(conn)?;
+        Ok::<_, diesel::result::Error>(count)
+      })
+      .await
+    }
   }
-}
 
-pub trait DbModelCreate {
-  fn create<D>(
+  /// `update_by` returning the updated rows with `RETURNING`,
+  /// as they are once the changeset applied.
+  /// Not available on MySQL, which has no `RETURNING`.
+  fn update_by_returning<D, C>(
     db: &DbDriver<D>,
-    item: &Self,
-  ) -> impl Future<Output = Result<Self, diesel::result::Error>> + Send
+    filter: &GenericFilter,
+    changeset: C,
+  ) -> impl Future<Output = Result<Vec<Self>, TurboDieselError>> + Send
   where
-    D: diesel::r2d2::R2D2Connection
-      + diesel::connection::LoadConnection
-      + 'static,
+    D: diesel::r2d2::R2D2Connection + Connection + LoadConnection + 'static,
+    C: AsChangeset<Target = Self::Table> + Send + 'static,
     Self: Sized
       + Send
-      + Clone
-      + Sync
       + HasTable
-      + diesel::Insertable<Self::Table>
+      + FilterableColumns<<D as Connection>::Backend>
       + 'static,
-    Self::Table: diesel::Table,
-    query_builder::InsertStatement<
+    Self::Table:
+      query_builder::IntoUpdateTarget + HasTable<Table = Self::Table> + 'static,
+    query_builder::UpdateStatement<
       Self::Table,
-      <Self as diesel::Insertable<Self::Table>>::Values,
-    >: query_dsl::LoadQuery<'static, D, Self>,
+      <Self::Table as query_builder::IntoUpdateTarget>::WhereClause,
+    >: query_dsl::methods::BoxedDsl<
+      'static,
+      <D as Connection>::Backend,
+      Output = query_builder::BoxedUpdateStatement<
+        'static,
+        <D as Connection>::Backend,
+        Self::Table,
+      >,
+    >,
+    query_builder::BoxedUpdateStatement<
+      'static,
+      <D as Connection>::Backend,
+      Self::Table,
+      C::Changeset,
+    >: query_builder::AsQuery + query_dsl::LoadQuery<'static, D, Self>,
+    <D as diesel::Connection>::Backend:
+      diesel::internal::derives::multiconnection::DieselReserveSpecialization,
   {
     async {
-      let item = item.to_owned();
-      db.execute(move |mut conn| {
-        let item = diesel::insert_into(<Self as HasTable>::table())
-          .values(item)
-          .get_result(&mut conn)?;
-        Ok::<_, diesel::result::Error>(item)
+      let filter = filter.clone();
+      if !filter.has_conditions() {
+        return Err(
+          diesel::result::Error::QueryBuilderError(
+            "Refusing to update without a where clause, use update_all instead"
+              .into(),
+          )
+          .into(),
+        );
+      }
+      db.execute_op("update_by_returning", move |conn| {
+        let query =
+          diesel::update(<Self::Table as HasTable>::table()).into_boxed();
+        let rows = apply_where::<Self, _, _>(query, &filter)?
+          .set(changeset)
+          .get_results(conn)?;
+        Ok::<_, diesel::result::Error>(rows)
+      })
+      .await
+    }
+  }
+
+  /// Set the column named `column` to the same `value` on the rows
+  /// matching the where clause of the filter and return how many
+  /// were updated. The value is converted to the type of the column,
+  /// an empty where clause is refused like `update_by`.
+  fn set_field<D>(
+    db: &DbDriver<D>,
+    filter: &GenericFilter,
+    column: &str,
+    value: GenericValue,
+  ) -> impl Future<Output = Result<usize, TurboDieselError>> + Send
+  where
+    D: diesel::r2d2::R2D2Connection + Connection + LoadConnection + 'static,
+    Self: Sized + HasTable + FilterableColumns<<D as Connection>::Backend>,
+    Self::Table:
+      query_builder::IntoUpdateTarget + HasTable<Table = Self::Table> + 'static,
+    query_builder::UpdateStatement<
+      Self::Table,
+      <Self::Table as query_builder::IntoUpdateTarget>::WhereClause,
+    >: query_dsl::methods::BoxedDsl<
+      'static,
+      <D as Connection>::Backend,
+      Output = query_builder::BoxedUpdateStatement<
+        'static,
+        <D as Connection>::Backend,
+        Self::Table,
+      >,
+    >,
+    query_builder::BoxedUpdateStatement<
+      'static,
+      <D as Connection>::Backend,
+      Self::Table,
+      SetField<Self::Table, <D as Connection>::Backend>,
+    >: query_builder::AsQuery
+      + query_builder::QueryFragment<<D as Connection>::Backend>
+      + query_builder::QueryId,
+    <D as diesel::Connection>::Backend:
+      diesel::internal::derives::multiconnection::DieselReserveSpecialization,
+  {
+    let changeset = set_column::<Self, _>(column, &value);
+    async move { Self::update_by(db, filter, changeset?).await }
+  }
+
+  /// Update every row of the table and return how many were updated,
+  /// `Confirm::Yes` being the explicit go ahead.
+  fn update_all<D, C>(
+    db: &DbDriver<D>,
+    changeset: C,
+    _confirm: Confirm,
+  ) -> impl Future<Output = Result<usize, TurboDieselError>> + Send
+  where
+    D: diesel::r2d2::R2D2Connection + Connection + LoadConnection + 'static,
+    C: AsChangeset<Target = Self::Table> + Send + 'static,
+    Self: Sized + HasTable,
+    Self::Table:
+      query_builder::IntoUpdateTarget + HasTable<Table = Self::Table> + 'static,
+    query_builder::UpdateStatement<
+      Self::Table,
+      <Self::Table as query_builder::IntoUpdateTarget>::WhereClause,
+      C::Changeset,
+    >: query_builder::AsQuery
+      + query_builder::QueryFragment<<D as Connection>::Backend>
+      + query_builder::QueryId,
+  {
+    async {
+      db.execute_op("update_all", move |conn| {
+        let count = diesel::update(<Self::Table as HasTable>::table())
+          .set(changeset)
+          .execute(conn)?;
+        Ok::<_, diesel::result::Error>(count)
       })
       .await
     }
@@ -163,16 +3751,19 @@ pub trait DbModelCreate {
 }
 
 pub trait DbModelDelByPk {
+  /// Delete the row with the primary key `pk`,
+  /// return 0 when there was none and 1 otherwise.
+  /// A composite primary key is given as a tuple.
   fn del_by_pk<D, Pk>(
     db: &DbDriver<D>,
     pk: &Pk,
-  ) -> impl Future<Output = Result<(), diesel::result::Error>> + Send
+  ) -> impl Future<Output = Result<usize, TurboDieselError>> + Send
   where
   D: diesel::r2d2::R2D2Connection
   + Connection
   + LoadConnection
   + 'static,
-  Pk: Sync + ToOwned + std::fmt::Display + ?Sized,
+  Pk: Sync + ToOwned + ?Sized,
   <Pk as ToOwned>::Owned: Send + 'static,
   Self: Sized + HasTable,
   Self::Table: query_dsl::methods::FindDsl<<Pk as ToOwned>::Owned> + HasTable<Table = Self::Table>,
@@ -184,10 +3775,103 @@ pub trait DbModelDelByPk {
   {
     async {
       let pk = pk.to_owned();
-      db.execute(move |mut conn| {
-        diesel::delete(<Self::Table as HasTable>::table().find(pk))
-          .execute(&mut conn)?;
-        Ok::<_, diesel::result::Error>(())
+      db.execute_op("del_by_pk", move |conn| {
+        let count = diesel::delete(<Self::Table as HasTable>::table().find(pk))
+          .execute(conn)?;
+        Ok::<_, diesel::result::Error>(count)
+      })
+      .await
+    }
+  }
+
+  /// `del_by_pk` returning `TurboDieselError::NotFound`
+  /// when there was no row to delete.
+  fn del_by_pk_strict<D, Pk>(
+    db: &DbDriver<D>,
+    pk: &Pk,
+  ) -> impl Future<Output = Result<(), TurboDieselError>> + Send
+  where
+    D: diesel::r2d2::R2D2Connection + Connection + LoadConnection + 'static,
+    Pk: Sync + ToOwned + ?Sized,
+    <Pk as ToOwned>::Owned: Send + 'static,
+    Self: Sized + HasTable,
+    Self::Table: query_dsl::methods::FindDsl<<Pk as ToOwned>::Owned>
+      + HasTable<Table = Self::Table>,
+    diesel::helper_types::Find<Self::Table, <Pk as ToOwned>::Owned>:
+      query_builder::IntoUpdateTarget,
+    query_builder::DeleteStatement<
+      <diesel::helper_types::Find<Self::Table, <Pk as ToOwned>::Owned> as HasTable>::Table,
+      <diesel::helper_types::Find<Self::Table, <Pk as ToOwned>::Owned> as query_builder::IntoUpdateTarget>::WhereClause,
+    >: query_builder::QueryFragment<<D as Connection>::Backend>
+      + query_builder::QueryId,
+  {
+    async {
+      match Self::del_by_pk(db, pk).await? {
+        0 => Err(TurboDieselError::NotFound),
+        _ => Ok(()),
+      }
+    }
+  }
+
+  /// Delete the rows whose primary key is in `pks` with a single query
+  /// and return how many were deleted, an empty slice runs no query.
+  /// Only single column primary keys are supported.
+  fn del_by_pks<D, Pk>(
+    db: &DbDriver<D>,
+    pks: &[Pk],
+  ) -> impl Future<Output = Result<usize, TurboDieselError>> + Send
+  where
+    D: diesel::r2d2::R2D2Connection + Connection + LoadConnection + 'static,
+    Pk: Sync + ToOwned,
+    <Pk as ToOwned>::Owned: Send + 'static,
+    Self: Sized + HasTable,
+    Self::Table: query_dsl::methods::FilterDsl<
+        diesel::dsl::EqAny<
+          <Self::Table as diesel::Table>::PrimaryKey,
+          Vec<<Pk as ToOwned>::Owned>,
+        >,
+      > + HasTable<Table = Self::Table>,
+    <<Self::Table as diesel::Table>::PrimaryKey as Expression>::SqlType:
+      diesel::sql_types::SingleValue,
+    Vec<<Pk as ToOwned>::Owned>: diesel::internal::derives::multiconnection::array_comparison::AsInExpression<
+      <<Self::Table as diesel::Table>::PrimaryKey as Expression>::SqlType,
+    >,
+    diesel::dsl::Filter<
+      Self::Table,
+      diesel::dsl::EqAny<
+        <Self::Table as diesel::Table>::PrimaryKey,
+        Vec<<Pk as ToOwned>::Owned>,
+      >,
+    >: query_builder::IntoUpdateTarget,
+    query_builder::DeleteStatement<
+      <diesel::dsl::Filter<
+        Self::Table,
+        diesel::dsl::EqAny<
+          <Self::Table as diesel::Table>::PrimaryKey,
+          Vec<<Pk as ToOwned>::Owned>,
+        >,
+      > as HasTable>::Table,
+      <diesel::dsl::Filter<
+        Self::Table,
+        diesel::dsl::EqAny<
+          <Self::Table as diesel::Table>::PrimaryKey,
+          Vec<<Pk as ToOwned>::Owned>,
+        >,
+      > as query_builder::IntoUpdateTarget>::WhereClause,
+    >: query_builder::QueryFragment<<D as Connection>::Backend>
+      + query_builder::QueryId,
+  {
+    let pks = pks.iter().map(ToOwned::to_owned).collect::<Vec<_>>();
+    async {
+      if pks.is_empty() {
+        return Ok(0);
+      }
+      db.execute_op("del_by_pks", move |conn| {
+        let table = <Self::Table as HasTable>::table();
+        let pk = diesel::Table::primary_key(&table);
+        let count =
+          diesel::delete(table.filter(pk.eq_any(pks))).execute(conn)?;
+        Ok::<_, diesel::result::Error>(count)
       })
       .await
     }
@@ -195,24 +3879,36 @@ pub trait DbModelDelByPk {
 }
 
 pub trait DbModelDelBy {
+  /// Build the delete statement with every clause of `filter.r#where` applied.
   fn gen_del_query<D>(
     filter: &GenericFilter,
-  ) -> diesel::query_builder::BoxedDeleteStatement<
-    'static,
-    <D as Connection>::Backend,
-    <Self as diesel::associations::HasTable>::Table,
+  ) -> Result<
+    diesel::query_builder::BoxedDeleteStatement<
+      'static,
+      <D as Connection>::Backend,
+      <Self as diesel::associations::HasTable>::Table,
+    >,
+    diesel::result::Error,
   >
   where
     D: diesel::r2d2::R2D2Connection + Connection + LoadConnection + 'static,
-    Self: diesel::associations::HasTable;
+    Self: diesel::associations::HasTable
+      + FilterableColumns<<D as Connection>::Backend>,
+    <D as diesel::Connection>::Backend:
+      diesel::internal::derives::multiconnection::DieselReserveSpecialization;
 
+  /// Delete the rows matching the where clause of the filter
+  /// and return how many were deleted.
+  /// A filter true for every row, see `GenericFilter::has_conditions`,
+  /// is refused with `Error::QueryBuilderError`
+  /// to avoid wiping the table by mistake, use `del_all` for that.
   fn del_by<D>(
     db: &DbDriver<D>,
     filter: &GenericFilter,
-  ) -> impl std::future::Future<Output = Result<(), diesel::result::Error>> + Send
+  ) -> impl std::future::Future<Output = Result<usize, TurboDieselError>> + Send
   where
     D: diesel::r2d2::R2D2Connection + Connection + LoadConnection + 'static,
-    Self: Sized + HasTable,
+    Self: Sized + HasTable + FilterableColumns<<D as Connection>::Backend>,
     <Self as HasTable>::Table: query_builder::QueryId + 'static,
     <<Self as HasTable>::Table as diesel::QuerySource>::FromClause:
       diesel::query_builder::QueryFragment<<D as Connection>::Backend>,
@@ -223,12 +3919,528 @@ pub trait DbModelDelBy {
   {
     async {
       let filter = filter.clone();
-      db.execute(move |mut conn| {
-        let query = Self::gen_del_query::<D>(&filter);
-        query.execute(&mut conn)?;
-        Ok::<_, diesel::result::Error>(())
+      if !filter.has_conditions() {
+        return Err(
+          diesel::result::Error::QueryBuilderError(
+            "Refusing to delete without a where clause, use del_all instead"
+              .into(),
+          )
+          .into(),
+        );
+      }
+      db.execute_op("del_by", move |conn| {
+        let query = Self::gen_del_query::<D>(&filter)?;
+        let count = query.execute(conn)?;
+        Ok::<_, diesel::result::Error>(count)
+      })
+      .await
+    }
+  }
+
+  /// `del_by` returning the deleted rows with `RETURNING`,
+  /// in a single statement so no row can be changed in between.
+  /// Not available on MySQL, which has no `RETURNING`.
+  fn del_by_returning<D>(
+    db: &DbDriver<D>,
+    filter: &GenericFilter,
+  ) -> impl Future<Output = Result<Vec<Self>, TurboDieselError>> + Send
+  where
+    D: diesel::r2d2::R2D2Connection + Connection + LoadConnection + 'static,
+    Self: Sized
+      + Send
+      + HasTable
+      + FilterableColumns<<D as Connection>::Backend>
+      + 'static,
+    <Self as HasTable>::Table: query_builder::QueryId + 'static,
+    query_builder::BoxedDeleteStatement<
+      'static,
+      <D as Connection>::Backend,
+      Self::Table,
+    >: query_dsl::LoadQuery<'static, D, Self>,
+    <D as diesel::Connection>::Backend:
+      diesel::internal::derives::multiconnection::DieselReserveSpecialization,
+  {
+    async {
+      let filter = filter.clone();
+      if !filter.has_conditions() {
+        return Err(
+          diesel::result::Error::QueryBuilderError(
+            "Refusing to delete without a where clause, use del_all instead"
+              .into(),
+          )
+          .into(),
+        );
+      }
+      db.execute_op("del_by_returning", move |conn| {
+        let query = Self::gen_del_query::<D>(&filter)?;
+        let rows = query.get_results(conn)?;
+        Ok::<_, diesel::result::Error>(rows)
+      })
+      .await
+    }
+  }
+
+  /// Delete every row of the table and return how many were deleted,
+  /// `Confirm::Yes` being the explicit go ahead.
+  fn del_all<D>(
+    db: &DbDriver<D>,
+    _confirm: Confirm,
+  ) -> impl std::future::Future<Output = Result<usize, TurboDieselError>> + Send
+  where
+    D: diesel::r2d2::R2D2Connection + Connection + LoadConnection + 'static,
+    Self: Sized + HasTable,
+    <Self as HasTable>::Table: query_builder::QueryId
+      + query_builder::IntoUpdateTarget
+      + HasTable<Table = Self::Table>
+      + 'static,
+    query_builder::DeleteStatement<
+      <Self::Table as HasTable>::Table,
+      <Self::Table as query_builder::IntoUpdateTarget>::WhereClause,
+    >: query_builder::QueryFragment<<D as Connection>::Backend>
+      + query_builder::QueryId,
+  {
+    async {
+      db.execute_op("del_all", move |conn| {
+        let count =
+          diesel::delete(<Self as HasTable>::table()).execute(conn)?;
+        Ok::<_, diesel::result::Error>(count)
+      })
+      .await
+    }
+  }
+}
+
+/// The changeset setting the `DeletedAt` column of `I` to the current time.
+pub type SoftDeleteChangeset<I> =
+  diesel::dsl::Eq<<I as DbModelSoftDelete>::DeletedAt, diesel::dsl::now>;
+
+/// The changeset setting the `DeletedAt` column of `I` back to `NULL`.
+pub type RestoreChangeset<I> = diesel::dsl::Eq<
+  <I as DbModelSoftDelete>::DeletedAt,
+  diesel::expression::SqlLiteral<
+    <<I as DbModelSoftDelete>::DeletedAt as Expression>::SqlType,
+  >,
+>;
+
+/// A model whose rows are never deleted, a nullable timestamp column
+/// is set to the current time instead.
+/// `#[derive(TurboModel)]` with `#[turbo(soft_delete(deleted_at))]`
+/// implements it and makes `gen_read_query` skip the soft deleted rows,
+/// so do `read_by`, `read_one`, `count_by`, `paginate` and `exists_by`
+/// unless the filter asks for them with `GenericFilter::with_deleted`.
+/// `read_by_pk` still finds them, `del_by_pk` and `del_by` still hard delete.
+pub trait DbModelSoftDelete: HasTable {
+  /// The nullable timestamp column, `NULL` while the row isn't deleted
+  type DeletedAt: Column<Table = Self::Table, SqlType: SingleValue>
+    + Default
+    + Send
+    + 'static;
+
+  /// Soft delete the row with the primary key `pk`,
+  /// return 0 when there was none and 1 otherwise.
+  /// Soft deleting it again moves the timestamp.
+  fn soft_del_by_pk<D, Pk>(
+    db: &DbDriver<D>,
+    pk: &Pk,
+  ) -> impl Future<Output = Result<usize, TurboDieselError>> + Send
+  where
+    D: diesel::r2d2::R2D2Connection + Connection + LoadConnection + 'static,
+    Pk: Sync + ToOwned + ?Sized,
+    <Pk as ToOwned>::Owned: Send + 'static,
+    Self: Sized,
+    Self::Table: query_dsl::methods::FindDsl<<Pk as ToOwned>::Owned>
+      + HasTable<Table = Self::Table>,
+    diesel::helper_types::Find<Self::Table, <Pk as ToOwned>::Owned>:
+      query_builder::IntoUpdateTarget + HasTable<Table = Self::Table>,
+    diesel::dsl::now:
+      diesel::expression::AsExpression<<Self::DeletedAt as Expression>::SqlType>,
+    SoftDeleteChangeset<Self>: AsChangeset<Target = Self::Table>,
+    query_builder::UpdateStatement<
+      Self::Table,
+      <diesel::helper_types::Find<Self::Table, <Pk as ToOwned>::Owned> as query_builder::IntoUpdateTarget>::WhereClause,
+      <SoftDeleteChangeset<Self> as AsChangeset>::Changeset,
+    >: query_builder::AsQuery
+      + query_builder::QueryFragment<<D as Connection>::Backend>
+      + query_builder::QueryId,
+  {
+    async {
+      let pk = pk.to_owned();
+      db.execute_op("soft_del_by_pk", move |conn| {
+        let changeset = Self::DeletedAt::default().eq(diesel::dsl::now);
+        let count = diesel::update(<Self::Table as HasTable>::table().find(pk))
+          .set(changeset)
+          .execute(conn)?;
+        Ok::<_, diesel::result::Error>(count)
+      })
+      .await
+    }
+  }
+
+  /// Soft delete the rows matching the where clause of the filter
+  /// and return how many were soft deleted.
+  /// Like `del_by` a where clause true for every row is refused.
+  fn soft_del_by<D>(
+    db: &DbDriver<D>,
+    filter: &GenericFilter,
+  ) -> impl Future<Output = Result<usize, TurboDieselError>> + Send
+  where
+    D: diesel::r2d2::R2D2Connection + Connection + LoadConnection + 'static,
+    Self: Sized + FilterableColumns<<D as Connection>::Backend>,
+    Self::Table:
+      query_builder::IntoUpdateTarget + HasTable<Table = Self::Table> + 'static,
+    diesel::dsl::now: diesel::expression::AsExpression<
+      <Self::DeletedAt as Expression>::SqlType,
+    >,
+    SoftDeleteChangeset<Self>: AsChangeset<Target = Self::Table>,
+    query_builder::UpdateStatement<
+      Self::Table,
+      <Self::Table as query_builder::IntoUpdateTarget>::WhereClause,
+    >: query_dsl::methods::BoxedDsl<
+      'static,
+      <D as Connection>::Backend,
+      Output = query_builder::BoxedUpdateStatement<
+        'static,
+        <D as Connection>::Backend,
+        Self::Table,
+      >,
+    >,
+    query_builder::BoxedUpdateStatement<
+      'static,
+      <D as Connection>::Backend,
+      Self::Table,
+      <SoftDeleteChangeset<Self> as AsChangeset>::Changeset,
+    >: query_builder::AsQuery
+      + query_builder::QueryFragment<<D as Connection>::Backend>
+      + query_builder::QueryId,
+    <D as diesel::Connection>::Backend:
+      diesel::internal::derives::multiconnection::DieselReserveSpecialization,
+  {
+    async {
+      let filter = filter.clone();
+      if !filter.has_conditions() {
+        return Err(
+          diesel::result::Error::QueryBuilderError(
+            "Refusing to soft delete without a where clause".into(),
+          )
+          .into(),
+        );
+      }
+      db.execute_op("soft_del_by", move |conn| {
+        let query =
+          diesel::update(<Self::Table as HasTable>::table()).into_boxed();
+        let changeset = Self::DeletedAt::default().eq(diesel::dsl::now);
+        let count = apply_where::<Self, _, _>(query, &filter)?
+          .set(changeset)
+          .execute(conn)?;
+        Ok::<_, diesel::result::Error>(count)
+      })
+      .await
+    }
+  }
+
+  /// Restore the soft deleted row with the primary key `pk`
+  /// by setting the column back to `NULL`,
+  /// return 0 when there was none and 1 otherwise.
+  fn restore_by_pk<D, Pk>(
+    db: &DbDriver<D>,
+    pk: &Pk,
+  ) -> impl Future<Output = Result<usize, TurboDieselError>> + Send
+  where
+    D: diesel::r2d2::R2D2Connection + Connection + LoadConnection + 'static,
+    Pk: Sync + ToOwned + ?Sized,
+    <Pk as ToOwned>::Owned: Send + 'static,
+    Self: Sized,
+    Self::Table: query_dsl::methods::FindDsl<<Pk as ToOwned>::Owned>
+      + HasTable<Table = Self::Table>,
+    diesel::helper_types::Find<Self::Table, <Pk as ToOwned>::Owned>:
+      query_builder::IntoUpdateTarget + HasTable<Table = Self::Table>,
+    diesel::expression::SqlLiteral<<Self::DeletedAt as Expression>::SqlType>:
+      diesel::expression::AsExpression<
+        <Self::DeletedAt as Expression>::SqlType,
+      >,
+    RestoreChangeset<Self>: AsChangeset<Target = Self::Table>,
+    query_builder::UpdateStatement<
+      Self::Table,
+      <diesel::helper_types::Find<Self::Table, <Pk as ToOwned>::Owned> as query_builder::IntoUpdateTarget>::WhereClause,
+      <RestoreChangeset<Self> as AsChangeset>::Changeset,
+    >: query_builder::AsQuery
+      + query_builder::QueryFragment<<D as Connection>::Backend>
+      + query_builder::QueryId,
+  {
+    async {
+      let pk = pk.to_owned();
+      db.execute_op("restore_by_pk", move |conn| {
+        let null =
+          diesel::dsl::sql::<<Self::DeletedAt as Expression>::SqlType>("NULL");
+        let changeset = Self::DeletedAt::default().eq(null);
+        let count = diesel::update(<Self::Table as HasTable>::table().find(pk))
+          .set(changeset)
+          .execute(conn)?;
+        Ok::<_, diesel::result::Error>(count)
+      })
+      .await
+    }
+  }
+}
+
+/// The values setting both timestamps of `I` to the current time.
+pub type CreateTimestamps<I> = (
+  diesel::dsl::Eq<<I as Timestamped>::CreatedAt, diesel::dsl::now>,
+  diesel::dsl::Eq<<I as Timestamped>::UpdatedAt, diesel::dsl::now>,
+);
+
+/// The changeset setting the `UpdatedAt` column of `I` to the current time.
+pub type UpdateTimestamp<I> =
+  diesel::dsl::Eq<<I as Timestamped>::UpdatedAt, diesel::dsl::now>;
+
+/// A model whose rows keep when they were created and last updated.
+/// `#[derive(TurboModel)]` with
+/// `#[turbo(timestamps(created_at, updated_at))]` implements it.
+/// It is opt-in, `create`, `update_by_pk` and `update_by` are unchanged
+/// and the timestamps are only managed by the methods of this trait.
+pub trait Timestamped: HasTable {
+  /// The timestamp column set once when the row is created
+  type CreatedAt: Column<Table = Self::Table, SqlType: SingleValue>
+    + Default
+    + Send
+    + 'static;
+  /// The timestamp column set on every update
+  type UpdatedAt: Column<Table = Self::Table, SqlType: SingleValue>
+    + Default
+    + Send
+    + 'static;
+
+  /// Insert `item` with both timestamps set to `now` and return the row.
+  /// `item` is any insertable leaving the timestamps out,
+  /// usually a struct deriving `Insertable` without them.
+  fn create_timestamped<D, N>(
+    db: &DbDriver<D>,
+    item: &N,
+  ) -> impl Future<Output = Result<Self, TurboDieselError>> + Send
+  where
+    D: diesel::r2d2::R2D2Connection + Connection + LoadConnection + 'static,
+    N: Clone + Send + Sync + 'static,
+    Self: Sized + Send + 'static,
+    Self::Table: diesel::Table,
+    diesel::dsl::now: diesel::expression::AsExpression<
+      <Self::CreatedAt as Expression>::SqlType,
+    >,
+    diesel::dsl::now: diesel::expression::AsExpression<
+      <Self::UpdatedAt as Expression>::SqlType,
+    >,
+    (N, CreateTimestamps<Self>): diesel::Insertable<Self::Table>,
+    query_builder::InsertStatement<
+      Self::Table,
+      <(N, CreateTimestamps<Self>) as diesel::Insertable<Self::Table>>::Values,
+    >: query_dsl::LoadQuery<'static, D, Self>,
+  {
+    async {
+      let item = item.to_owned();
+      db.execute_op("create_timestamped", move |conn| {
+        let now = (
+          Self::CreatedAt::default().eq(diesel::dsl::now),
+          Self::UpdatedAt::default().eq(diesel::dsl::now),
+        );
+        let item = diesel::insert_into(<Self as HasTable>::table())
+          .values((item, now))
+          .get_result(conn)?;
+        Ok::<_, diesel::result::Error>(item)
+      })
+      .await
+    }
+  }
+
+  /// `DbModelUpdate::update_by_pk` also setting `UpdatedAt` to `now`.
+  /// The changeset must leave the timestamps out.
+  fn update_timestamped_by_pk<D, Pk, C>(
+    db: &DbDriver<D>,
+    pk: &Pk,
+    changeset: C,
+  ) -> impl Future<Output = Result<Self, TurboDieselError>> + Send
+  where
+    D: diesel::r2d2::R2D2Connection + Connection + LoadConnection + 'static,
+    Pk: Sync + ToOwned + ?Sized,
+    <Pk as ToOwned>::Owned: Send + 'static,
+    C: AsChangeset<Target = Self::Table> + Send + 'static,
+    Self: Sized + Send + 'static,
+    Self::Table: query_dsl::methods::FindDsl<<Pk as ToOwned>::Owned>
+      + HasTable<Table = Self::Table>,
+    diesel::helper_types::Find<Self::Table, <Pk as ToOwned>::Owned>:
+      query_builder::IntoUpdateTarget + HasTable<Table = Self::Table>,
+    diesel::dsl::now: diesel::expression::AsExpression<
+      <Self::UpdatedAt as Expression>::SqlType,
+    >,
+    (C, UpdateTimestamp<Self>): AsChangeset<Target = Self::Table>,
+    diesel::helper_types::Update<
+      diesel::helper_types::Find<Self::Table, <Pk as ToOwned>::Owned>,
+      (C, UpdateTimestamp<Self>),
+    >: query_builder::AsQuery + query_dsl::LoadQuery<'static, D, Self>,
+  {
+    async {
+      let pk = pk.to_owned();
+      db.execute_op("update_timestamped_by_pk", move |conn| {
+        let now = Self::UpdatedAt::default().eq(diesel::dsl::now);
+        let item = diesel::update(<Self::Table as HasTable>::table().find(pk))
+          .set((changeset, now))
+          .get_result(conn)?;
+        Ok::<_, diesel::result::Error>(item)
+      })
+      .await
+    }
+  }
+
+  /// `DbModelUpdate::update_by` also setting `UpdatedAt` to `now`.
+  /// The changeset must leave the timestamps out.
+  fn update_timestamped_by<D, C>(
+    db: &DbDriver<D>,
+    filter: &GenericFilter,
+    changeset: C,
+  ) -> impl Future<Output = Result<usize, TurboDieselError>> + Send
+  where
+    D: diesel::r2d2::R2D2Connection + Connection + LoadConnection + 'static,
+    C: AsChangeset<Target = Self::Table> + Send + 'static,
+    Self: Sized + FilterableColumns<<D as Connection>::Backend>,
+    Self::Table:
+      query_builder::IntoUpdateTarget + HasTable<Table = Self::Table> + 'static,
+    diesel::dsl::now: diesel::expression::AsExpression<
+      <Self::UpdatedAt as Expression>::SqlType,
+    >,
+    (C, UpdateTimestamp<Self>): AsChangeset<Target = Self::Table>,
+    query_builder::UpdateStatement<
+      Self::Table,
+      <Self::Table as query_builder::IntoUpdateTarget>::WhereClause,
+    >: query_dsl::methods::BoxedDsl<
+      'static,
+      <D as Connection>::Backend,
+      Output = query_builder::BoxedUpdateStatement<
+        'static,
+        <D as Connection>::Backend,
+        Self::Table,
+      >,
+    >,
+    query_builder::BoxedUpdateStatement<
+      'static,
+      <D as Connection>::Backend,
+      Self::Table,
+      <(C, UpdateTimestamp<Self>) as AsChangeset>::Changeset,
+    >: query_builder::AsQuery
+      + query_builder::QueryFragment<<D as Connection>::Backend>
+      + query_builder::QueryId,
+    <D as diesel::Connection>::Backend:
+      diesel::internal::derives::multiconnection::DieselReserveSpecialization,
+  {
+    async {
+      let filter = filter.clone();
+      if !filter.has_conditions() {
+        return Err(
+          diesel::result::Error::QueryBuilderError(
+            "Refusing to update without a where clause, use update_all instead"
+              .into(),
+          )
+          .into(),
+        );
+      }
+      db.execute_op("update_timestamped_by", move |conn| {
+        let query =
+          diesel::update(<Self::Table as HasTable>::table()).into_boxed();
+        let now = Self::UpdatedAt::default().eq(diesel::dsl::now);
+        let count = apply_where::<Self, _, _>(query, &filter)?
+          .set((changeset, now))
+          .execute(conn)?;
+        Ok::<_, diesel::result::Error>(count)
       })
       .await
     }
   }
 }
+
+/// The changeset incrementing the `Version` column of `I`.
+pub type VersionChangeset<I> = diesel::dsl::Eq<
+  <I as DbModelVersioned>::Version,
+  diesel::expression::SqlLiteral<
+    <<I as DbModelVersioned>::Version as Expression>::SqlType,
+  >,
+>;
+
+/// A model updated with optimistic concurrency control:
+/// every update checks the integer column still holds the version
+/// the caller read, and increments it.
+/// `#[derive(TurboModel)]` with `#[turbo(version(version))]` implements it.
+/// `update_by_pk` and `update_by` ignore the version.
+pub trait DbModelVersioned: HasTable {
+  /// The integer column holding the version of the row,
+  /// compared with the expected `i32` and incremented in SQL
+  type Version: Column<Table = Self::Table, SqlType: SingleValue>
+    + Default
+    + Send
+    + 'static;
+
+  /// Update the row with the primary key `pk` if it is still at the
+  /// `expected` version, increment the version and return the row.
+  /// `TurboDieselError::Conflict` is returned when no row matches,
+  /// because it was updated since it was read or deleted.
+  /// The changeset must leave the version out.
+  fn update_versioned_by_pk<D, Pk, C>(
+    db: &DbDriver<D>,
+    pk: &Pk,
+    expected: i32,
+    changeset: C,
+  ) -> impl Future<Output = Result<Self, TurboDieselError>> + Send
+  where
+    D: diesel::r2d2::R2D2Connection + Connection + LoadConnection + 'static,
+    Pk: Sync + ToOwned + ?Sized,
+    <Pk as ToOwned>::Owned: Send + 'static,
+    C: AsChangeset<Target = Self::Table> + Send + 'static,
+    Self: Sized + Send + 'static,
+    Self::Table: query_dsl::methods::FindDsl<<Pk as ToOwned>::Owned>
+      + HasTable<Table = Self::Table>,
+    diesel::helper_types::Find<Self::Table, <Pk as ToOwned>::Owned>:
+      query_builder::IntoUpdateTarget + HasTable<Table = Self::Table>,
+    i32: ToSql<diesel::sql_types::Integer, <D as Connection>::Backend>,
+    diesel::expression::SqlLiteral<<Self::Version as Expression>::SqlType>:
+      diesel::expression::AsExpression<<Self::Version as Expression>::SqlType>,
+    (C, VersionChangeset<Self>): AsChangeset<Target = Self::Table>,
+    query_builder::UpdateStatement<
+      Self::Table,
+      <diesel::helper_types::Find<Self::Table, <Pk as ToOwned>::Owned> as query_builder::IntoUpdateTarget>::WhereClause,
+    >: query_dsl::methods::BoxedDsl<
+      'static,
+      <D as Connection>::Backend,
+      Output = query_builder::BoxedUpdateStatement<
+        'static,
+        <D as Connection>::Backend,
+        Self::Table,
+      >,
+    >,
+    query_builder::BoxedUpdateStatement<
+      'static,
+      <D as Connection>::Backend,
+      Self::Table,
+      <(C, VersionChangeset<Self>) as AsChangeset>::Changeset,
+    >: query_builder::AsQuery + query_dsl::LoadQuery<'static, D, Self>,
+    <D as diesel::Connection>::Backend:
+      diesel::internal::derives::multiconnection::DieselReserveSpecialization,
+  {
+    async move {
+      let pk = pk.to_owned();
+      let item = db
+        .execute_op("update_versioned_by_pk", move |conn| {
+          let name = <Self::Version as Column>::NAME;
+          let expected: BoxedPredicate<Self::Table, _> = Box::new(
+            diesel::dsl::sql::<diesel::sql_types::Bool>(&format!("{name} = "))
+              .bind::<diesel::sql_types::Integer, _>(expected),
+          );
+          let next = diesel::dsl::sql(&format!("{name} + 1"));
+          let next = Self::Version::default().eq(next);
+          diesel::update(<Self::Table as HasTable>::table().find(pk))
+            .into_boxed()
+            .filter(expected)
+            .set((changeset, next))
+            .get_result(conn)
+            .optional()
+        })
+        .await?;
+      item.ok_or(TurboDieselError::Conflict)
+    }
+  }
+}
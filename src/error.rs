@@ -0,0 +1,225 @@
+use diesel::r2d2::PoolError;
+
+/// The error returned by every operation of the crate.
+#[derive(Debug)]
+pub enum TurboDieselError {
+  /// Every connection of the pool stayed in use for the whole
+  /// `connection_timeout`, the pool is saturated: back off and retry.
+  PoolTimeout,
+  /// The pool couldn't be created or connect to the database,
+  /// or the task holding the connection failed.
+  Connection(String),
+  /// The query failed.
+  Query(diesel::result::Error),
+  /// The query expected a row and got none.
+  NotFound,
+  /// The row was changed by someone else since it was read,
+  /// its version isn't the expected one anymore.
+  Conflict,
+  /// A unique constraint rejected the row, a duplicate key say.
+  UniqueViolation(ConstraintViolation),
+  /// A foreign key references no row, or a row still referenced was deleted.
+  ForeignKeyViolation(ConstraintViolation),
+  /// A column without a default was left null.
+  NotNullViolation(ConstraintViolation),
+  /// A check constraint rejected the row.
+  CheckViolation(ConstraintViolation),
+  /// The statement ran longer than the `statement_timeout` of the pool.
+  Timeout(diesel::result::Error),
+  /// The clause only applies on another backend, a JSON clause on SQLite say,
+  /// see `GenericClause` for the backends of each clause.
+  UnsupportedOnBackend {
+    /// The name of the clause, `JsonContains`
+    clause: &'static str,
+    /// The name of the backend, `SQLite`
+    backend: &'static str,
+  },
+  /// The filter was refused before running the query,
+  /// an offset without a limit under a `max_offset` say.
+  InvalidFilter(String),
+  /// The caller stopped waiting for the query,
+  /// see `DbDriver::execute_cancellable`.
+  Cancelled,
+}
+
+/// The details of a constraint violation reported by the database.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConstraintViolation {
+  /// The name of the constraint, when the backend reports it
+  /// (Postgres does, SQLite doesn't)
+  pub constraint: Option<String>,
+  /// The table of the constraint, when the backend reports it
+  pub table: Option<String>,
+  /// The message of the database
+  pub message: String,
+}
+
+impl ConstraintViolation {
+  fn new(info: &dyn diesel::result::DatabaseErrorInformation) -> Self {
+    Self {
+      constraint: info.constraint_name().map(str::to_owned),
+      table: info.table_name().map(str::to_owned),
+      message: info.message().to_owned(),
+    }
+  }
+}
+
+/// The error is a serialization failure or a deadlock,
+/// the transaction failed only because of a concurrent one
+/// and running it again may succeed.
+pub fn is_retryable(err: &diesel::result::Error) -> bool {
+  use diesel::result::{DatabaseErrorKind, Error};
+  match err {
+    Error::DatabaseError(DatabaseErrorKind::SerializationFailure, _) => true,
+    // Postgres reports the deadlocks with their own SQLSTATE 40P01
+    Error::DatabaseError(_, info) => {
+      info.message().starts_with("deadlock detected")
+    }
+    _ => false,
+  }
+}
+
+/// The statement was cancelled by a timeout of the database.
+fn is_timeout(info: &dyn diesel::result::DatabaseErrorInformation) -> bool {
+  let message = info.message();
+  // Postgres uses the SQLSTATE 57014 of any cancelled query
+  message.starts_with("canceling statement due to statement timeout")
+    || message.contains("maximum statement execution time exceeded")
+}
+
+impl TurboDieselError {
+  /// The name of the variant, the `kind` label of
+  /// `turbo_diesel_query_errors_total`.
+  #[cfg(feature = "metrics")]
+  pub(crate) fn kind(&self) -> &'static str {
+    match self {
+      Self::PoolTimeout => "pool_timeout",
+      Self::Connection(_) => "connection",
+      Self::Query(_) => "query",
+      Self::NotFound => "not_found",
+      Self::Conflict => "conflict",
+      Self::UniqueViolation(_) => "unique_violation",
+      Self::ForeignKeyViolation(_) => "foreign_key_violation",
+      Self::NotNullViolation(_) => "not_null_violation",
+      Self::CheckViolation(_) => "check_violation",
+      Self::Timeout(_) => "timeout",
+      Self::UnsupportedOnBackend { .. } => "unsupported_on_backend",
+      Self::InvalidFilter(_) => "invalid_filter",
+      Self::Cancelled => "cancelled",
+    }
+  }
+}
+
+/// A `Result` defaulting to `TurboDieselError`.
+pub type Result<T, E = TurboDieselError> = std::result::Result<T, E>;
+
+impl std::fmt::Display for TurboDieselError {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      Self::PoolTimeout => write!(f, "Timed out waiting for a connection"),
+      Self::Connection(msg) => write!(f, "Connection error: {msg}"),
+      Self::Query(err) => write!(f, "Query error: {err}"),
+      Self::NotFound => write!(f, "Record not found"),
+      Self::Conflict => write!(f, "Record was modified concurrently"),
+      Self::UniqueViolation(err) => {
+        write!(f, "Unique constraint violated: {}", err.message)
+      }
+      Self::ForeignKeyViolation(err) => {
+        write!(f, "Foreign key constraint violated: {}", err.message)
+      }
+      Self::NotNullViolation(err) => {
+        write!(f, "Not null constraint violated: {}", err.message)
+      }
+      Self::CheckViolation(err) => {
+        write!(f, "Check constraint violated: {}", err.message)
+      }
+      Self::Timeout(err) => write!(f, "Query timed out: {err}"),
+      Self::UnsupportedOnBackend { clause, backend } => {
+        write!(f, "{clause} is not supported on {backend}")
+      }
+      Self::InvalidFilter(msg) => write!(f, "Invalid filter: {msg}"),
+      Self::Cancelled => write!(f, "Query was cancelled"),
+    }
+  }
+}
+
+impl std::error::Error for TurboDieselError {
+  fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+    match self {
+      Self::Query(err) | Self::Timeout(err) => Some(err),
+      Self::PoolTimeout
+      | Self::Connection(_)
+      | Self::NotFound
+      | Self::Conflict
+      | Self::UniqueViolation(_)
+      | Self::ForeignKeyViolation(_)
+      | Self::NotNullViolation(_)
+      | Self::CheckViolation(_)
+      | Self::UnsupportedOnBackend { .. }
+      | Self::InvalidFilter(_)
+      | Self::Cancelled => None,
+    }
+  }
+}
+
+/// r2d2 only fails on a timeout, with the error of the last attempt
+/// to connect when there was one: the database is unreachable
+/// and it's a `Connection`, the pool is saturated otherwise.
+/// The error keeps that attempt only in its message, this is best effort:
+/// the driver tells them apart with the state of the pool instead.
+impl From<PoolError> for TurboDieselError {
+  fn from(err: PoolError) -> Self {
+    // The last error is only in the message, after `: `
+    let message = err.to_string();
+    if message.contains(": ") {
+      Self::Connection(message)
+    } else {
+      Self::PoolTimeout
+    }
+  }
+}
+
+/// `diesel::result::Error::NotFound` becomes `TurboDieselError::NotFound`
+/// the constraint violations and the timeouts their own variant,
+/// a `TurboDieselError` carried by a `QueryBuilderError` is unwrapped,
+/// every other error is kept as `TurboDieselError::Query`.
+impl From<diesel::result::Error> for TurboDieselError {
+  fn from(err: diesel::result::Error) -> Self {
+    use diesel::result::{DatabaseErrorKind, Error};
+    match err {
+      Error::NotFound => Self::NotFound,
+      Error::DatabaseError(kind, info) => {
+        let violation = || ConstraintViolation::new(info.as_ref());
+        match kind {
+          DatabaseErrorKind::UniqueViolation => {
+            Self::UniqueViolation(violation())
+          }
+          DatabaseErrorKind::ForeignKeyViolation => {
+            Self::ForeignKeyViolation(violation())
+          }
+          DatabaseErrorKind::NotNullViolation => {
+            Self::NotNullViolation(violation())
+          }
+          DatabaseErrorKind::CheckViolation => {
+            Self::CheckViolation(violation())
+          }
+          kind if is_timeout(info.as_ref()) => {
+            Self::Timeout(Error::DatabaseError(kind, info))
+          }
+          kind => Self::Query(Error::DatabaseError(kind, info)),
+        }
+      }
+      Error::QueryBuilderError(err) => match err.downcast::<Self>() {
+        Ok(err) => *err,
+        Err(err) => Self::Query(Error::QueryBuilderError(err)),
+      },
+      err => Self::Query(err),
+    }
+  }
+}
+
+impl From<TurboDieselError> for std::io::Error {
+  fn from(err: TurboDieselError) -> Self {
+    std::io::Error::other(err)
+  }
+}
@@ -1,51 +1,339 @@
 use std::collections::HashMap;
 
+use diesel::backend::Backend;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::column::FilterableColumns;
+
 /// Generic where clause
-#[derive(Debug, Clone)]
+///
+/// Every clause applies on SQLite, Postgres and MySQL, but these:
+///
+/// - `JsonContains`, `HasKey`, `JsonPathEq` and `JsonPathExists`
+///   on Postgres jsonb columns only
+/// - `ArrayContains` and `ArrayOverlaps` on Postgres arrays only
+/// - `FullText` on Postgres text columns only
+///
+/// They fail with `TurboDieselError::UnsupportedOnBackend` on the other
+/// backends, before the column is looked up.
+///
+/// With the `serde` feature a clause is named in kebab-case,
+/// `ILike` being `i-like`. A clause without operand is that name alone,
+/// `"is-null"` and `"is-not-null"`, the others an object of one key:
+///
+/// - one operand as is, `{"eq": 1}`, `{"starts-with": "te"}`,
+///   `{"json-contains": {"a": 1}}`
+/// - a list as an array, `{"in": [1, "2"]}`, `{"json-path-exists": ["a"]}`
+/// - two operands as an array of both, `{"between": [1, 10]}`,
+///   `{"json-path-eq": [["a", "b"], "x"]}`, `{"full-text": ["rust", null]}`
+///   without a configuration
+///
+/// A `GenericValue` is the JSON value itself, see its variants.
+/// `ExistsRelated` is neither serialized nor deserialized.
+#[derive(Debug, Clone, PartialEq)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
 pub enum GenericClause {
-  /// Equal
-  Eq(String),
-  /// Not equal
-  Ne(String),
+  /// Equal, `IS NULL` with `GenericValue::Null`
+  Eq(GenericValue),
+  /// Not equal, `IS NOT NULL` with `GenericValue::Null`
+  Ne(GenericValue),
   /// Greater than
-  Gt(String),
+  Gt(GenericValue),
   /// Less than
-  Lt(String),
+  Lt(GenericValue),
   /// Greater than or equal
-  Ge(String),
+  Ge(GenericValue),
   /// Less than or equal
-  Le(String),
+  Le(GenericValue),
   /// Like
   Like(String),
   /// Not like
   NotLike(String),
+  /// Case insensitive like
+  ILike(String),
+  /// Starts with the text, `%` and `_` are matched literally
+  StartsWith(String),
+  /// Ends with the text, `%` and `_` are matched literally
+  EndsWith(String),
+  /// Contains the text, `%` and `_` are matched literally,
+  /// `LIKE '%text%'`: see `JsonContains` for a JSON document
+  ContainsText(String),
+  /// Between, bounds included
+  Between(GenericValue, GenericValue),
+  /// Not between
+  NotBetween(GenericValue, GenericValue),
   /// In
-  In(Vec<String>),
+  In(Vec<GenericValue>),
   /// Not in
-  NotIn(Vec<String>),
+  NotIn(Vec<GenericValue>),
   /// Is null
   IsNull,
   /// Is not null
   IsNotNull,
-  /// JSON contains
-  Contains(serde_json::Value),
-  /// JSON Has key
+  /// The JSON document contains the value, `@>`, Postgres jsonb only.
+  /// See `ContainsText` for a substring of a text
+  JsonContains(serde_json::Value),
+  /// The JSON object has the key, `?`, Postgres jsonb only
   HasKey(String),
+  /// The JSON value at the path equals the value, Postgres jsonb only.
+  /// A string is compared as text with `#>>`, other values with `#>`.
+  JsonPathEq(Vec<String>, serde_json::Value),
+  /// The JSON path exists, Postgres jsonb only
+  JsonPathExists(Vec<String>),
+  /// The array contains every value, `@>`, Postgres arrays only
+  ArrayContains(Vec<GenericValue>),
+  /// The array has a value in common with the values, `&&`,
+  /// Postgres arrays only
+  ArrayOverlaps(Vec<GenericValue>),
+  /// The text matches the full text search query, Postgres only:
+  /// `to_tsvector(column) @@ plainto_tsquery(query)`, with the text search
+  /// configuration, `english` say, when given and the default one otherwise
+  FullText(String, Option<String>),
+  /// A row of the related table has its column, the foreign key,
+  /// equal to this column: `ExistsRelated("orders".into(), "user_id".into())`
+  /// on `id` renders
+  /// `EXISTS (SELECT 1 FROM orders WHERE orders.user_id = users.id)`,
+  /// the users having an order.
+  /// The names are quoted but not checked, never take them from a request:
+  /// the variant is skipped by serde.
+  #[cfg_attr(feature = "serde", serde(skip))]
+  ExistsRelated(String, String),
+}
+
+impl GenericClause {
+  /// Whether the clause can leave rows out, see `has_conditions`.
+  fn restricts(&self) -> bool {
+    !matches!(
+      self,
+      Self::NotIn(values) | Self::ArrayContains(values) if values.is_empty()
+    )
+  }
+
+  fn push_operands(&self, operands: &mut Vec<GenericValue>) {
+    match self {
+      Self::Eq(value)
+      | Self::Ne(value)
+      | Self::Gt(value)
+      | Self::Lt(value)
+      | Self::Ge(value)
+      | Self::Le(value) => operands.push(value.clone()),
+      Self::Like(text)
+      | Self::NotLike(text)
+      | Self::ILike(text)
+      | Self::StartsWith(text)
+      | Self::EndsWith(text)
+      | Self::ContainsText(text)
+      | Self::HasKey(text)
+      | Self::FullText(text, _) => operands.push(text.as_str().into()),
+      Self::Between(lower, upper) | Self::NotBetween(lower, upper) => {
+        operands.extend([lower.clone(), upper.clone()]);
+      }
+      Self::In(values)
+      | Self::NotIn(values)
+      | Self::ArrayContains(values)
+      | Self::ArrayOverlaps(values) => operands.extend(values.iter().cloned()),
+      Self::JsonContains(json) | Self::JsonPathEq(_, json) => {
+        operands.push(json.clone().into());
+      }
+      Self::IsNull
+      | Self::IsNotNull
+      | Self::JsonPathExists(_)
+      | Self::ExistsRelated(..) => {}
+    }
+  }
+
+  /// The name of the clause when it only applies on Postgres.
+  pub(crate) fn postgres_only(&self) -> Option<&'static str> {
+    let name = match self {
+      Self::JsonContains(_) => "JsonContains",
+      Self::HasKey(_) => "HasKey",
+      Self::JsonPathEq(..) => "JsonPathEq",
+      Self::JsonPathExists(_) => "JsonPathExists",
+      Self::ArrayContains(_) => "ArrayContains",
+      Self::ArrayOverlaps(_) => "ArrayOverlaps",
+      Self::FullText(..) => "FullText",
+      _ => return None,
+    };
+    Some(name)
+  }
+}
+
+/// Operand of a clause, converted to the type of the column.
+/// Text is parsed when the column isn't a text,
+/// so `"18"` still works on an integer column.
+/// With the `serde` feature it's the JSON value as is: a number without
+/// a fraction is an `Int`, `null` is `Null` and an array or an object
+/// is `Json`, so `Json(serde_json::Value::Null)` comes back as `Null`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(untagged))]
+pub enum GenericValue {
+  /// Integer
+  Int(i64),
+  /// Floating point number
+  Float(f64),
+  /// Boolean
+  Bool(bool),
+  /// Text
+  Text(String),
+  /// Null
+  Null,
+  /// JSON array or object
+  Json(serde_json::Value),
+}
+
+impl From<i64> for GenericValue {
+  fn from(value: i64) -> Self {
+    Self::Int(value)
+  }
+}
+
+impl From<i32> for GenericValue {
+  fn from(value: i32) -> Self {
+    Self::Int(value.into())
+  }
+}
+
+impl From<f64> for GenericValue {
+  fn from(value: f64) -> Self {
+    Self::Float(value)
+  }
+}
+
+impl From<bool> for GenericValue {
+  fn from(value: bool) -> Self {
+    Self::Bool(value)
+  }
+}
+
+impl From<String> for GenericValue {
+  fn from(value: String) -> Self {
+    Self::Text(value)
+  }
+}
+
+impl From<&str> for GenericValue {
+  fn from(value: &str) -> Self {
+    Self::Text(value.to_owned())
+  }
+}
+
+/// JSON scalars become the matching variant.
+impl From<serde_json::Value> for GenericValue {
+  fn from(value: serde_json::Value) -> Self {
+    match value {
+      serde_json::Value::Null => Self::Null,
+      serde_json::Value::Bool(value) => Self::Bool(value),
+      serde_json::Value::Number(number) => match number.as_i64() {
+        Some(value) => Self::Int(value),
+        None => Self::Float(number.as_f64().unwrap_or(f64::NAN)),
+      },
+      serde_json::Value::String(value) => Self::Text(value),
+      value => Self::Json(value),
+    }
+  }
+}
+
+/// Nested where clauses
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
+pub enum FilterGroup {
+  /// Every group must match, an empty list matches every row
+  And(Vec<FilterGroup>),
+  /// One group must match, an empty list matches no row
+  Or(Vec<FilterGroup>),
+  /// A clause applied on a column
+  Leaf(String, GenericClause),
+  /// The group must not match, rendered as `NOT (...)`
+  Not(Box<FilterGroup>),
+}
+
+impl FilterGroup {
+  pub fn leaf(column: &str, clause: GenericClause) -> Self {
+    Self::Leaf(column.to_owned(), clause)
+  }
+
+  /// Whether the group can leave rows out, see `has_conditions`.
+  fn restricts(&self) -> bool {
+    match self {
+      Self::And(groups) => groups.iter().any(Self::restricts),
+      Self::Or(groups) => groups.iter().all(Self::restricts),
+      Self::Leaf(_, clause) => clause.restricts(),
+      Self::Not(group) => group.restricts(),
+    }
+  }
+
+  fn push_operands(&self, operands: &mut Vec<GenericValue>) {
+    match self {
+      Self::And(groups) | Self::Or(groups) => {
+        for group in groups {
+          group.push_operands(operands);
+        }
+      }
+      Self::Leaf(_, clause) => clause.push_operands(operands),
+      Self::Not(group) => group.push_operands(operands),
+    }
+  }
+}
+
+/// `!group` wraps the group in `FilterGroup::Not`.
+impl std::ops::Not for FilterGroup {
+  type Output = Self;
+
+  fn not(self) -> Self {
+    Self::Not(Box::new(self))
+  }
+}
+
+/// Direction of an order by
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
+pub enum OrderDirection {
+  /// Ascending
+  Asc,
+  /// Descending
+  Desc,
 }
 
 /// Generic filter for list operation
 #[derive(Default, Debug, Clone)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct GenericFilter {
-  /// Where clause
-  #[cfg_attr(feature = "serde", serde(rename = "where"))]
-  pub r#where: Option<HashMap<String, GenericClause>>,
-  /// Limit number of items default (100)
+  /// Where clauses, ANDed together in order.
+  /// A column can appear several times, e.g. for a range.
+  #[cfg_attr(
+    feature = "serde",
+    serde(
+      rename = "where",
+      default,
+      deserialize_with = "where_serde::deserialize"
+    )
+  )]
+  pub r#where: Option<Vec<(String, GenericClause)>>,
+  /// Groups of clauses, ANDed together and with `r#where`
+  #[cfg_attr(feature = "serde", serde(default))]
+  pub groups: Option<Vec<FilterGroup>>,
+  /// Limit number of items, the default limit of the driver when `None`:
+  /// `DEFAULT_LIMIT` (100) unless changed with `PoolConfig::default_limit`
+  /// or `DbDriver::with_default_limit`
   pub limit: Option<usize>,
   /// Offset to navigate through items
   pub offset: Option<usize>,
+  /// Columns to order by, in order of priority
+  pub order: Option<Vec<(String, OrderDirection)>>,
+  /// Also read the soft deleted rows of a `DbModelSoftDelete` model
+  #[cfg_attr(feature = "serde", serde(default))]
+  pub with_deleted: bool,
+  /// Keep the first row of each distinct value of these columns,
+  /// `DISTINCT ON` on Postgres only.
+  /// The rows are ordered by these columns first, then by `order`,
+  /// so `order` picks the row kept. Counting ignores it.
+  #[cfg_attr(feature = "serde", serde(default))]
+  pub distinct_on: Option<Vec<String>>,
 }
 
 impl GenericFilter {
@@ -58,20 +346,280 @@ impl GenericFilter {
     self
   }
 
+  /// Return every matching row, ignoring the default limit of the driver.
+  pub fn unlimited(mut self) -> Self {
+    self.limit = Some(usize::MAX);
+    self
+  }
+
+  /// Include the soft deleted rows, see `DbModelSoftDelete`.
+  pub fn with_deleted(mut self) -> Self {
+    self.with_deleted = true;
+    self
+  }
+
   pub fn offset(mut self, offset: usize) -> Self {
     self.offset = Some(offset);
     self
   }
 
-  pub fn r#where(mut self, key: &str, clause: GenericClause) -> Self {
-    if self.r#where.is_none() {
-      self.r#where = Some(HashMap::new());
+  /// Whether the where clauses and the groups can leave rows out,
+  /// the deletes and updates of a filter refuse one that can't.
+  /// An empty `NotIn` or `ArrayContains` list is true for every row,
+  /// an `And` restricts with one group that does, an `Or` when they all do
+  /// and a `Not` when its group does, so `!GenericFilter::new()` doesn't.
+  pub fn has_conditions(&self) -> bool {
+    self
+      .r#where
+      .iter()
+      .flatten()
+      .any(|(_, clause)| clause.restricts())
+      || self.groups.iter().flatten().any(FilterGroup::restricts)
+  }
+
+  /// The operands of the where clauses then of the groups, in order,
+  /// the values of a list one by one and the texts of the text clauses
+  /// as `GenericValue::Text`.
+  /// The JSON paths and the text search configuration are left out.
+  pub fn operands(&self) -> Vec<GenericValue> {
+    let mut operands = Vec::new();
+    for (_, clause) in self.r#where.iter().flatten() {
+      clause.push_operands(&mut operands);
     }
+    for group in self.groups.iter().flatten() {
+      group.push_operands(&mut operands);
+    }
+    operands
+  }
+
+  pub fn order_by(mut self, column: &str, direction: OrderDirection) -> Self {
+    self
+      .order
+      .get_or_insert_with(Vec::new)
+      .push((column.to_owned(), direction));
+    self
+  }
+
+  pub fn r#where(mut self, key: &str, clause: GenericClause) -> Self {
     self
       .r#where
-      .as_mut()
-      .unwrap()
-      .insert(key.to_owned(), clause);
+      .get_or_insert_with(Vec::new)
+      .push((key.to_owned(), clause));
+    self
+  }
+
+  pub fn where_group(mut self, group: FilterGroup) -> Self {
+    self.groups.get_or_insert_with(Vec::new).push(group);
+    self
+  }
+
+  /// Keep one row per distinct value of the column, see `distinct_on`.
+  pub fn distinct_on(mut self, column: &str) -> Self {
     self
+      .distinct_on
+      .get_or_insert_with(Vec::new)
+      .push(column.to_owned());
+    self
+  }
+
+  /// Combine two filters, a base filter and one built from a request say.
+  ///
+  /// The where clauses and the groups of both are ANDed, `self`'s first.
+  /// A column in both keeps both clauses, so `other` can only narrow
+  /// the rows of `self`, never widen them.
+  /// `limit`, `offset`, `order` and `distinct_on` are taken from `other`
+  /// when it has them, from `self` otherwise.
+  /// The soft deleted rows are read when either filter asks for them.
+  pub fn merge(self, other: Self) -> Self {
+    Self {
+      r#where: merge_lists(self.r#where, other.r#where),
+      groups: merge_lists(self.groups, other.groups),
+      limit: other.limit.or(self.limit),
+      offset: other.offset.or(self.offset),
+      order: other.order.or(self.order),
+      with_deleted: self.with_deleted || other.with_deleted,
+      distinct_on: other.distinct_on.or(self.distinct_on),
+    }
+  }
+
+  /// Check every column of the where clauses, the groups, the order
+  /// and `distinct_on` against `FilterableColumns::COLUMNS` of the model.
+  /// Return the unknown names, once each and in the order they appear.
+  pub fn validate<I, DB>(&self) -> Result<(), Vec<String>>
+  where
+    DB: Backend,
+    I: FilterableColumns<DB>,
+  {
+    let mut invalid = Vec::new();
+    let mut check = |column: &str| {
+      if !I::COLUMNS.contains(&column) && !invalid.iter().any(|c| c == column) {
+        invalid.push(column.to_owned());
+      }
+    };
+    for (column, _) in self.r#where.iter().flatten() {
+      check(column);
+    }
+    let mut groups = self.groups.iter().flatten().rev().collect::<Vec<_>>();
+    while let Some(group) = groups.pop() {
+      match group {
+        FilterGroup::And(items) | FilterGroup::Or(items) => {
+          groups.extend(items.iter().rev());
+        }
+        FilterGroup::Not(group) => groups.push(group),
+        FilterGroup::Leaf(column, _) => check(column),
+      }
+    }
+    for (column, _) in self.order.iter().flatten() {
+      check(column);
+    }
+    for column in self.distinct_on.iter().flatten() {
+      check(column);
+    }
+    if invalid.is_empty() {
+      Ok(())
+    } else {
+      Err(invalid)
+    }
+  }
+}
+
+/// Negate the where clauses and the groups of the filter together,
+/// `!filter` matches the rows `filter` doesn't, `NOT (a = 1 AND b = 2)`.
+/// The limit, the offset, the order and the other settings are kept.
+/// A filter without clauses matches every row, so no row once negated.
+impl std::ops::Not for GenericFilter {
+  type Output = Self;
+
+  fn not(mut self) -> Self {
+    let mut groups = self
+      .r#where
+      .take()
+      .into_iter()
+      .flatten()
+      .map(|(column, clause)| FilterGroup::Leaf(column, clause))
+      .collect::<Vec<_>>();
+    groups.extend(self.groups.take().into_iter().flatten());
+    self.groups = Some(vec![!FilterGroup::And(groups)]);
+    self
+  }
+}
+
+/// A filter of the where clauses, in order, without limit nor order.
+impl From<Vec<(String, GenericClause)>> for GenericFilter {
+  fn from(clauses: Vec<(String, GenericClause)>) -> Self {
+    Self {
+      r#where: Some(clauses),
+      ..Self::default()
+    }
+  }
+}
+
+/// A filter of a clause per column, without limit nor order.
+/// The clauses are sorted by column, the map has no order of its own
+/// and the same map gives the same SQL.
+impl From<HashMap<String, GenericClause>> for GenericFilter {
+  fn from(clauses: HashMap<String, GenericClause>) -> Self {
+    let mut clauses = clauses.into_iter().collect::<Vec<_>>();
+    clauses.sort_by(|(a, _), (b, _)| a.cmp(b));
+    clauses.into()
+  }
+}
+
+/// Turn the query of a handler into a filter,
+/// usually implemented with `#[derive(IntoFilter)]`.
+pub trait IntoFilter {
+  fn into_filter(self) -> GenericFilter;
+}
+
+/// Append `other` to `list`, `None` when both are.
+fn merge_lists<T>(
+  list: Option<Vec<T>>,
+  other: Option<Vec<T>>,
+) -> Option<Vec<T>> {
+  match (list, other) {
+    (Some(mut list), Some(other)) => {
+      list.extend(other);
+      Some(list)
+    }
+    (list, other) => list.or(other),
+  }
+}
+
+/// Accept both the list form `[["age", {"gt": "18"}], ...]`
+/// and the former object form `{"age": {"gt": "18"}}` for `where`.
+#[cfg(feature = "serde")]
+mod where_serde {
+  use super::GenericClause;
+
+  type Where = Vec<(String, GenericClause)>;
+
+  pub fn deserialize<'de, D>(deserializer: D) -> Result<Option<Where>, D::Error>
+  where
+    D: serde::Deserializer<'de>,
+  {
+    deserializer.deserialize_option(OptionVisitor)
+  }
+
+  struct OptionVisitor;
+
+  impl<'de> serde::de::Visitor<'de> for OptionVisitor {
+    type Value = Option<Where>;
+
+    fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+      f.write_str("a list of [column, clause] or a map of column to clause")
+    }
+
+    fn visit_none<E>(self) -> Result<Self::Value, E>
+    where
+      E: serde::de::Error,
+    {
+      Ok(None)
+    }
+
+    fn visit_unit<E>(self) -> Result<Self::Value, E>
+    where
+      E: serde::de::Error,
+    {
+      Ok(None)
+    }
+
+    fn visit_some<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+      D: serde::Deserializer<'de>,
+    {
+      deserializer.deserialize_any(WhereVisitor).map(Some)
+    }
+  }
+
+  struct WhereVisitor;
+
+  impl<'de> serde::de::Visitor<'de> for WhereVisitor {
+    type Value = Where;
+
+    fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+      f.write_str("a list of [column, clause] or a map of column to clause")
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+      A: serde::de::SeqAccess<'de>,
+    {
+      let mut r#where = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+      while let Some(item) = seq.next_element()? {
+        r#where.push(item);
+      }
+      Ok(r#where)
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+      A: serde::de::MapAccess<'de>,
+    {
+      let mut r#where = Vec::with_capacity(map.size_hint().unwrap_or(0));
+      while let Some(item) = map.next_entry()? {
+        r#where.push(item);
+      }
+      Ok(r#where)
+    }
   }
 }
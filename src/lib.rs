@@ -1,3 +1,11 @@
+#[cfg(feature = "async")]
+mod async_db;
+mod column;
 mod db;
+mod error;
 mod filter;
+mod query;
+#[cfg(feature = "web")]
+mod query_string;
+mod runtime;
 pub mod prelude;
@@ -8,7 +8,9 @@ diesel::table! {
   }
 }
 
-#[derive(Clone, Debug, Insertable, Queryable, Identifiable)]
+#[derive(
+  Clone, Debug, Insertable, Queryable, Identifiable, FilterableColumns,
+)]
 #[diesel(primary_key(id))]
 #[diesel(table_name = users)]
 #[diesel(check_for_backend(diesel::sqlite::Sqlite))]
@@ -18,34 +20,79 @@ pub struct DbUser {
 }
 
 impl DbModelCreate for DbUser {}
+impl DbModelReadByPk for DbUser {}
 impl DbModelDelByPk for DbUser {}
+impl DbModelRead for DbUser {
+  fn gen_read_query<D>(
+    filter: &GenericFilter,
+  ) -> Result<
+    BoxedSelect<
+      <Self as diesel::associations::HasTable>::Table,
+      <D as Connection>::Backend,
+    >,
+    diesel::result::Error,
+  >
+  where
+    D: diesel::r2d2::R2D2Connection
+      + Connection
+      + diesel::connection::LoadConnection
+      + 'static,
+    Self: diesel::associations::HasTable
+      + FilterableColumns<<D as Connection>::Backend>,
+    <Self as diesel::associations::HasTable>::Table:
+      diesel::query_dsl::methods::BoxedDsl<
+        'static,
+        <D as Connection>::Backend,
+        Output = BoxedSelect<
+          <Self as diesel::associations::HasTable>::Table,
+          <D as Connection>::Backend,
+        >,
+      >,
+    <D as Connection>::Backend:
+      diesel::internal::derives::multiconnection::DieselReserveSpecialization,
+  {
+    let query = <Self as diesel::associations::HasTable>::table().into_boxed();
+    apply_where::<Self, _, _>(query, filter)
+  }
+}
+
 impl DbModelDelBy for DbUser {
   fn gen_del_query<D>(
     filter: &GenericFilter,
-  ) -> diesel::query_builder::BoxedDeleteStatement<
-    'static,
-    <D as Connection>::Backend,
-    <Self as diesel::associations::HasTable>::Table,
+  ) -> Result<
+    diesel::query_builder::BoxedDeleteStatement<
+      'static,
+      <D as Connection>::Backend,
+      <Self as diesel::associations::HasTable>::Table,
+    >,
+    diesel::result::Error,
   >
   where
     D: diesel::r2d2::R2D2Connection
       + Connection
       + diesel::connection::LoadConnection
       + 'static,
-    Self: diesel::associations::HasTable,
+    Self: diesel::associations::HasTable
+      + FilterableColumns<<D as Connection>::Backend>,
+    <D as Connection>::Backend:
+      diesel::internal::derives::multiconnection::DieselReserveSpecialization,
   {
-    let mut query =
+    let query =
       diesel::delete(<Self as diesel::associations::HasTable>::table())
         .into_boxed();
-    let r#where = filter.r#where.clone().unwrap_or_default();
-
-    query
+    apply_where::<Self, _, _>(query, filter)
   }
 }
 
+const CREATE_USERS: &str =
+  "CREATE TABLE IF NOT EXISTS users (id TEXT PRIMARY KEY, name TEXT NOT NULL)";
+
 #[ntex::main]
 async fn main() -> std::io::Result<()> {
   let db = DbDriver::<SqliteConnection>::new("file:///tmp/test.db")?;
+  diesel::sql_query(CREATE_USERS)
+    .execute(&mut db.get_conn()?)
+    .unwrap();
   db.create(&DbUser {
     id: "1".to_string(),
     name: "test".to_string(),
@@ -54,9 +101,17 @@ async fn main() -> std::io::Result<()> {
   .unwrap();
   db.del_by_pk::<DbUser, _>("123").await.unwrap();
   let filter =
-    GenericFilter::new().r#where("id", GenericClause::Eq("1".to_owned()));
+    GenericFilter::new().r#where("id", GenericClause::Eq("1".into()));
   db.del_by::<DbUser>(&filter).await.unwrap();
-  let db = DbDriver::<PgConnection>::new("file:///tmp/test.db")?;
+  db.del_all::<DbUser>(Confirm::Yes).await.unwrap();
+  // Postgres is only run when `DATABASE_URL` points to a server
+  let Ok(url) = std::env::var("DATABASE_URL") else {
+    return Ok(());
+  };
+  let db = DbDriver::<PgConnection>::new(&url)?;
+  diesel::sql_query(CREATE_USERS)
+    .execute(&mut db.get_conn()?)
+    .unwrap();
   db.create(&DbUser {
     id: "1".to_string(),
     name: "test".to_string(),
@@ -65,7 +120,8 @@ async fn main() -> std::io::Result<()> {
   .unwrap();
   db.del_by_pk::<DbUser, _>("123").await.unwrap();
   let filter =
-    GenericFilter::new().r#where("id", GenericClause::Eq("1".to_owned()));
+    GenericFilter::new().r#where("id", GenericClause::Eq("1".into()));
   db.del_by::<DbUser>(&filter).await.unwrap();
+  db.del_all::<DbUser>(Confirm::Yes).await.unwrap();
   Ok(())
 }
@@ -1,4 +1,19 @@
+#[cfg(feature = "async")]
+pub use crate::async_db::*;
+pub use crate::column::*;
 pub use crate::db::*;
+pub use crate::error::*;
 pub use crate::filter::*;
+pub use crate::query::*;
+#[cfg(feature = "web")]
+pub use crate::query_string::*;
 pub use diesel::prelude::*;
 pub use diesel::{PgConnection, SqliteConnection};
+#[cfg(feature = "mysql")]
+pub use diesel::MysqlConnection;
+#[cfg(feature = "async")]
+pub use diesel_async::AsyncPgConnection;
+pub use diesel_rt_macro::{
+  DbModelDelBy, DbModelUpsert, FilterableColumns, IntoFilter, PrimaryKey,
+  TurboModel,
+};
@@ -0,0 +1,1061 @@
+use std::any::TypeId;
+use std::marker::PhantomData;
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use diesel::{
+  prelude::*,
+  backend::Backend,
+  dsl::AsSelect,
+  expression::{is_aggregate, TypedExpressionType, ValidGrouping},
+  sql_types::{is_nullable, SingleValue, SqlType},
+  internal::{
+    derives::multiconnection::{
+      BoxedSelectStatement, DieselReserveSpecialization,
+    },
+    table_macro::{FromClause, SelectStatement},
+  },
+  pg::Pg,
+  query_builder::{
+    AsQuery, AstPass, BoxedSqlQuery, Query, QueryBuilder, QueryFragment,
+    QueryId, SqlQuery,
+  },
+  query_dsl::methods::{FilterDsl, SelectDsl},
+  serialize::ToSql,
+  sql_types::{
+    BigInt, Bool, Double, Float, HasSqlType, Integer, Nullable, SmallInt, Text,
+  },
+};
+
+use crate::column::{
+  BoxedValue, ColumnVisitor, FilterColumn, FilterSqlType, FilterableColumns,
+};
+use crate::filter::{
+  FilterGroup, GenericClause, GenericFilter, GenericValue, OrderDirection,
+};
+
+/// A boxed boolean expression on the table `T`.
+/// It's what every `GenericClause` is turned into,
+/// so it can be given to `filter()` on a select, update or delete statement.
+pub type BoxedPredicate<T, DB> =
+  Box<dyn BoxableExpression<T, DB, SqlType = Bool>>;
+
+/// The boxed select statement of the table `T`.
+pub type BoxedSelect<T, DB> =
+  BoxedSelectStatement<'static, <T as AsQuery>::SqlType, FromClause<T>, DB>;
+
+/// The boxed select statement of the table `T` loading the SQL type `ST`,
+/// see `select_columns`.
+pub type BoxedSelectAs<T, ST, DB> =
+  BoxedSelectStatement<'static, ST, FromClause<T>, DB>;
+
+/// The boxed select statement of the table `T` loading the struct `R`,
+/// see `select_into`.
+pub type BoxedSelectInto<T, R, DB> = BoxedSelectAs<T, AsSelect<R, DB>, DB>;
+
+/// The SQL type of a page of `paginate_keyset`,
+/// the row of the table `T` followed by its keys as a JSON array of texts.
+pub type KeysetSqlType<T> = (<T as AsQuery>::SqlType, Text);
+
+/// The boxed select of a page of `paginate_keyset` on the table `T`.
+pub type KeysetSelect<T, DB> = BoxedSelectAs<T, KeysetSqlType<T>, DB>;
+
+/// The boxed select of an aggregate of the table `T` read as `R`.
+pub type AggregateSelect<T, R, DB> =
+  BoxedSelectAs<T, Nullable<<R as AggregateValue>::SqlType>, DB>;
+
+/// The query of `DbModelCount::group_count` on the table `T`.
+pub type GroupCountQuery<T, DB> =
+  GroupCount<BoxedSelectAs<T, Nullable<Text>, DB>>;
+
+/// The select statement of the table `T` before boxing.
+pub type TableSelect<T> = SelectStatement<FromClause<T>>;
+
+/// A raw SQL query with its bind parameters.
+pub type RawSqlQuery<DB> = BoxedSqlQuery<'static, DB, SqlQuery>;
+
+/// Build a raw SQL query binding `binds` in order,
+/// the placeholders being `?` on SQLite and MySQL and `$1` on Postgres.
+/// Integers are bound as `BigInt`, floats as `Double`,
+/// JSON values as their text and `Null` as a null `Text`.
+pub fn raw_sql_query<DB>(sql: &str, binds: Vec<GenericValue>) -> RawSqlQuery<DB>
+where
+  DB: Backend
+    + HasSqlType<BigInt>
+    + HasSqlType<Double>
+    + HasSqlType<Bool>
+    + HasSqlType<Text>,
+  i64: ToSql<BigInt, DB>,
+  f64: ToSql<Double, DB>,
+  bool: ToSql<Bool, DB>,
+  String: ToSql<Text, DB>,
+  Option<String>: ToSql<Nullable<Text>, DB>,
+{
+  let mut query = diesel::sql_query(sql).into_boxed();
+  for value in binds {
+    query = match value {
+      GenericValue::Int(value) => query.bind::<BigInt, _>(value),
+      GenericValue::Float(value) => query.bind::<Double, _>(value),
+      GenericValue::Bool(value) => query.bind::<Bool, _>(value),
+      GenericValue::Text(value) => query.bind::<Text, _>(value),
+      GenericValue::Null => query.bind::<Nullable<Text>, _>(None::<String>),
+      GenericValue::Json(value) => query.bind::<Text, _>(value.to_string()),
+    };
+  }
+  query
+}
+
+/// Apply every clause of `filter.r#where` on a boxed statement of the model `I`.
+/// Clauses then groups are applied in the order of the filter,
+/// so the same filter always renders the same SQL.
+pub fn apply_where<I, DB, Q>(
+  mut query: Q,
+  filter: &GenericFilter,
+) -> Result<Q, diesel::result::Error>
+where
+  DB: Backend + DieselReserveSpecialization + 'static,
+  I: FilterableColumns<DB>,
+  I::Table: 'static,
+  Q: FilterDsl<BoxedPredicate<I::Table, DB>, Output = Q>,
+{
+  for (key, clause) in filter.r#where.iter().flatten() {
+    let predicate = I::predicate(key, clause)?;
+    query = FilterDsl::filter(query, predicate);
+  }
+  for group in filter.groups.iter().flatten() {
+    query = FilterDsl::filter(query, group_predicate::<I, DB>(group)?);
+  }
+  Ok(query)
+}
+
+/// Build a single predicate of every clause and group of the filter,
+/// always true when the filter has none.
+/// It's for the statements that can't be boxed, like a locking select.
+pub fn filter_predicate<I, DB>(
+  filter: &GenericFilter,
+) -> Result<BoxedPredicate<I::Table, DB>, diesel::result::Error>
+where
+  DB: Backend + DieselReserveSpecialization + 'static,
+  I: FilterableColumns<DB>,
+  I::Table: 'static,
+{
+  let mut groups = Vec::new();
+  for (column, clause) in filter.r#where.iter().flatten() {
+    groups.push(FilterGroup::leaf(column, clause.clone()));
+  }
+  groups.extend(filter.groups.iter().flatten().cloned());
+  group_predicate::<I, DB>(&FilterGroup::And(groups))
+}
+
+/// Build the predicate of a group of clauses on the model `I`.
+/// Nested groups are wrapped in parentheses by diesel.
+pub fn group_predicate<I, DB>(
+  group: &FilterGroup,
+) -> Result<BoxedPredicate<I::Table, DB>, diesel::result::Error>
+where
+  DB: Backend + DieselReserveSpecialization + 'static,
+  I: FilterableColumns<DB>,
+  I::Table: 'static,
+{
+  let (groups, or) = match group {
+    FilterGroup::Leaf(column, clause) => return I::predicate(column, clause),
+    FilterGroup::Not(group) => {
+      let predicate = group_predicate::<I, DB>(group)?;
+      return Ok(Box::new(diesel::dsl::not(predicate)));
+    }
+    FilterGroup::And(groups) => (groups, false),
+    FilterGroup::Or(groups) => (groups, true),
+  };
+  let mut groups = groups.iter();
+  let Some(first) = groups.next() else {
+    return Ok(constant_predicate(!or));
+  };
+  let mut predicate = group_predicate::<I, DB>(first)?;
+  for group in groups {
+    let next = group_predicate::<I, DB>(group)?;
+    predicate = if or {
+      Box::new(predicate.or(next))
+    } else {
+      Box::new(predicate.and(next))
+    };
+  }
+  Ok(predicate)
+}
+
+/// A predicate always true or always false, rendered as `1 = 1` or `1 = 0`.
+pub(crate) fn constant_predicate<T, DB>(value: bool) -> BoxedPredicate<T, DB>
+where
+  DB: Backend + DieselReserveSpecialization,
+{
+  Box::new(Constant(value))
+}
+
+/// The fragment of `constant_predicate`.
+/// Unlike `diesel::dsl::sql` it keeps the statement in the prepared
+/// statement cache of the connection, its SQL being one of two.
+struct Constant(bool);
+
+impl Expression for Constant {
+  type SqlType = Bool;
+}
+
+impl<QS> AppearsOnTable<QS> for Constant {}
+
+impl<QS> SelectableExpression<QS> for Constant {}
+
+impl<GB> ValidGrouping<GB> for Constant {
+  type IsAggregate = is_aggregate::Never;
+}
+
+impl<DB> QueryFragment<DB> for Constant
+where
+  DB: Backend,
+{
+  fn walk_ast<'b>(&'b self, mut out: AstPass<'_, 'b, DB>) -> QueryResult<()> {
+    out.push_sql(if self.0 { "1 = 1" } else { "1 = 0" });
+    Ok(())
+  }
+}
+
+/// Apply every entry of `filter.order` on a boxed select of the model `I`,
+/// the first entry being the main ordering.
+/// The columns of `filter.distinct_on` come first as Postgres requires,
+/// ascending unless `filter.order` gives their direction.
+pub fn apply_order<I, DB>(
+  mut query: BoxedSelect<I::Table, DB>,
+  filter: &GenericFilter,
+) -> Result<BoxedSelect<I::Table, DB>, diesel::result::Error>
+where
+  DB: Backend + 'static,
+  I: FilterableColumns<DB>,
+  I::Table: 'static,
+{
+  let order = filter.order.as_deref().unwrap_or_default();
+  let distinct_on = filter.distinct_on.as_deref().unwrap_or_default();
+  for column in distinct_on {
+    let direction = order
+      .iter()
+      .find(|(name, _)| name == column)
+      .map_or(OrderDirection::Asc, |(_, direction)| *direction);
+    query = I::visit_column(column, OrderVisitor(query, direction))?;
+  }
+  for (column, direction) in order {
+    if !distinct_on.contains(column) {
+      query = I::visit_column(column, OrderVisitor(query, *direction))?;
+    }
+  }
+  Ok(query)
+}
+
+/// Add the ordering on a column to a boxed select.
+struct OrderVisitor<Q>(Q, OrderDirection);
+
+impl<T, DB> ColumnVisitor<T, DB> for OrderVisitor<BoxedSelect<T, DB>>
+where
+  T: Table + 'static,
+  DB: Backend + 'static,
+{
+  type Output = BoxedSelect<T, DB>;
+
+  fn visit<C>(self, column: C) -> Result<Self::Output, diesel::result::Error>
+  where
+    C: FilterColumn<T, DB>,
+  {
+    let OrderVisitor(query, direction) = self;
+    let query = match direction {
+      OrderDirection::Asc => query.then_order_by(column.asc()),
+      OrderDirection::Desc => query.then_order_by(column.desc()),
+    };
+    Ok(query)
+  }
+}
+
+/// Select the rows of `filter.distinct_on` with `DISTINCT ON`,
+/// keeping the first row of each distinct value in the order of the query.
+/// `selection` is the select clause of the model, every column of the table.
+/// Without `distinct_on` the query is returned as is,
+/// other backends than Postgres are a `QueryBuilderError`.
+pub fn apply_distinct_on<I, DB, S>(
+  query: BoxedSelect<I::Table, DB>,
+  selection: S,
+  filter: &GenericFilter,
+) -> Result<BoxedSelect<I::Table, DB>, diesel::result::Error>
+where
+  DB: Backend + 'static,
+  I: FilterableColumns<DB>,
+  I::Table: 'static,
+  S: QueryFragment<DB> + Send + 'static,
+  <I::Table as AsQuery>::SqlType: TypedExpressionType,
+  BoxedSelect<I::Table, DB>: SelectDsl<
+    ColumnsSelect<<I::Table as AsQuery>::SqlType, DB>,
+    Output = BoxedSelect<I::Table, DB>,
+  >,
+{
+  let distinct_on = distinct_on_columns::<I, DB>(filter)?;
+  if distinct_on.is_empty() {
+    return Ok(query);
+  }
+  let selection = ColumnsSelect {
+    distinct_on,
+    columns: vec![Box::new(selection)],
+    sql_type: PhantomData,
+  };
+  Ok(SelectDsl::select(query, selection))
+}
+
+/// Select only the columns named `columns` of the model `I`, in order,
+/// the rows being loaded as `ST`, the SQL type of the tuple of columns.
+/// `filter.distinct_on` is kept.
+pub fn select_columns<I, DB, ST>(
+  query: BoxedSelect<I::Table, DB>,
+  columns: &[&str],
+  filter: &GenericFilter,
+) -> Result<BoxedSelectAs<I::Table, ST, DB>, diesel::result::Error>
+where
+  DB: Backend + 'static,
+  I: FilterableColumns<DB>,
+  I::Table: 'static,
+  ST: TypedExpressionType + 'static,
+{
+  if columns.is_empty() {
+    return Err(diesel::result::Error::QueryBuilderError(
+      "select_columns needs at least one column".into(),
+    ));
+  }
+  let distinct_on = distinct_on_columns::<I, DB>(filter)?;
+  let columns = columns
+    .iter()
+    .map(|column| I::visit_column(column, BoxColumnVisitor))
+    .collect::<Result<_, _>>()?;
+  let selection = ColumnsSelect {
+    distinct_on,
+    columns,
+    sql_type: PhantomData,
+  };
+  Ok(SelectDsl::select(query, selection))
+}
+
+/// Select the columns of `R`, a struct deriving `Selectable`
+/// on the table of the model `I`, the rows being loaded as `R`.
+/// `filter.distinct_on` is kept.
+pub fn select_into<I, DB, R>(
+  query: BoxedSelect<I::Table, DB>,
+  filter: &GenericFilter,
+) -> Result<BoxedSelectInto<I::Table, R, DB>, diesel::result::Error>
+where
+  DB: Backend + 'static,
+  I: FilterableColumns<DB>,
+  I::Table: 'static,
+  R: Selectable<DB> + 'static,
+  R::SelectExpression: QueryFragment<DB> + Send + 'static,
+  AsSelect<R, DB>: TypedExpressionType,
+{
+  let selection = ColumnsSelect {
+    distinct_on: distinct_on_columns::<I, DB>(filter)?,
+    columns: vec![Box::new(R::construct_selection())],
+    sql_type: PhantomData,
+  };
+  Ok(SelectDsl::select(query, selection))
+}
+
+/// Resolve the columns of `filter.distinct_on`,
+/// a `QueryBuilderError` on other backends than Postgres.
+fn distinct_on_columns<I, DB>(
+  filter: &GenericFilter,
+) -> Result<Vec<Box<dyn QueryFragment<DB> + Send>>, diesel::result::Error>
+where
+  DB: Backend + 'static,
+  I: FilterableColumns<DB>,
+{
+  let distinct_on = filter.distinct_on.as_deref().unwrap_or_default();
+  if !distinct_on.is_empty() && TypeId::of::<DB>() != TypeId::of::<Pg>() {
+    return Err(diesel::result::Error::QueryBuilderError(
+      "distinct_on is only supported on Postgres".into(),
+    ));
+  }
+  distinct_on
+    .iter()
+    .map(|column| I::visit_column(column, BoxColumnVisitor))
+    .collect()
+}
+
+/// A select clause built at runtime, the columns separated by commas
+/// and prefixed by `DISTINCT ON (distinct_on)` when there are some.
+/// It's typed by the caller, `ST` must match the columns.
+pub struct ColumnsSelect<ST, DB> {
+  distinct_on: Vec<Box<dyn QueryFragment<DB> + Send>>,
+  columns: Vec<Box<dyn QueryFragment<DB> + Send>>,
+  sql_type: PhantomData<fn() -> ST>,
+}
+
+impl<ST, DB> Expression for ColumnsSelect<ST, DB>
+where
+  ST: TypedExpressionType,
+{
+  type SqlType = ST;
+}
+
+impl<ST, DB, QS> AppearsOnTable<QS> for ColumnsSelect<ST, DB> where
+  Self: Expression
+{
+}
+
+impl<ST, DB, QS> SelectableExpression<QS> for ColumnsSelect<ST, DB> where
+  Self: AppearsOnTable<QS>
+{
+}
+
+impl<ST, DB, GB> ValidGrouping<GB> for ColumnsSelect<ST, DB> {
+  type IsAggregate = is_aggregate::No;
+}
+
+impl<ST, DB> QueryFragment<DB> for ColumnsSelect<ST, DB>
+where
+  DB: Backend,
+{
+  fn walk_ast<'b>(&'b self, mut out: AstPass<'_, 'b, DB>) -> QueryResult<()> {
+    if !self.distinct_on.is_empty() {
+      out.push_sql("DISTINCT ON (");
+      walk_list(&self.distinct_on, out.reborrow())?;
+      out.push_sql(") ");
+    }
+    walk_list(&self.columns, out)
+  }
+}
+
+fn walk_list<'b, DB>(
+  fragments: &'b [Box<dyn QueryFragment<DB> + Send>],
+  mut out: AstPass<'_, 'b, DB>,
+) -> QueryResult<()>
+where
+  DB: Backend,
+{
+  for (i, fragment) in fragments.iter().enumerate() {
+    if i > 0 {
+      out.push_sql(", ");
+    }
+    fragment.walk_ast(out.reborrow())?;
+  }
+  Ok(())
+}
+
+/// Box a column to render it in a `ColumnsSelect`.
+struct BoxColumnVisitor;
+
+impl<T, DB> ColumnVisitor<T, DB> for BoxColumnVisitor
+where
+  DB: Backend,
+{
+  type Output = Box<dyn QueryFragment<DB> + Send>;
+
+  fn visit<C>(self, column: C) -> Result<Self::Output, diesel::result::Error>
+  where
+    C: FilterColumn<T, DB>,
+  {
+    Ok(Box::new(column))
+  }
+}
+
+/// Position after the last row of a page of `paginate_keyset`,
+/// the values of the order columns and of the primary key as text.
+/// `encode` makes it opaque to the clients and `decode` reads it back.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Cursor {
+  pub(crate) values: Vec<Option<String>>,
+}
+
+impl Cursor {
+  /// Encode the cursor as URL safe base64, to give to the client.
+  pub fn encode(&self) -> String {
+    let json = serde_json::to_string(&self.values)
+      .expect("a list of strings always serializes");
+    URL_SAFE_NO_PAD.encode(json)
+  }
+
+  /// Decode a cursor given by `encode`, `None` when it isn't one.
+  pub fn decode(encoded: &str) -> Option<Self> {
+    let json = URL_SAFE_NO_PAD.decode(encoded).ok()?;
+    let values = serde_json::from_slice(&json).ok()?;
+    Some(Self { values })
+  }
+
+  /// Read the keys selected by `keyset_query`.
+  pub(crate) fn from_keys(keys: &str) -> Result<Self, diesel::result::Error> {
+    let values = serde_json::from_str(keys)
+      .map_err(|err| diesel::result::Error::DeserializationError(err.into()))?;
+    Ok(Self { values })
+  }
+}
+
+/// Turn a select of the model `I` into a page of `paginate_keyset`,
+/// ordered by `keys` in order and starting after `after`.
+/// The last key must tell the rows apart, the primary key say.
+/// Each key has its own direction, the rows after the cursor are
+/// compared key by key as `ORDER BY` does.
+/// The keys are selected as a JSON array of texts after `selection`,
+/// the columns of the row.
+/// A key whose type can't be read back from the cursor,
+/// see `FilterSqlType::KEYSET_KEY`, is a `QueryBuilderError`.
+/// A nullable key is a `QueryBuilderError` too: no row compares
+/// after a null, a page ending on one would end the pagination.
+pub fn keyset_query<I, DB, S>(
+  mut query: BoxedSelect<I::Table, DB>,
+  selection: S,
+  keys: &[(&str, OrderDirection)],
+  after: Option<&Cursor>,
+) -> Result<KeysetSelect<I::Table, DB>, diesel::result::Error>
+where
+  DB: Backend + DieselReserveSpecialization + 'static,
+  I: FilterableColumns<DB>,
+  I::Table: 'static,
+  S: QueryFragment<DB> + Send + 'static,
+  KeysetSqlType<I::Table>: TypedExpressionType,
+{
+  for (key, _) in keys {
+    if !I::visit_column(key, KeysetKeyVisitor)? {
+      return Err(diesel::result::Error::QueryBuilderError(
+        format!("the column {key} can't order a keyset page").into(),
+      ));
+    }
+    if I::visit_column(key, NullableVisitor)? {
+      return Err(diesel::result::Error::QueryBuilderError(
+        format!("the nullable column {key} can't order a keyset page").into(),
+      ));
+    }
+  }
+  if let Some(cursor) = after {
+    if cursor.values.len() != keys.len() {
+      return Err(diesel::result::Error::QueryBuilderError(
+        "malformed cursor".into(),
+      ));
+    }
+    let value = |value: &Option<String>| {
+      value.as_deref().map_or(GenericValue::Null, Into::into)
+    };
+    // `(a, b) > (x, y)` spelled out for every backend and mixed directions:
+    // `a > x OR (a = x AND b > y)`
+    let mut options = Vec::with_capacity(keys.len());
+    for (i, ((column, direction), after)) in
+      keys.iter().zip(&cursor.values).enumerate()
+    {
+      let mut option = keys[..i]
+        .iter()
+        .zip(&cursor.values)
+        .map(|((column, _), before)| {
+          FilterGroup::leaf(column, GenericClause::Eq(value(before)))
+        })
+        .collect::<Vec<_>>();
+      option.push(FilterGroup::leaf(
+        column,
+        match direction {
+          OrderDirection::Asc => GenericClause::Gt(value(after)),
+          OrderDirection::Desc => GenericClause::Lt(value(after)),
+        },
+      ));
+      options.push(FilterGroup::And(option));
+    }
+    let group = FilterGroup::Or(options);
+    query = FilterDsl::filter(query, group_predicate::<I, DB>(&group)?);
+  }
+  let mut columns = Vec::with_capacity(keys.len());
+  for (column, direction) in keys {
+    query = I::visit_column(column, OrderVisitor(query, *direction))?;
+    columns.push(I::visit_column(column, TextColumnVisitor)?);
+  }
+  let selection = ColumnsSelect {
+    distinct_on: Vec::new(),
+    columns: vec![
+      Box::new(selection),
+      Box::new(TextCast(Box::new(JsonArray(columns)))),
+    ],
+    sql_type: PhantomData,
+  };
+  Ok(SelectDsl::select(query, selection))
+}
+
+/// A JSON array of the fragments,
+/// each backend names the function its own way.
+struct JsonArray<DB>(Vec<Box<dyn QueryFragment<DB> + Send>>);
+
+impl<DB> QueryFragment<DB> for JsonArray<DB>
+where
+  DB: Backend + 'static,
+{
+  fn walk_ast<'b>(&'b self, mut out: AstPass<'_, 'b, DB>) -> QueryResult<()> {
+    if TypeId::of::<DB>() == TypeId::of::<Pg>() {
+      out.push_sql("json_build_array(");
+    } else if is_mysql::<DB>() {
+      out.push_sql("JSON_ARRAY(");
+    } else {
+      out.push_sql("json_array(");
+    }
+    walk_list(&self.0, out.reborrow())?;
+    out.push_sql(")");
+    Ok(())
+  }
+}
+
+/// A column cast to text, to read the keys of any type the same way.
+struct TextCast<DB>(Box<dyn QueryFragment<DB> + Send>);
+
+impl<DB> QueryFragment<DB> for TextCast<DB>
+where
+  DB: Backend + 'static,
+{
+  fn walk_ast<'b>(&'b self, mut out: AstPass<'_, 'b, DB>) -> QueryResult<()> {
+    out.push_sql("CAST(");
+    self.0.walk_ast(out.reborrow())?;
+    out.push_sql(" AS ");
+    out.push_sql(String::cast_type::<DB>());
+    out.push_sql(")");
+    Ok(())
+  }
+}
+
+/// Whether the type of the column can be a key of a keyset page.
+struct KeysetKeyVisitor;
+
+impl<T, DB> ColumnVisitor<T, DB> for KeysetKeyVisitor
+where
+  DB: Backend,
+{
+  type Output = bool;
+
+  fn visit<C>(self, _column: C) -> Result<Self::Output, diesel::result::Error>
+  where
+    C: FilterColumn<T, DB>,
+  {
+    Ok(<C::SqlType as FilterSqlType<DB>>::KEYSET_KEY)
+  }
+}
+
+/// MySQL names the types of a cast its own way.
+#[cfg_attr(not(feature = "mysql"), allow(clippy::extra_unused_type_parameters))]
+fn is_mysql<DB: 'static>() -> bool {
+  #[cfg(feature = "mysql")]
+  return TypeId::of::<DB>() == TypeId::of::<diesel::mysql::Mysql>();
+  #[cfg(not(feature = "mysql"))]
+  false
+}
+
+/// Tell whether a column is nullable.
+struct NullableVisitor;
+
+impl<T, DB> ColumnVisitor<T, DB> for NullableVisitor
+where
+  DB: Backend,
+{
+  type Output = bool;
+
+  fn visit<C>(self, _column: C) -> Result<Self::Output, diesel::result::Error>
+  where
+    C: FilterColumn<T, DB>,
+  {
+    let nullability = TypeId::of::<<C::SqlType as SqlType>::IsNull>();
+    Ok(nullability == TypeId::of::<is_nullable::IsNullable>())
+  }
+}
+
+/// Box a column cast to text to render it in a `ColumnsSelect`.
+struct TextColumnVisitor;
+
+impl<T, DB> ColumnVisitor<T, DB> for TextColumnVisitor
+where
+  DB: Backend + 'static,
+{
+  type Output = Box<dyn QueryFragment<DB> + Send>;
+
+  fn visit<C>(self, column: C) -> Result<Self::Output, diesel::result::Error>
+  where
+    C: FilterColumn<T, DB>,
+  {
+    Ok(Box::new(TextCast(Box::new(column))))
+  }
+}
+
+/// Aggregate function of `DbModelCount::aggregate`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AggregateFn {
+  /// Sum of the values
+  Sum,
+  /// Average of the values
+  Avg,
+  /// Smallest value
+  Min,
+  /// Largest value
+  Max,
+  /// Number of values, nulls left out
+  Count,
+  /// Number of distinct values, nulls left out
+  CountDistinct,
+}
+
+impl AggregateFn {
+  /// The SQL function
+  pub fn as_sql(&self) -> &'static str {
+    match self {
+      Self::Sum => "SUM",
+      Self::Avg => "AVG",
+      Self::Min => "MIN",
+      Self::Max => "MAX",
+      Self::Count | Self::CountDistinct => "COUNT",
+    }
+  }
+}
+
+/// A value an aggregate is read as, see `DbModelCount::aggregate`.
+/// The aggregate is cast to `SqlType`, so a `SUM` read as `i64`
+/// is one whatever the type the backend gives it.
+pub trait AggregateValue: Sized {
+  type SqlType: SqlType<IsNull = is_nullable::NotNull> + SingleValue;
+
+  /// The name of `SqlType` in a cast on the backend `DB`.
+  fn cast_type<DB: 'static>() -> &'static str;
+}
+
+impl AggregateValue for i64 {
+  type SqlType = BigInt;
+
+  fn cast_type<DB: 'static>() -> &'static str {
+    if is_mysql::<DB>() {
+      "SIGNED"
+    } else {
+      "BIGINT"
+    }
+  }
+}
+
+impl AggregateValue for f64 {
+  type SqlType = Double;
+
+  fn cast_type<DB: 'static>() -> &'static str {
+    if is_mysql::<DB>() {
+      "DOUBLE"
+    } else {
+      "DOUBLE PRECISION"
+    }
+  }
+}
+
+impl AggregateValue for String {
+  type SqlType = Text;
+
+  fn cast_type<DB: 'static>() -> &'static str {
+    if is_mysql::<DB>() {
+      "CHAR"
+    } else {
+      "TEXT"
+    }
+  }
+}
+
+/// Select `function` applied on the column named `column` of the model `I`,
+/// cast to the SQL type of `R`. Null when no row matches.
+pub fn select_aggregate<I, DB, R>(
+  query: BoxedSelect<I::Table, DB>,
+  function: AggregateFn,
+  column: &str,
+) -> Result<AggregateSelect<I::Table, R, DB>, diesel::result::Error>
+where
+  DB: Backend + 'static,
+  I: FilterableColumns<DB>,
+  I::Table: 'static,
+  R: AggregateValue,
+  Nullable<R::SqlType>: TypedExpressionType,
+{
+  let column = I::visit_column(column, BoxColumnVisitor)?;
+  let selection = ColumnsSelect {
+    distinct_on: Vec::new(),
+    columns: vec![Box::new(Aggregate {
+      function,
+      column,
+      sql_type: R::cast_type::<DB>(),
+    })],
+    sql_type: PhantomData,
+  };
+  Ok(SelectDsl::select(query, selection))
+}
+
+/// `CAST(function(column) AS sql_type)`
+struct Aggregate<DB> {
+  function: AggregateFn,
+  column: Box<dyn QueryFragment<DB> + Send>,
+  sql_type: &'static str,
+}
+
+impl<DB> QueryFragment<DB> for Aggregate<DB>
+where
+  DB: Backend,
+{
+  fn walk_ast<'b>(&'b self, mut out: AstPass<'_, 'b, DB>) -> QueryResult<()> {
+    out.push_sql("CAST(");
+    out.push_sql(self.function.as_sql());
+    out.push_sql("(");
+    if self.function == AggregateFn::CountDistinct {
+      out.push_sql("DISTINCT ");
+    }
+    self.column.walk_ast(out.reborrow())?;
+    out.push_sql(") AS ");
+    out.push_sql(self.sql_type);
+    out.push_sql(")");
+    Ok(())
+  }
+}
+
+/// The name of the grouped column in the subquery of `GroupCount`.
+const GROUP_VALUE: &str = "group_value";
+
+/// Count the rows of `query` per value of the column named `column`
+/// of the model `I`, the value read as text.
+/// A boxed select can't be grouped, it becomes the subquery of
+/// `SELECT group_value, COUNT(*) FROM (query) GROUP BY group_value`.
+pub fn group_count_query<I, DB>(
+  query: BoxedSelect<I::Table, DB>,
+  column: &str,
+) -> Result<GroupCountQuery<I::Table, DB>, diesel::result::Error>
+where
+  DB: Backend + 'static,
+  I: FilterableColumns<DB>,
+  I::Table: 'static,
+{
+  let column = I::visit_column(column, TextColumnVisitor)?;
+  let selection = ColumnsSelect {
+    distinct_on: Vec::new(),
+    columns: vec![Box::new(Aliased(column, GROUP_VALUE))],
+    sql_type: PhantomData,
+  };
+  Ok(GroupCount {
+    query: SelectDsl::select(query, selection),
+  })
+}
+
+/// `fragment AS alias`
+struct Aliased<DB>(Box<dyn QueryFragment<DB> + Send>, &'static str);
+
+impl<DB> QueryFragment<DB> for Aliased<DB>
+where
+  DB: Backend,
+{
+  fn walk_ast<'b>(&'b self, mut out: AstPass<'_, 'b, DB>) -> QueryResult<()> {
+    self.0.walk_ast(out.reborrow())?;
+    out.push_sql(" AS ");
+    out.push_identifier(self.1)
+  }
+}
+
+/// The rows of a query selecting `group_value` counted per value,
+/// the largest groups first, see `group_count_query`.
+pub struct GroupCount<Q> {
+  query: Q,
+}
+
+impl<Q> QueryId for GroupCount<Q> {
+  type QueryId = ();
+
+  const HAS_STATIC_QUERY_ID: bool = false;
+}
+
+impl<Q> Query for GroupCount<Q> {
+  type SqlType = (Nullable<Text>, BigInt);
+}
+
+impl<Q, DB> QueryFragment<DB> for GroupCount<Q>
+where
+  DB: Backend,
+  Q: QueryFragment<DB>,
+{
+  fn walk_ast<'b>(&'b self, mut out: AstPass<'_, 'b, DB>) -> QueryResult<()> {
+    out.push_sql("SELECT ");
+    out.push_identifier(GROUP_VALUE)?;
+    out.push_sql(", COUNT(*) FROM (");
+    self.query.walk_ast(out.reborrow())?;
+    // MySQL and older Postgres need an alias for the subquery
+    out.push_sql(") AS ");
+    out.push_identifier("grouped")?;
+    out.push_sql(" GROUP BY ");
+    out.push_identifier(GROUP_VALUE)?;
+    out.push_sql(" ORDER BY COUNT(*) DESC, ");
+    out.push_identifier(GROUP_VALUE)?;
+    Ok(())
+  }
+}
+
+impl<Q, Conn> RunQueryDsl<Conn> for GroupCount<Q> {}
+
+/// The changeset of `DbModelUpdate::increment` on the table `T`,
+/// `SET column = column + delta`.
+pub struct Increment<T, DB> {
+  name: String,
+  column: Box<dyn QueryFragment<DB> + Send>,
+  delta: i64,
+  table: PhantomData<fn() -> T>,
+}
+
+/// Add `delta` to the numeric column named `column` of the model `I`.
+/// The name is written as is after `SET`, so it must be the name
+/// of the column in the table, as `#[derive(FilterableColumns)]` maps them.
+pub fn increment_column<I, DB>(
+  column: &str,
+  delta: i64,
+) -> Result<Increment<I::Table, DB>, diesel::result::Error>
+where
+  DB: Backend + 'static,
+  I: FilterableColumns<DB>,
+{
+  Ok(Increment {
+    name: column.to_owned(),
+    column: I::visit_column(column, NumericColumnVisitor(column))?,
+    delta,
+    table: PhantomData,
+  })
+}
+
+impl<T, DB> AsChangeset for Increment<T, DB>
+where
+  T: Table,
+{
+  type Target = T;
+  type Changeset = Self;
+
+  fn as_changeset(self) -> Self::Changeset {
+    self
+  }
+}
+
+impl<T, DB> QueryFragment<DB> for Increment<T, DB>
+where
+  DB: Backend + HasSqlType<BigInt>,
+  i64: ToSql<BigInt, DB>,
+{
+  fn walk_ast<'b>(&'b self, mut out: AstPass<'_, 'b, DB>) -> QueryResult<()> {
+    out.push_identifier(&self.name)?;
+    out.push_sql(" = ");
+    self.column.walk_ast(out.reborrow())?;
+    out.push_sql(" + ");
+    out.push_bind_param::<BigInt, _>(&self.delta)?;
+    Ok(())
+  }
+}
+
+/// Box a column whose SQL type is an integer or a floating point number.
+struct NumericColumnVisitor<'a>(&'a str);
+
+impl<T, DB> ColumnVisitor<T, DB> for NumericColumnVisitor<'_>
+where
+  DB: Backend + 'static,
+{
+  type Output = Box<dyn QueryFragment<DB> + Send>;
+
+  fn visit<C>(self, column: C) -> Result<Self::Output, diesel::result::Error>
+  where
+    C: FilterColumn<T, DB>,
+  {
+    let numeric = [
+      TypeId::of::<SmallInt>(),
+      TypeId::of::<Integer>(),
+      TypeId::of::<BigInt>(),
+      TypeId::of::<Float>(),
+      TypeId::of::<Double>(),
+      TypeId::of::<Nullable<SmallInt>>(),
+      TypeId::of::<Nullable<Integer>>(),
+      TypeId::of::<Nullable<BigInt>>(),
+      TypeId::of::<Nullable<Float>>(),
+      TypeId::of::<Nullable<Double>>(),
+    ];
+    if !numeric.contains(&TypeId::of::<C::SqlType>()) {
+      return Err(diesel::result::Error::QueryBuilderError(
+        format!("column {} is not numeric", self.0).into(),
+      ));
+    }
+    Ok(Box::new(column))
+  }
+}
+
+/// The changeset of `DbModelUpdate::set_field` on the table `T`,
+/// `SET column = value`.
+pub struct SetField<T, DB> {
+  name: String,
+  value: BoxedValue<DB>,
+  table: PhantomData<fn() -> T>,
+}
+
+/// Set the column named `column` of the model `I` to `value`,
+/// converted to the type of the column like the operand of a clause.
+/// The name is written as is after `SET`, like `increment_column`.
+pub fn set_column<I, DB>(
+  column: &str,
+  value: &GenericValue,
+) -> Result<SetField<I::Table, DB>, diesel::result::Error>
+where
+  DB: Backend + 'static,
+  I: FilterableColumns<DB>,
+{
+  Ok(SetField {
+    name: column.to_owned(),
+    value: I::visit_column(column, BindVisitor(value))?,
+    table: PhantomData,
+  })
+}
+
+impl<T, DB> AsChangeset for SetField<T, DB>
+where
+  T: Table,
+{
+  type Target = T;
+  type Changeset = Self;
+
+  fn as_changeset(self) -> Self::Changeset {
+    self
+  }
+}
+
+impl<T, DB> QueryFragment<DB> for SetField<T, DB>
+where
+  DB: Backend,
+{
+  fn walk_ast<'b>(&'b self, mut out: AstPass<'_, 'b, DB>) -> QueryResult<()> {
+    out.push_identifier(&self.name)?;
+    out.push_sql(" = ");
+    self.value.walk_ast(out.reborrow())?;
+    Ok(())
+  }
+}
+
+/// Bind a value converted to the type of the column.
+struct BindVisitor<'a>(&'a GenericValue);
+
+impl<T, DB> ColumnVisitor<T, DB> for BindVisitor<'_>
+where
+  DB: Backend,
+{
+  type Output = BoxedValue<DB>;
+
+  fn visit<C>(self, _column: C) -> Result<Self::Output, diesel::result::Error>
+  where
+    C: FilterColumn<T, DB>,
+  {
+    <C::SqlType as FilterSqlType<DB>>::bind(self.0)
+  }
+}
+
+/// A bind parameter of a statement built by `build_sql`,
+/// an operand of a clause of the filter.
+pub type SqlValue = GenericValue;
+
+/// Render a statement built from `filter` into its SQL without running it,
+/// with the operands of the filter, see `GenericFilter::operands`.
+/// Those are the values of the clauses as given, not the binds diesel
+/// renders: the pattern of `StartsWith` is bound with its `%`,
+/// the limit, the offset and the changeset are bound too.
+pub fn build_sql<DB, Q>(
+  query: &Q,
+  filter: &GenericFilter,
+) -> Result<(String, Vec<SqlValue>), diesel::result::Error>
+where
+  DB: Backend + Default,
+  DB::QueryBuilder: Default,
+  Q: QueryFragment<DB>,
+{
+  let mut builder = DB::QueryBuilder::default();
+  query.to_sql(&mut builder, &DB::default())?;
+  Ok((builder.finish(), filter.operands()))
+}
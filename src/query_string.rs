@@ -0,0 +1,399 @@
+//! Build a `GenericFilter` from the query string of a request,
+//! enabled with the `web` feature.
+
+use crate::filter::{GenericClause, GenericFilter, GenericValue, OrderDirection};
+
+/// A parameter of the query string couldn't be read into a `GenericFilter`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueryStringError {
+  /// The decoded key of the parameter
+  pub key: String,
+  /// What's wrong with it
+  pub message: String,
+}
+
+impl QueryStringError {
+  fn new(key: &str, message: impl Into<String>) -> Self {
+    Self {
+      key: key.to_owned(),
+      message: message.into(),
+    }
+  }
+}
+
+impl std::fmt::Display for QueryStringError {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "Invalid query parameter {}: {}", self.key, self.message)
+  }
+}
+
+impl std::error::Error for QueryStringError {}
+
+impl GenericFilter {
+  /// Parse the query string of a request, without the leading `?`.
+  ///
+  /// The keys are percent decoded, `+` being a space:
+  ///
+  /// - `limit=10` and `offset=20`
+  /// - `with_deleted=true` to include the soft deleted rows
+  /// - `order[name]=desc`, `asc` or `desc`, in order of priority
+  /// - `where[name][eq]=foo`, the clause in kebab-case like with serde:
+  ///   - `eq`, `ne`, `gt`, `lt`, `ge`, `le` take the value as a text,
+  ///     parsed against the type of the column
+  ///   - `like`, `not-like`, `i-like`, `starts-with`, `ends-with`
+  ///     and `contains-text` take the pattern or the text
+  ///   - `between` and `not-between` take the two bounds, `18,30`
+  ///   - `in`, `not-in`, `array-contains` and `array-overlaps` take
+  ///     the values separated by commas, `1,2,3`,
+  ///     an empty value is an empty list
+  ///   - `is-null` and `is-not-null` ignore the value
+  ///   - `json-contains` takes a JSON document and `has-key` the key
+  ///   - `json-path-eq` and `json-path-exists` take the path as extra
+  ///     segments, `where[data][json-path-eq][profile][country]=FR`,
+  ///     the value is parsed as JSON and falls back to a string
+  ///   - `full-text` takes the search query, and the text search
+  ///     configuration as an optional extra segment,
+  ///     `where[body][full-text][english]=rust diesel`
+  ///
+  /// A column can appear several times, the clauses are ANDed in order.
+  /// Groups and `ExistsRelated` can't be written in a query string.
+  /// Other keys are ignored so the filter can share the query string
+  /// with the parameters of the handler.
+  pub fn from_query_str(query: &str) -> Result<Self, QueryStringError> {
+    let mut filter = Self::new();
+    for (key, value) in form_urlencoded::parse(query.as_bytes()) {
+      let (name, segments) = split_key(&key)?;
+      match name {
+        "limit" => {
+          no_segments(&key, &segments)?;
+          filter.limit = Some(parse_usize(&key, &value)?);
+        }
+        "offset" => {
+          no_segments(&key, &segments)?;
+          filter.offset = Some(parse_usize(&key, &value)?);
+        }
+        "with_deleted" => {
+          no_segments(&key, &segments)?;
+          filter.with_deleted = match value.as_ref() {
+            "true" => true,
+            "false" => false,
+            _ => {
+              return Err(QueryStringError::new(&key, "expected true or false"))
+            }
+          };
+        }
+        "order" => {
+          let [column] = segments.as_slice() else {
+            return Err(QueryStringError::new(&key, "expected order[column]"));
+          };
+          let direction = match value.as_ref() {
+            "asc" => OrderDirection::Asc,
+            "desc" => OrderDirection::Desc,
+            _ => {
+              return Err(QueryStringError::new(&key, "expected asc or desc"))
+            }
+          };
+          filter = filter.order_by(column, direction);
+        }
+        "where" => {
+          let [column, clause, path @ ..] = segments.as_slice() else {
+            return Err(QueryStringError::new(
+              &key,
+              "expected where[column][clause]",
+            ));
+          };
+          let clause = parse_clause(&key, clause, path, &value)?;
+          filter = filter.r#where(column, clause);
+        }
+        _ => {}
+      }
+    }
+    Ok(filter)
+  }
+}
+
+/// Split `where[name][eq]` into `where` and `["name", "eq"]`.
+fn split_key(key: &str) -> Result<(&str, Vec<&str>), QueryStringError> {
+  let Some(start) = key.find('[') else {
+    return Ok((key, Vec::new()));
+  };
+  let (name, mut rest) = key.split_at(start);
+  let mut segments = Vec::new();
+  while !rest.is_empty() {
+    let segment = rest
+      .strip_prefix('[')
+      .and_then(|rest| rest.split_once(']'))
+      .filter(|(segment, _)| !segment.is_empty() && !segment.contains('['));
+    let Some((segment, next)) = segment else {
+      return Err(QueryStringError::new(key, "malformed brackets"));
+    };
+    segments.push(segment);
+    rest = next;
+  }
+  Ok((name, segments))
+}
+
+fn no_segments(key: &str, segments: &[&str]) -> Result<(), QueryStringError> {
+  if segments.is_empty() {
+    Ok(())
+  } else {
+    Err(QueryStringError::new(key, "unexpected brackets"))
+  }
+}
+
+fn parse_usize(key: &str, value: &str) -> Result<usize, QueryStringError> {
+  value
+    .parse()
+    .map_err(|err| QueryStringError::new(key, format!("{value:?}: {err}")))
+}
+
+fn parse_clause(
+  key: &str,
+  clause: &str,
+  path: &[&str],
+  value: &str,
+) -> Result<GenericClause, QueryStringError> {
+  let text = || GenericValue::from(value);
+  let bounds = || match value.split_once(',') {
+    Some((lower, upper)) if !upper.contains(',') => {
+      Ok((GenericValue::from(lower), GenericValue::from(upper)))
+    }
+    _ => Err(QueryStringError::new(
+      key,
+      "expected two bounds, lower,upper",
+    )),
+  };
+  let list = || match value {
+    "" => Vec::new(),
+    value => value.split(',').map(GenericValue::from).collect(),
+  };
+  let json_path = clause.starts_with("json-path-");
+  if json_path && path.is_empty() {
+    return Err(QueryStringError::new(key, "expected the json path"));
+  }
+  let full_text = clause == "full-text" && path.len() == 1;
+  if !json_path && !full_text && !path.is_empty() {
+    return Err(QueryStringError::new(key, "unexpected brackets"));
+  }
+  let config = full_text.then(|| path[0].to_owned());
+  let path = || path.iter().map(|segment| segment.to_string()).collect();
+  let clause = match clause {
+    "eq" => GenericClause::Eq(text()),
+    "ne" => GenericClause::Ne(text()),
+    "gt" => GenericClause::Gt(text()),
+    "lt" => GenericClause::Lt(text()),
+    "ge" => GenericClause::Ge(text()),
+    "le" => GenericClause::Le(text()),
+    "like" => GenericClause::Like(value.to_owned()),
+    "not-like" => GenericClause::NotLike(value.to_owned()),
+    "i-like" => GenericClause::ILike(value.to_owned()),
+    "starts-with" => GenericClause::StartsWith(value.to_owned()),
+    "ends-with" => GenericClause::EndsWith(value.to_owned()),
+    "contains-text" => GenericClause::ContainsText(value.to_owned()),
+    "between" => {
+      let (lower, upper) = bounds()?;
+      GenericClause::Between(lower, upper)
+    }
+    "not-between" => {
+      let (lower, upper) = bounds()?;
+      GenericClause::NotBetween(lower, upper)
+    }
+    "in" => GenericClause::In(list()),
+    "not-in" => GenericClause::NotIn(list()),
+    "array-contains" => GenericClause::ArrayContains(list()),
+    "array-overlaps" => GenericClause::ArrayOverlaps(list()),
+    "is-null" => GenericClause::IsNull,
+    "is-not-null" => GenericClause::IsNotNull,
+    "json-contains" => {
+      let json = serde_json::from_str(value)
+        .map_err(|err| QueryStringError::new(key, err.to_string()))?;
+      GenericClause::JsonContains(json)
+    }
+    "has-key" => GenericClause::HasKey(value.to_owned()),
+    "json-path-eq" => {
+      let json = serde_json::from_str(value)
+        .unwrap_or_else(|_| serde_json::Value::String(value.to_owned()));
+      GenericClause::JsonPathEq(path(), json)
+    }
+    "json-path-exists" => GenericClause::JsonPathExists(path()),
+    "full-text" => GenericClause::FullText(value.to_owned(), config),
+    clause => {
+      return Err(QueryStringError::new(
+        key,
+        format!("unknown clause {clause}"),
+      ))
+    }
+  };
+  Ok(clause)
+}
+
+/// Extract the filter from the query string,
+/// a malformed one is answered with `400 Bad Request`.
+#[cfg(feature = "runtime-ntex")]
+impl<Err> ntex::web::FromRequest<Err> for GenericFilter
+where
+  Err: ntex::web::ErrorRenderer,
+{
+  type Error = QueryStringError;
+
+  async fn from_request(
+    req: &ntex::web::HttpRequest,
+    _: &mut ntex::http::Payload,
+  ) -> Result<Self, Self::Error> {
+    Self::from_query_str(req.query_string())
+  }
+}
+
+#[cfg(feature = "runtime-ntex")]
+impl ntex::web::WebResponseError<ntex::web::DefaultError> for QueryStringError {
+  fn status_code(&self) -> ntex::http::StatusCode {
+    ntex::http::StatusCode::BAD_REQUEST
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  /// The where clauses of the query string, in the debug format.
+  fn clauses(query: &str) -> Vec<String> {
+    let filter = GenericFilter::from_query_str(query).unwrap();
+    filter
+      .r#where
+      .unwrap_or_default()
+      .iter()
+      .map(|(column, clause)| format!("{column} {clause:?}"))
+      .collect()
+  }
+
+  fn error(query: &str) -> QueryStringError {
+    GenericFilter::from_query_str(query).unwrap_err()
+  }
+
+  #[test]
+  fn where_reads_the_column_and_the_clause() {
+    assert_eq!(
+      clauses("where%5Bname%5D%5Beq%5D=foo+bar&where[name][is-not-null]="),
+      [r#"name Eq(Text("foo bar"))"#, "name IsNotNull"]
+    );
+  }
+
+  #[test]
+  fn json_path_reads_the_extra_segments() {
+    assert_eq!(
+      clauses(
+        "where[data][json-path-eq][x][y]=FR&where[data][json-path-eq][z]=1"
+      ),
+      [
+        r#"data JsonPathEq(["x", "y"], String("FR"))"#,
+        r#"data JsonPathEq(["z"], Number(1))"#,
+      ]
+    );
+    assert_eq!(
+      error("where[data][json-path-exists]=").message,
+      "expected the json path"
+    );
+  }
+
+  #[test]
+  fn between_takes_two_bounds() {
+    assert_eq!(
+      clauses("where[age][between]=18,30"),
+      [r#"age Between(Text("18"), Text("30"))"#]
+    );
+    for query in ["where[age][between]=18", "where[age][between]=1,2,3"] {
+      assert_eq!(error(query).message, "expected two bounds, lower,upper");
+    }
+  }
+
+  #[test]
+  fn array_clauses_take_a_list() {
+    assert_eq!(
+      clauses("where[tags][array-contains]=a,b&where[tags][array-overlaps]="),
+      [
+        r#"tags ArrayContains([Text("a"), Text("b")])"#,
+        "tags ArrayOverlaps([])"
+      ]
+    );
+  }
+
+  #[test]
+  fn full_text_takes_an_optional_configuration() {
+    assert_eq!(
+      clauses("where[body][full-text]=fast db"),
+      [r#"body FullText("fast db", None)"#]
+    );
+    assert_eq!(
+      clauses("where[body][full-text][english]=fast"),
+      [r#"body FullText("fast", Some("english"))"#]
+    );
+    assert_eq!(
+      error("where[body][full-text][english][x]=fast").message,
+      "unexpected brackets"
+    );
+  }
+
+  #[test]
+  fn json_contains_takes_a_json_document() {
+    assert_eq!(
+      clauses(
+        r#"where[data][json-contains]={"a":1}&where[name][contains-text]={"#
+      ),
+      [
+        r#"data JsonContains(Object {"a": Number(1)})"#,
+        r#"name ContainsText("{")"#
+      ]
+    );
+    assert_eq!(
+      error("where[data][contains]=1").message,
+      "unknown clause contains"
+    );
+  }
+
+  #[test]
+  fn malformed_brackets_are_refused() {
+    for query in ["where[a=1", "where[][eq]=1", "where[a]b[eq]=1"] {
+      assert_eq!(error(query).message, "malformed brackets", "{query}");
+    }
+    assert_eq!(
+      error("where[a]=1").message,
+      "expected where[column][clause]"
+    );
+  }
+
+  #[test]
+  fn unknown_clauses_are_refused() {
+    let err = error("where[a][near]=1");
+    assert_eq!(err.key, "where[a][near]");
+    assert_eq!(err.message, "unknown clause near");
+  }
+
+  #[test]
+  fn limit_offset_and_order_are_read() {
+    let filter = GenericFilter::from_query_str(
+      "limit=10&offset=20&order[name]=desc&order[id]=asc&page=2",
+    )
+    .unwrap();
+    assert_eq!(filter.limit, Some(10));
+    assert_eq!(filter.offset, Some(20));
+    assert_eq!(
+      filter.order,
+      Some(vec![
+        ("name".to_owned(), OrderDirection::Desc),
+        ("id".to_owned(), OrderDirection::Asc),
+      ])
+    );
+    let err = error("limit=-1");
+    assert_eq!(err.key, "limit");
+    assert!(err.message.starts_with("\"-1\": "), "{}", err.message);
+    assert_eq!(error("order[name]=up").message, "expected asc or desc");
+  }
+
+  #[test]
+  fn with_deleted_takes_a_bool() {
+    let filter = GenericFilter::from_query_str("with_deleted=true").unwrap();
+    assert!(filter.with_deleted);
+    assert!(!GenericFilter::from_query_str("").unwrap().with_deleted);
+    assert_eq!(error("with_deleted=1").message, "expected true or false");
+  }
+}
@@ -0,0 +1,93 @@
+//! Where the blocking diesel calls are run, selected by the `runtime-*`
+//! features. When several are enabled ntex wins over tokio over async-std.
+
+use crate::error::TurboDieselError;
+
+#[cfg(feature = "runtime-ntex")]
+use ntex::rt::JoinError;
+#[cfg(all(feature = "runtime-tokio", not(feature = "runtime-ntex")))]
+use tokio::task::JoinError;
+
+#[cfg(not(any(
+  feature = "runtime-ntex",
+  feature = "runtime-tokio",
+  feature = "runtime-async-std"
+)))]
+compile_error!(
+  "enable one of the `runtime-ntex`, `runtime-tokio` or `runtime-async-std` features"
+);
+
+/// Resume a panic of the blocking closure on the awaiting task, any other
+/// error of the join handle means the runtime is shutting down.
+#[cfg(any(feature = "runtime-ntex", feature = "runtime-tokio"))]
+fn join_error(err: JoinError) -> TurboDieselError {
+  if err.is_panic() {
+    std::panic::resume_unwind(err.into_panic());
+  }
+  TurboDieselError::Connection(err.to_string())
+}
+
+/// Run `f` on the blocking thread pool of the runtime.
+#[cfg(feature = "runtime-ntex")]
+pub(crate) async fn spawn_blocking<F, R>(f: F) -> Result<R, TurboDieselError>
+where
+  F: FnOnce() -> R + Send + 'static,
+  R: Send + 'static,
+{
+  ntex::rt::spawn_blocking(f).await.map_err(join_error)
+}
+
+/// Run `f` on the blocking thread pool of the runtime.
+#[cfg(all(feature = "runtime-tokio", not(feature = "runtime-ntex")))]
+pub(crate) async fn spawn_blocking<F, R>(f: F) -> Result<R, TurboDieselError>
+where
+  F: FnOnce() -> R + Send + 'static,
+  R: Send + 'static,
+{
+  tokio::task::spawn_blocking(f).await.map_err(join_error)
+}
+
+/// Run `f` on the blocking thread pool of the runtime.
+#[cfg(all(
+  feature = "runtime-async-std",
+  not(feature = "runtime-ntex"),
+  not(feature = "runtime-tokio")
+))]
+pub(crate) async fn spawn_blocking<F, R>(f: F) -> Result<R, TurboDieselError>
+where
+  F: FnOnce() -> R + Send + 'static,
+  R: Send + 'static,
+{
+  Ok(async_std::task::spawn_blocking(f).await)
+}
+
+/// Run `f` on the blocking thread pool of the runtime without waiting for it.
+#[cfg(feature = "runtime-ntex")]
+pub(crate) fn spawn_blocking_detached<F>(f: F)
+where
+  F: FnOnce() + Send + 'static,
+{
+  drop(ntex::rt::spawn_blocking(f));
+}
+
+/// Run `f` on the blocking thread pool of the runtime without waiting for it.
+#[cfg(all(feature = "runtime-tokio", not(feature = "runtime-ntex")))]
+pub(crate) fn spawn_blocking_detached<F>(f: F)
+where
+  F: FnOnce() + Send + 'static,
+{
+  drop(tokio::task::spawn_blocking(f));
+}
+
+/// Run `f` on the blocking thread pool of the runtime without waiting for it.
+#[cfg(all(
+  feature = "runtime-async-std",
+  not(feature = "runtime-ntex"),
+  not(feature = "runtime-tokio")
+))]
+pub(crate) fn spawn_blocking_detached<F>(f: F)
+where
+  F: FnOnce() + Send + 'static,
+{
+  drop(async_std::task::spawn_blocking(f));
+}
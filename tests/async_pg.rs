@@ -0,0 +1,36 @@
+//! The queries of `AsyncDbDriver` on the Postgres server at `DATABASE_URL`,
+//! skipped when it isn't set.
+#![cfg(all(feature = "async", feature = "runtime-ntex"))]
+
+mod common;
+
+use common::*;
+use diesel_async::AsyncPgConnection;
+use turbo_diesel::prelude::*;
+
+#[ntex::test]
+async fn create_and_delete_rows() {
+  let Some(url) = pg_url("async_create_and_delete_rows") else {
+    return;
+  };
+  let db = AsyncDbDriver::<AsyncPgConnection>::new(&url).await.unwrap();
+  for (id, name) in [("1", "a"), ("2", "b"), ("3", "b")] {
+    let created = db.create(&user(id, name)).await.unwrap();
+    assert_eq!(created.id, id);
+  }
+  assert_eq!(db.del_by_pk::<DbUser, _>("1").await.unwrap(), 1);
+  assert_eq!(db.del_by_pk::<DbUser, _>("1").await.unwrap(), 0);
+  let filter =
+    GenericFilter::new().r#where("name", GenericClause::Eq("b".into()));
+  assert_eq!(db.del_by::<DbUser>(&filter).await.unwrap(), 2);
+  let err = db
+    .del_by::<DbUser>(&GenericFilter::new())
+    .await
+    .unwrap_err();
+  assert!(matches!(
+    err,
+    TurboDieselError::Query(diesel::result::Error::QueryBuilderError(_))
+  ));
+  let sync = DbDriver::<PgConnection>::new(&url).unwrap();
+  assert_eq!(count_users(&sync), 0);
+}
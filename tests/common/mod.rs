@@ -0,0 +1,614 @@
+//! Models and databases shared by the integration tests.
+#![allow(dead_code)]
+
+use diesel::prelude::*;
+use turbo_diesel::prelude::*;
+
+diesel::table! {
+  users (id) {
+      id -> Varchar,
+      name -> Varchar,
+  }
+}
+
+#[derive(
+  Clone,
+  Debug,
+  Insertable,
+  Queryable,
+  Identifiable,
+  AsChangeset,
+  FilterableColumns,
+  DbModelUpsert,
+  TurboModel,
+)]
+#[diesel(primary_key(id))]
+#[diesel(table_name = users)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+pub struct DbUser {
+  pub id: String,
+  pub name: String,
+}
+
+#[derive(Clone, Debug, AsChangeset)]
+#[diesel(table_name = users)]
+pub struct DbUserUpdate {
+  pub name: Option<String>,
+}
+
+/// A view of `DbUser` read with `read_by_into`.
+#[derive(Debug, PartialEq, Queryable, Selectable)]
+#[diesel(table_name = users)]
+pub struct UserName {
+  pub name: String,
+}
+
+diesel::table! {
+  memberships (user_id, group_id) {
+      user_id -> Varchar,
+      group_id -> Varchar,
+      role -> Varchar,
+  }
+}
+
+/// A join table, its primary key has two columns.
+#[derive(
+  Clone,
+  Debug,
+  Insertable,
+  Queryable,
+  Identifiable,
+  AsChangeset,
+  FilterableColumns,
+  TurboModel,
+)]
+#[diesel(primary_key(user_id, group_id))]
+#[diesel(table_name = memberships)]
+pub struct DbMembership {
+  pub user_id: String,
+  pub group_id: String,
+  pub role: String,
+}
+
+diesel::table! {
+  items (id) {
+      id -> Integer,
+      quantity -> Integer,
+      price -> Double,
+      active -> Bool,
+      note -> Nullable<Text>,
+  }
+}
+
+/// A model with a column of each scalar type.
+#[derive(
+  Clone,
+  Debug,
+  Insertable,
+  Queryable,
+  Identifiable,
+  FilterableColumns,
+  TurboModel,
+)]
+#[diesel(table_name = items)]
+pub struct DbItem {
+  pub id: i32,
+  pub quantity: i32,
+  pub price: f64,
+  pub active: bool,
+  pub note: Option<String>,
+}
+
+diesel::table! {
+  profiles (id) {
+      id -> Varchar,
+      data -> Jsonb,
+  }
+}
+
+/// A model with a jsonb column, filtered by JSON path on Postgres.
+#[derive(
+  Clone,
+  Debug,
+  Insertable,
+  Queryable,
+  Identifiable,
+  FilterableColumns,
+  TurboModel,
+)]
+#[diesel(table_name = profiles)]
+pub struct DbProfile {
+  pub id: String,
+  pub data: serde_json::Value,
+}
+
+diesel::table! {
+  notes (id) {
+      id -> Varchar,
+      body -> Varchar,
+      deleted_at -> Nullable<Timestamp>,
+  }
+}
+
+/// A soft deleted model, SQLite reads its timestamps as text.
+#[derive(
+  Clone,
+  Debug,
+  Insertable,
+  Queryable,
+  Identifiable,
+  FilterableColumns,
+  TurboModel,
+)]
+#[diesel(table_name = notes)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+#[turbo(soft_delete(deleted_at))]
+pub struct DbNote {
+  pub id: String,
+  pub body: String,
+  pub deleted_at: Option<String>,
+}
+
+diesel::table! {
+  posts (id) {
+      id -> Varchar,
+      title -> Varchar,
+      created_at -> Timestamp,
+      updated_at -> Timestamp,
+  }
+}
+
+/// A timestamped model, created from `DbPostCreate`.
+#[derive(
+  Clone,
+  Debug,
+  Insertable,
+  Queryable,
+  Identifiable,
+  FilterableColumns,
+  TurboModel,
+)]
+#[diesel(table_name = posts)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+#[turbo(timestamps(created_at, updated_at))]
+pub struct DbPost {
+  pub id: String,
+  pub title: String,
+  pub created_at: String,
+  pub updated_at: String,
+}
+
+#[derive(Clone, Debug, Insertable)]
+#[diesel(table_name = posts)]
+pub struct DbPostCreate {
+  pub id: String,
+  pub title: String,
+}
+
+#[derive(Clone, Debug, AsChangeset)]
+#[diesel(table_name = posts)]
+pub struct DbPostUpdate {
+  pub title: Option<String>,
+}
+
+diesel::table! {
+  documents (id) {
+      id -> Varchar,
+      body -> Varchar,
+      version -> Integer,
+  }
+}
+
+/// A model updated with optimistic concurrency control.
+#[derive(
+  Clone,
+  Debug,
+  Insertable,
+  Queryable,
+  Identifiable,
+  FilterableColumns,
+  TurboModel,
+)]
+#[diesel(table_name = documents)]
+#[turbo(version(version))]
+pub struct DbDocument {
+  pub id: String,
+  pub body: String,
+  pub version: i32,
+}
+
+#[derive(Clone, Debug, AsChangeset)]
+#[diesel(table_name = documents)]
+pub struct DbDocumentUpdate {
+  pub body: Option<String>,
+}
+
+diesel::table! {
+  articles (id) {
+      id -> Varchar,
+      tags -> Array<Text>,
+  }
+}
+
+/// A model with a Postgres array column.
+#[derive(
+  Clone,
+  Debug,
+  Insertable,
+  Queryable,
+  Identifiable,
+  FilterableColumns,
+  TurboModel,
+)]
+#[diesel(table_name = articles)]
+pub struct DbArticle {
+  pub id: String,
+  pub tags: Vec<String>,
+}
+
+diesel::table! {
+  orders (id) {
+      id -> Varchar,
+      user_id -> Varchar,
+  }
+}
+
+/// An order of a user, the related table of `ExistsRelated`.
+#[derive(Clone, Debug, Insertable, Queryable, Identifiable, TurboModel)]
+#[diesel(table_name = orders)]
+pub struct DbOrder {
+  pub id: String,
+  pub user_id: String,
+}
+
+diesel::table! {
+  accounts (id) {
+      id -> Varchar,
+      status -> Nullable<Varchar>,
+  }
+}
+
+/// An account with a status, counted per status on the dashboards.
+#[derive(
+  Clone,
+  Debug,
+  Insertable,
+  Queryable,
+  Identifiable,
+  FilterableColumns,
+  TurboModel,
+)]
+#[diesel(table_name = accounts)]
+pub struct DbAccount {
+  pub id: String,
+  pub status: Option<String>,
+}
+
+/// The query string of a handler listing the users.
+#[derive(Default, IntoFilter)]
+pub struct UserQuery {
+  #[filter(op = "starts-with")]
+  pub name: Option<String>,
+  #[filter(op = "in", column = "id")]
+  pub ids: Option<Vec<String>>,
+  #[filter(op = "not-between", column = "name")]
+  pub outside: Option<(String, String)>,
+  #[filter(skip)]
+  pub page: usize,
+}
+
+/// The query string of a handler listing the items,
+/// its fields without `Option` always add their clause.
+#[derive(IntoFilter)]
+pub struct ItemQuery {
+  #[filter(op = "ge", column = "quantity")]
+  pub min_quantity: i32,
+  #[filter(op = "is-null", column = "note")]
+  pub no_note: Option<bool>,
+  pub active: Option<bool>,
+}
+
+/// A row of a raw SQL query.
+#[derive(Debug, QueryableByName)]
+pub struct NameCount {
+  #[diesel(sql_type = diesel::sql_types::BigInt)]
+  pub count: i64,
+}
+
+#[cfg(feature = "chrono")]
+diesel::table! {
+  events (id) {
+      id -> Integer,
+      created_at -> Timestamp,
+      seen_at -> Timestamptz,
+  }
+}
+
+#[cfg(feature = "chrono")]
+#[derive(Debug, Insertable, Queryable, Identifiable, FilterableColumns)]
+#[diesel(table_name = events)]
+pub struct DbEvent {
+  pub id: i32,
+  pub created_at: chrono::NaiveDateTime,
+  pub seen_at: chrono::DateTime<chrono::Utc>,
+}
+
+diesel::table! {
+  customers (id) {
+      id -> Uuid,
+      name -> Varchar,
+  }
+}
+
+/// A `UUID` primary key wrapped in a newtype.
+#[derive(
+  Clone,
+  Copy,
+  Debug,
+  PartialEq,
+  Eq,
+  Hash,
+  diesel::AsExpression,
+  diesel::FromSqlRow,
+  PrimaryKey,
+)]
+#[diesel(sql_type = diesel::sql_types::Uuid)]
+pub struct CustomerId(pub uuid::Uuid);
+
+#[derive(Clone, Debug, Insertable, Queryable, Identifiable, TurboModel)]
+#[diesel(table_name = customers)]
+pub struct DbCustomer {
+  pub id: CustomerId,
+  pub name: String,
+}
+
+diesel::table! {
+  labels (id) {
+      id -> Varchar,
+      name -> Varchar,
+  }
+}
+
+/// A `TEXT` primary key wrapped in a newtype.
+#[derive(
+  Clone,
+  Debug,
+  PartialEq,
+  Eq,
+  Hash,
+  diesel::AsExpression,
+  diesel::FromSqlRow,
+  PrimaryKey,
+)]
+#[diesel(sql_type = diesel::sql_types::Text)]
+pub struct LabelId(pub String);
+
+#[derive(Clone, Debug, Insertable, Queryable, Identifiable, TurboModel)]
+#[diesel(table_name = labels)]
+pub struct DbLabel {
+  pub id: LabelId,
+  pub name: String,
+}
+
+#[cfg(feature = "uuid")]
+diesel::table! {
+  tickets (id) {
+      id -> Uuid,
+      title -> Varchar,
+      priority -> Integer,
+  }
+}
+
+/// A model keyed by a `Uuid`, filtered with the `uuid` feature.
+#[cfg(feature = "uuid")]
+#[derive(
+  Clone,
+  Debug,
+  Insertable,
+  Queryable,
+  Identifiable,
+  FilterableColumns,
+  TurboModel,
+)]
+#[diesel(table_name = tickets)]
+pub struct DbTicket {
+  pub id: uuid::Uuid,
+  pub title: String,
+  pub priority: i32,
+}
+
+#[cfg(feature = "uuid")]
+#[derive(Clone, Debug, AsChangeset)]
+#[diesel(table_name = tickets)]
+pub struct DbTicketUpdate {
+  pub title: Option<String>,
+}
+
+#[cfg(feature = "uuid")]
+pub fn ticket(id: uuid::Uuid) -> DbTicket {
+  DbTicket {
+    id,
+    title: "open".to_owned(),
+    priority: 0,
+  }
+}
+
+pub fn membership(user_id: &str, group_id: &str, role: &str) -> DbMembership {
+  DbMembership {
+    user_id: user_id.to_owned(),
+    group_id: group_id.to_owned(),
+    role: role.to_owned(),
+  }
+}
+
+/// The items 1 to 3, with a quantity equal to the id,
+/// a price of 1.0, 2.0 and 3.0, active but the second one,
+/// and a note on the first one.
+pub fn items() -> Vec<DbItem> {
+  (1..=3)
+    .map(|id| DbItem {
+      id,
+      quantity: id,
+      price: id.into(),
+      active: id != 2,
+      note: (id == 1).then(|| "first".to_owned()),
+    })
+    .collect()
+}
+
+pub fn note(id: &str, body: &str) -> DbNote {
+  DbNote {
+    id: id.to_owned(),
+    body: body.to_owned(),
+    deleted_at: None,
+  }
+}
+
+/// A document with the body, at version 0.
+pub fn document(id: &str, body: &str) -> DbDocument {
+  DbDocument {
+    id: id.to_owned(),
+    body: body.to_owned(),
+    version: 0,
+  }
+}
+
+pub fn order(id: &str, user_id: &str) -> DbOrder {
+  DbOrder {
+    id: id.to_owned(),
+    user_id: user_id.to_owned(),
+  }
+}
+
+pub fn account(id: &str, status: Option<&str>) -> DbAccount {
+  DbAccount {
+    id: id.to_owned(),
+    status: status.map(str::to_owned),
+  }
+}
+
+pub fn label(id: &str, name: &str) -> DbLabel {
+  DbLabel {
+    id: LabelId(id.to_owned()),
+    name: name.to_owned(),
+  }
+}
+
+pub fn user(id: &str, name: &str) -> DbUser {
+  DbUser {
+    id: id.to_owned(),
+    name: name.to_owned(),
+  }
+}
+
+/// The tables of the models, valid on SQLite and Postgres.
+const SCHEMA: &str = "
+  CREATE TABLE users (id TEXT PRIMARY KEY NOT NULL, name TEXT NOT NULL);
+  CREATE TABLE memberships (
+    user_id TEXT NOT NULL,
+    group_id TEXT NOT NULL,
+    role TEXT NOT NULL,
+    PRIMARY KEY (user_id, group_id)
+  );
+  CREATE TABLE items (
+    id INTEGER PRIMARY KEY NOT NULL,
+    quantity INTEGER NOT NULL,
+    price DOUBLE PRECISION NOT NULL,
+    active BOOLEAN NOT NULL,
+    note TEXT
+  );
+  CREATE TABLE notes (
+    id TEXT PRIMARY KEY NOT NULL,
+    body TEXT NOT NULL,
+    deleted_at TIMESTAMP
+  );
+  CREATE TABLE posts (
+    id TEXT PRIMARY KEY NOT NULL,
+    title TEXT NOT NULL,
+    created_at TIMESTAMP NOT NULL,
+    updated_at TIMESTAMP NOT NULL
+  );
+  CREATE TABLE orders (id TEXT PRIMARY KEY NOT NULL, user_id TEXT NOT NULL);
+  CREATE TABLE accounts (id TEXT PRIMARY KEY NOT NULL, status TEXT);
+  CREATE TABLE labels (id TEXT PRIMARY KEY NOT NULL, name TEXT NOT NULL);
+  CREATE TABLE documents (
+    id TEXT PRIMARY KEY NOT NULL,
+    body TEXT NOT NULL,
+    version INTEGER NOT NULL
+  );
+";
+
+/// The tables of the models only valid on Postgres.
+const PG_SCHEMA: &str = "
+  CREATE TABLE profiles (id TEXT PRIMARY KEY NOT NULL, data JSONB NOT NULL);
+  CREATE TABLE articles (id TEXT PRIMARY KEY NOT NULL, tags TEXT[] NOT NULL);
+  CREATE TABLE customers (id UUID PRIMARY KEY NOT NULL, name TEXT NOT NULL);
+  CREATE TABLE tickets (
+    id UUID PRIMARY KEY NOT NULL,
+    title TEXT NOT NULL,
+    priority INTEGER NOT NULL
+  );
+  CREATE TABLE events (
+    id INTEGER PRIMARY KEY,
+    created_at TIMESTAMP NOT NULL,
+    seen_at TIMESTAMPTZ NOT NULL
+  );
+";
+
+/// The path of a new SQLite database named after the test.
+pub fn sqlite_path(name: &str) -> String {
+  let path = std::env::temp_dir().join(format!("turbo_diesel_{name}.db"));
+  let _ = std::fs::remove_file(&path);
+  path.to_str().unwrap().to_owned()
+}
+
+/// A driver on a new SQLite database named after the test.
+pub fn sqlite(name: &str) -> DbDriver<SqliteConnection> {
+  use diesel::connection::SimpleConnection;
+
+  let db = DbDriver::<SqliteConnection>::new(&sqlite_path(name)).unwrap();
+  db.get_conn().unwrap().batch_execute(SCHEMA).unwrap();
+  db
+}
+
+/// The url of a new schema named after the test of the Postgres server
+/// at `DATABASE_URL`, `None` when it isn't set.
+pub fn pg_url(name: &str) -> Option<String> {
+  use diesel::connection::SimpleConnection;
+
+  let url = std::env::var("DATABASE_URL").ok()?;
+  let schema = format!("turbo_diesel_{name}");
+  let mut conn = PgConnection::establish(&url).unwrap();
+  conn
+    .batch_execute(&format!(
+      "DROP SCHEMA IF EXISTS {schema} CASCADE; CREATE SCHEMA {schema}; \
+       SET search_path TO {schema}"
+    ))
+    .unwrap();
+  conn.batch_execute(SCHEMA).unwrap();
+  conn.batch_execute(PG_SCHEMA).unwrap();
+  let separator = if url.contains('?') { '&' } else { '?' };
+  Some(format!("{url}{separator}options=-csearch_path%3D{schema}"))
+}
+
+/// A driver on a new schema named after the test of the Postgres server
+/// at `DATABASE_URL`, `None` when it isn't set.
+pub fn pg(name: &str) -> Option<DbDriver<PgConnection>> {
+  Some(DbDriver::<PgConnection>::new(&pg_url(name)?).unwrap())
+}
+
+/// The number of rows of the users table.
+pub fn count_users<D>(db: &DbDriver<D>) -> i64
+where
+  D: diesel::r2d2::R2D2Connection
+    + Connection
+    + diesel::connection::LoadConnection
+    + 'static,
+  diesel::dsl::Select<users::table, diesel::dsl::CountStar>:
+    diesel::query_dsl::LoadQuery<'static, D, i64>,
+{
+  users::table
+    .count()
+    .get_result(&mut *db.get_conn().unwrap())
+    .unwrap()
+}
@@ -0,0 +1,141 @@
+//! The metrics of the `metrics` feature.
+#![cfg(all(feature = "metrics", feature = "runtime-ntex"))]
+
+mod common;
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+
+use common::*;
+use metrics::{
+  Counter, Gauge, Histogram, HistogramFn, Key, KeyName, Metadata, Recorder,
+  SharedString, Unit,
+};
+use turbo_diesel::prelude::*;
+
+/// Counts the recordings of a histogram.
+#[derive(Default)]
+struct Recordings(AtomicU64);
+
+impl HistogramFn for Recordings {
+  fn record(&self, _: f64) {
+    self.0.fetch_add(1, Ordering::Relaxed);
+  }
+}
+
+/// A global recorder keeping the values in memory, by name and labels,
+/// the metrics of the blocking threads only reach the global one.
+#[derive(Default)]
+struct Values {
+  /// Counters and gauges, a gauge holding the bits of its `f64`
+  atomics: Mutex<HashMap<String, Arc<AtomicU64>>>,
+  /// Number of recordings of the histograms
+  histograms: Mutex<HashMap<String, Arc<Recordings>>>,
+}
+
+/// `name{label=value,...}`, the labels in the order they're recorded in.
+fn key_name(key: &Key) -> String {
+  let labels = key
+    .labels()
+    .map(|label| format!("{}={}", label.key(), label.value()))
+    .collect::<Vec<_>>();
+  format!("{}{{{}}}", key.name(), labels.join(","))
+}
+
+impl Values {
+  fn atomic(&self, key: &Key) -> Arc<AtomicU64> {
+    let mut atomics = self.atomics.lock().unwrap();
+    atomics.entry(key_name(key)).or_default().clone()
+  }
+
+  fn counter(&self, name: &str) -> u64 {
+    let atomics = self.atomics.lock().unwrap();
+    atomics
+      .get(name)
+      .map_or(0, |value| value.load(Ordering::Relaxed))
+  }
+
+  fn gauge(&self, name: &str) -> Option<f64> {
+    let atomics = self.atomics.lock().unwrap();
+    let value = atomics.get(name)?;
+    Some(f64::from_bits(value.load(Ordering::Relaxed)))
+  }
+
+  fn recordings(&self, name: &str) -> u64 {
+    let histograms = self.histograms.lock().unwrap();
+    histograms
+      .get(name)
+      .map_or(0, |value| value.0.load(Ordering::Relaxed))
+  }
+}
+
+struct InMemory(&'static Values);
+
+impl Recorder for InMemory {
+  fn describe_counter(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+
+  fn describe_gauge(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+
+  fn describe_histogram(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+
+  fn register_counter(&self, key: &Key, _: &Metadata<'_>) -> Counter {
+    Counter::from_arc(self.0.atomic(key))
+  }
+
+  fn register_gauge(&self, key: &Key, _: &Metadata<'_>) -> Gauge {
+    Gauge::from_arc(self.0.atomic(key))
+  }
+
+  fn register_histogram(&self, key: &Key, _: &Metadata<'_>) -> Histogram {
+    let mut histograms = self.0.histograms.lock().unwrap();
+    Histogram::from_arc(histograms.entry(key_name(key)).or_default().clone())
+  }
+}
+
+/// The values recorded since the first call.
+fn values() -> &'static Values {
+  static VALUES: OnceLock<&'static Values> = OnceLock::new();
+  VALUES.get_or_init(|| {
+    let values = Box::leak(Box::default());
+    metrics::set_global_recorder(InMemory(values)).unwrap();
+    values
+  })
+}
+
+#[ntex::test]
+async fn queries_and_their_errors_are_counted() {
+  let values = values();
+  let db = sqlite("queries_and_their_errors_are_counted");
+  let created = "turbo_diesel_queries_total{operation=create}";
+  let duplicates =
+    "turbo_diesel_query_errors_total{operation=create,kind=unique_violation}";
+  let (created_before, duplicates_before) =
+    (values.counter(created), values.counter(duplicates));
+  db.create(&user("1", "a")).await.unwrap();
+  assert_eq!(values.counter(created), created_before + 1);
+  assert_eq!(values.counter(duplicates), duplicates_before);
+  let res = db.create(&user("1", "a")).await;
+  assert!(matches!(res, Err(TurboDieselError::UniqueViolation(_))));
+  assert_eq!(values.counter(created), created_before + 2);
+  assert_eq!(values.counter(duplicates), duplicates_before + 1);
+  let read = "turbo_diesel_queries_total{operation=read_by}";
+  let read_before = values.counter(read);
+  db.read_by::<DbUser>(&GenericFilter::new()).await.unwrap();
+  assert_eq!(values.counter(read), read_before + 1);
+}
+
+#[ntex::test]
+async fn checkouts_record_the_wait_and_the_pool() {
+  let values = values();
+  let db = sqlite("checkouts_record_the_wait_and_the_pool");
+  let checkouts = "turbo_diesel_checkout_seconds{pool=primary}";
+  let before = values.recordings(checkouts);
+  let _held = db.get_conn().unwrap();
+  assert!(values.recordings(checkouts) > before);
+  let in_use = values.gauge("turbo_diesel_connections_in_use{pool=primary}");
+  let idle = values.gauge("turbo_diesel_connections_idle{pool=primary}");
+  // The gauges are shared with the other tests running at the same time
+  assert!(in_use.is_some_and(|in_use| in_use >= 1.0), "{in_use:?}");
+  assert!(idle.is_some(), "{idle:?}");
+}
@@ -0,0 +1,64 @@
+//! The queries of the driver on the MySQL server at `MYSQL_URL`,
+//! skipped when it isn't set.
+#![cfg(all(feature = "mysql", feature = "runtime-ntex"))]
+
+mod common;
+
+use common::*;
+use diesel::connection::SimpleConnection;
+use turbo_diesel::prelude::*;
+
+/// A driver on the database at `MYSQL_URL` with an empty users table,
+/// `None` when it isn't set.
+/// Every check runs in a single test as the tables are shared.
+fn mysql() -> Option<DbDriver<MysqlConnection>> {
+  let url = std::env::var("MYSQL_URL").ok()?;
+  let db = DbDriver::<MysqlConnection>::new(&url).unwrap();
+  db.get_conn()
+    .unwrap()
+    .batch_execute(
+      "DROP TABLE IF EXISTS users; \
+       CREATE TABLE users (id VARCHAR(255) PRIMARY KEY, name TEXT NOT NULL);",
+    )
+    .unwrap();
+  Some(db)
+}
+
+#[ntex::test]
+async fn queries_work_without_returning() {
+  let Some(db) = mysql() else {
+    return;
+  };
+  for (id, name) in [("1", "Test a"), ("2", "test b")] {
+    let created = db.create_and_find(&user(id, name)).await.unwrap();
+    assert_eq!(created.name, name);
+  }
+  assert_eq!(db.create_fast(&user("3", "other")).await.unwrap(), 1);
+  let found = db.read_by_pk::<DbUser, _>("1").await.unwrap().unwrap();
+  assert_eq!(found.name, "Test a");
+  let filter = GenericFilter::new()
+    .r#where("name", GenericClause::ILike("te%".to_owned()))
+    .order_by("id", OrderDirection::Desc);
+  let users = db.read_by::<DbUser>(&filter).await.unwrap();
+  let ids = users
+    .iter()
+    .map(|user| user.id.as_str())
+    .collect::<Vec<_>>();
+  assert_eq!(ids, ["2", "1"]);
+  let first = db.read_one::<DbUser>(&filter).await.unwrap().unwrap();
+  assert_eq!(first.id, "2");
+  assert_eq!(db.count_by::<DbUser>(&filter).await.unwrap(), 2);
+  assert!(db.exists_by::<DbUser>(&filter).await.unwrap());
+  let changeset = DbUserUpdate {
+    name: Some("matched".to_owned()),
+  };
+  assert_eq!(
+    db.update_by::<DbUser, _>(&filter, changeset).await.unwrap(),
+    2
+  );
+  assert_eq!(db.del_by_pk::<DbUser, _>("1").await.unwrap(), 1);
+  let filter =
+    GenericFilter::new().r#where("name", GenericClause::Eq("matched".into()));
+  assert_eq!(db.del_by::<DbUser>(&filter).await.unwrap(), 1);
+  assert_eq!(db.del_all::<DbUser>(Confirm::Yes).await.unwrap(), 1);
+}
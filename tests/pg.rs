@@ -0,0 +1,1480 @@
+//! The queries of the driver on the Postgres server at `DATABASE_URL`,
+//! skipped when it isn't set.
+#![cfg(feature = "runtime-ntex")]
+
+mod common;
+
+use common::*;
+use diesel::result::Error;
+use turbo_diesel::prelude::*;
+
+#[ntex::test]
+async fn del_by_applies_the_where_clause() {
+  let Some(db) = pg("del_by_applies_the_where_clause") else {
+    return;
+  };
+  db.create(&user("1", "a")).await.unwrap();
+  db.create(&user("2", "b")).await.unwrap();
+  let res = db.del_by::<DbUser>(&GenericFilter::new()).await;
+  assert!(matches!(
+    res,
+    Err(TurboDieselError::Query(Error::QueryBuilderError(_)))
+  ));
+  let filter =
+    GenericFilter::new().r#where("name", GenericClause::Eq("a".into()));
+  assert_eq!(db.del_by::<DbUser>(&filter).await.unwrap(), 1);
+  assert_eq!(db.del_by::<DbUser>(&filter).await.unwrap(), 0);
+  assert_eq!(count_users(&db), 1);
+  assert_eq!(db.del_by_pk::<DbUser, _>("2").await.unwrap(), 1);
+  assert_eq!(db.del_by_pk::<DbUser, _>("2").await.unwrap(), 0);
+  db.create(&user("3", "c")).await.unwrap();
+  assert_eq!(db.del_all::<DbUser>(Confirm::Yes).await.unwrap(), 1);
+  assert_eq!(count_users(&db), 0);
+}
+
+#[ntex::test]
+async fn read_by_pk_returns_none_for_a_missing_row() {
+  let Some(db) = pg("read_by_pk_returns_none_for_a_missing_row") else {
+    return;
+  };
+  db.create(&user("1", "a")).await.unwrap();
+  let found = db.read_by_pk::<DbUser, _>("1").await.unwrap();
+  assert_eq!(found.map(|user| user.name), Some("a".to_owned()));
+  let missing = db.read_by_pk::<DbUser, _>("2").await.unwrap();
+  assert!(missing.is_none());
+}
+
+#[ntex::test]
+async fn read_by_applies_the_filter() {
+  let Some(db) = pg("read_by_applies_the_filter") else {
+    return;
+  };
+  for (id, name) in [("1", "ab"), ("2", "ac"), ("3", "b")] {
+    db.create(&user(id, name)).await.unwrap();
+  }
+  let filter = GenericFilter::new()
+    .r#where("name", GenericClause::Like("a%".to_owned()))
+    .limit(10);
+  let users = db.read_by::<DbUser>(&filter).await.unwrap();
+  assert_eq!(users.len(), 2);
+}
+
+#[ntex::test]
+async fn update_by_pk_updates_the_name() {
+  let Some(db) = pg("update_by_pk_updates_the_name") else {
+    return;
+  };
+  db.create(&user("1", "a")).await.unwrap();
+  let changeset = DbUserUpdate {
+    name: Some("b".to_owned()),
+  };
+  let updated = db
+    .update_by_pk::<DbUser, _, _>("1", changeset)
+    .await
+    .unwrap();
+  assert_eq!(updated.name, "b");
+}
+
+#[ntex::test]
+async fn create_many_inserts_every_chunk() {
+  let Some(db) = pg("create_many_inserts_every_chunk") else {
+    return;
+  };
+  assert_eq!(
+    <<users::table as diesel::Table>::AllColumns as ColumnCount>::COUNT,
+    2
+  );
+  let users = (0..1200)
+    .map(|i| user(&i.to_string(), "a"))
+    .collect::<Vec<_>>();
+  let created = db.create_many(users, None).await.unwrap();
+  assert_eq!(created.len(), 1200);
+  assert_eq!(count_users(&db), 1200);
+}
+
+#[ntex::test]
+async fn count_by_counts_the_matching_rows() {
+  let Some(db) = pg("count_by_counts_the_matching_rows") else {
+    return;
+  };
+  for (id, name) in [("1", "a"), ("2", "a"), ("3", "b")] {
+    db.create(&user(id, name)).await.unwrap();
+  }
+  let filter =
+    GenericFilter::new().r#where("name", GenericClause::Eq("a".into()));
+  assert_eq!(db.count_by::<DbUser>(&filter).await.unwrap(), 2);
+}
+
+#[ntex::test]
+async fn exists_by_tells_if_a_row_matches() {
+  let Some(db) = pg("exists_by_tells_if_a_row_matches") else {
+    return;
+  };
+  db.create(&user("1", "a")).await.unwrap();
+  let a = GenericFilter::new().r#where("name", GenericClause::Eq("a".into()));
+  assert!(db.exists_by::<DbUser>(&a).await.unwrap());
+  let b = GenericFilter::new().r#where("name", GenericClause::Eq("b".into()));
+  assert!(!db.exists_by::<DbUser>(&b).await.unwrap());
+}
+
+#[ntex::test]
+async fn transaction_rolls_back_on_err() {
+  let Some(db) = pg("transaction_rolls_back_on_err") else {
+    return;
+  };
+  let res = db
+    .transaction(|conn| {
+      diesel::insert_into(users::table)
+        .values(user("1", "a"))
+        .execute(conn)?;
+      diesel::insert_into(users::table)
+        .values(user("1", "a"))
+        .execute(conn)?;
+      Ok(())
+    })
+    .await;
+  assert!(res.is_err());
+  assert_eq!(count_users(&db), 0);
+}
+
+#[cfg(feature = "chrono")]
+#[ntex::test]
+async fn between_filters_a_timestamp_window() {
+  let Some(db) = pg("between_filters_a_timestamp_window") else {
+    return;
+  };
+  let mut conn = db.get_conn().unwrap();
+  let events = (1..=3)
+    .map(|day| {
+      let at = format!("2024-01-0{day}T12:00:00");
+      DbEvent {
+        id: day,
+        created_at: at.parse().unwrap(),
+        seen_at: format!("{at}Z").parse().unwrap(),
+      }
+    })
+    .collect::<Vec<_>>();
+  diesel::insert_into(events::table)
+    .values(events)
+    .execute(&mut conn)
+    .unwrap();
+  let window = [
+    (
+      "created_at",
+      GenericClause::Between(
+        "2024-01-01T00:00:00".into(),
+        "2024-01-02T23:59:59".into(),
+      ),
+    ),
+    (
+      "seen_at",
+      GenericClause::Between(
+        "2024-01-02T00:00:00Z".into(),
+        "2024-01-03T23:59:59+02:00".into(),
+      ),
+    ),
+  ];
+  for (column, clause) in window {
+    let predicate = <DbEvent as FilterableColumns<diesel::pg::Pg>>::predicate(
+      column, &clause,
+    )
+    .unwrap();
+    let found = events::table
+      .filter(predicate)
+      .load::<DbEvent>(&mut conn)
+      .unwrap();
+    assert_eq!(found.len(), 2, "{column}");
+  }
+}
+
+#[ntex::test]
+async fn read_by_sorts_by_every_order_entry() {
+  let Some(db) = pg("read_by_sorts_by_every_order_entry") else {
+    return;
+  };
+  for (id, name) in [("1", "b"), ("2", "a"), ("3", "b"), ("4", "c")] {
+    db.create(&user(id, name)).await.unwrap();
+  }
+  let filter = GenericFilter::new()
+    .order_by("name", OrderDirection::Asc)
+    .order_by("id", OrderDirection::Desc)
+    .limit(3);
+  let users = db.read_by::<DbUser>(&filter).await.unwrap();
+  let ids = users
+    .iter()
+    .map(|user| user.id.as_str())
+    .collect::<Vec<_>>();
+  assert_eq!(ids, ["2", "3", "1"]);
+}
+
+#[ntex::test]
+async fn read_by_ilike_ignores_the_case() {
+  let Some(db) = pg("read_by_ilike_ignores_the_case") else {
+    return;
+  };
+  for (id, name) in [("1", "Bob"), ("2", "BOBBY"), ("3", "alice")] {
+    db.create(&user(id, name)).await.unwrap();
+  }
+  let filter = GenericFilter::new()
+    .r#where("name", GenericClause::ILike("bob%".to_owned()));
+  assert_eq!(db.count_by::<DbUser>(&filter).await.unwrap(), 2);
+}
+
+#[ntex::test]
+async fn text_clauses_match_the_wildcards_literally() {
+  let Some(db) = pg("text_clauses_match_the_wildcards_literally") else {
+    return;
+  };
+  let names = [
+    ("1", "50% off"),
+    ("2", "500 off"),
+    ("3", "a_b"),
+    ("4", "axb"),
+  ];
+  for (id, name) in names {
+    db.create(&user(id, name)).await.unwrap();
+  }
+  db.create(&user("5", "c:\\dir")).await.unwrap();
+  let db = &db;
+  let count = |clause| {
+    let filter = GenericFilter::new().r#where("name", clause);
+    async move { db.count_by::<DbUser>(&filter).await.unwrap() }
+  };
+  assert_eq!(count(GenericClause::StartsWith("50%".to_owned())).await, 1);
+  assert_eq!(count(GenericClause::Like("50%".to_owned())).await, 2);
+  assert_eq!(count(GenericClause::EndsWith("_b".to_owned())).await, 1);
+  assert_eq!(count(GenericClause::ContainsText("_".to_owned())).await, 1);
+  assert_eq!(count(GenericClause::ContainsText("\\".to_owned())).await, 1);
+  assert_eq!(
+    count(GenericClause::ContainsText("off".to_owned())).await,
+    2
+  );
+}
+
+#[ntex::test]
+async fn read_by_unlimited_returns_every_row() {
+  let Some(db) = pg("read_by_unlimited_returns_every_row") else {
+    return;
+  };
+  let users = (0..150)
+    .map(|i| user(&i.to_string(), "a"))
+    .collect::<Vec<_>>();
+  db.create_many(users, None).await.unwrap();
+  let filter = GenericFilter::new();
+  assert_eq!(db.read_by::<DbUser>(&filter).await.unwrap().len(), 100);
+  let unlimited = GenericFilter::new().unlimited();
+  assert_eq!(db.read_by::<DbUser>(&unlimited).await.unwrap().len(), 150);
+}
+
+#[ntex::test]
+async fn upsert_updates_the_existing_row() {
+  let Some(db) = pg("upsert_updates_the_existing_row") else {
+    return;
+  };
+  let created = db.upsert(&user("1", "first")).await.unwrap();
+  assert_eq!(created.name, "first");
+  let updated = db.upsert(&user("1", "second")).await.unwrap();
+  assert_eq!(updated.name, "second");
+  assert_eq!(count_users(&db), 1);
+  let read = db.read_by_pk::<DbUser, _>("1").await.unwrap().unwrap();
+  assert_eq!(read.name, "second");
+}
+
+#[ntex::test]
+async fn read_one_returns_the_first_match() {
+  let Some(db) = pg("read_one_returns_the_first_match") else {
+    return;
+  };
+  for (id, name) in [("2", "b"), ("1", "a"), ("3", "b")] {
+    db.create(&user(id, name)).await.unwrap();
+  }
+  let filter =
+    GenericFilter::new().r#where("name", GenericClause::Eq("a".into()));
+  let found = db.read_one::<DbUser>(&filter).await.unwrap().unwrap();
+  assert_eq!(found.id, "1");
+  let filter =
+    GenericFilter::new().r#where("name", GenericClause::Eq("b".into()));
+  let found = db.read_one::<DbUser>(&filter).await.unwrap().unwrap();
+  assert_eq!(found.id, "2");
+  let filter = filter.order_by("id", OrderDirection::Desc);
+  let found = db.read_one::<DbUser>(&filter).await.unwrap().unwrap();
+  assert_eq!(found.id, "3");
+  let filter =
+    GenericFilter::new().r#where("name", GenericClause::Eq("c".into()));
+  assert!(db.read_one::<DbUser>(&filter).await.unwrap().is_none());
+}
+
+#[ntex::test]
+async fn update_by_updates_the_matching_rows() {
+  let Some(db) = pg("update_by_updates_the_matching_rows") else {
+    return;
+  };
+  for (id, name) in [("1", "test a"), ("2", "test b"), ("3", "other")] {
+    db.create(&user(id, name)).await.unwrap();
+  }
+  let filter = GenericFilter::new()
+    .r#where("name", GenericClause::Like("test%".to_owned()));
+  let changeset = DbUserUpdate {
+    name: Some("matched".to_owned()),
+  };
+  assert_eq!(
+    db.update_by::<DbUser, _>(&filter, changeset).await.unwrap(),
+    2
+  );
+  let filter =
+    GenericFilter::new().r#where("name", GenericClause::Eq("matched".into()));
+  assert_eq!(db.count_by::<DbUser>(&filter).await.unwrap(), 2);
+  let refused = [
+    GenericFilter::new(),
+    GenericFilter::new().r#where("id", GenericClause::NotIn(vec![])),
+  ];
+  for filter in refused {
+    let changeset = DbUserUpdate {
+      name: Some("everyone".to_owned()),
+    };
+    let res = db.update_by::<DbUser, _>(&filter, changeset).await;
+    assert!(
+      matches!(
+        res,
+        Err(TurboDieselError::Query(Error::QueryBuilderError(_)))
+      ),
+      "{filter:?}"
+    );
+  }
+  let changeset = DbUserUpdate {
+    name: Some("everyone".to_owned()),
+  };
+  assert_eq!(
+    db.update_all::<DbUser, _>(changeset, Confirm::Yes)
+      .await
+      .unwrap(),
+    3
+  );
+}
+
+#[ntex::test]
+async fn ping_reaches_the_server() {
+  let Some(db) = pg("ping_reaches_the_server") else {
+    return;
+  };
+  db.ping().await.unwrap();
+  assert!(db.pool_state().connections >= 1);
+}
+
+#[ntex::test]
+async fn by_pk_methods_take_a_composite_key() {
+  let Some(db) = pg("by_pk_methods_take_a_composite_key") else {
+    return;
+  };
+  db.create(&membership("1", "1", "owner")).await.unwrap();
+  db.create(&membership("1", "2", "member")).await.unwrap();
+  let pk = ("1".to_owned(), "2".to_owned());
+  let found = db
+    .read_by_pk::<DbMembership, _>(&pk)
+    .await
+    .unwrap()
+    .unwrap();
+  assert_eq!(found.role, "member");
+  let missing = ("2".to_owned(), "1".to_owned());
+  assert!(db
+    .read_by_pk::<DbMembership, _>(&missing)
+    .await
+    .unwrap()
+    .is_none());
+  assert_eq!(db.del_by_pk::<DbMembership, _>(&pk).await.unwrap(), 1);
+  assert_eq!(db.del_by_pk::<DbMembership, _>(&pk).await.unwrap(), 0);
+  let pk = ("1".to_owned(), "1".to_owned());
+  assert!(db
+    .read_by_pk::<DbMembership, _>(&pk)
+    .await
+    .unwrap()
+    .is_some());
+}
+
+#[ntex::test]
+async fn create_and_find_reads_the_row_back() {
+  let Some(db) = pg("create_and_find_reads_the_row_back") else {
+    return;
+  };
+  let created = db.create_and_find(&user("1", "a")).await.unwrap();
+  assert_eq!((created.id.as_str(), created.name.as_str()), ("1", "a"));
+  let res = db.create_and_find(&user("1", "b")).await;
+  assert!(res.is_err());
+  assert_eq!(count_users(&db), 1);
+}
+
+#[ntex::test]
+async fn paginate_counts_every_matching_row() {
+  let Some(db) = pg("paginate_counts_every_matching_row") else {
+    return;
+  };
+  let users = (0..25)
+    .map(|i| user(&format!("{i:02}"), if i < 20 { "a" } else { "b" }))
+    .collect::<Vec<_>>();
+  db.create_many(users, None).await.unwrap();
+  let filter = GenericFilter::new()
+    .r#where("name", GenericClause::Eq("a".into()))
+    .order_by("id", OrderDirection::Asc)
+    .limit(8)
+    .offset(16);
+  let page = db.paginate::<DbUser>(&filter).await.unwrap();
+  assert_eq!(page.total, 20);
+  assert_eq!((page.limit, page.offset), (8, 16));
+  let ids = page
+    .items
+    .iter()
+    .map(|user| user.id.as_str())
+    .collect::<Vec<_>>();
+  assert_eq!(ids, ["16", "17", "18", "19"]);
+  let page = db.paginate::<DbUser>(&GenericFilter::new()).await.unwrap();
+  assert_eq!((page.total, page.items.len()), (25, 25));
+  assert_eq!((page.limit, page.offset), (100, 0));
+}
+
+#[ntex::test]
+async fn typed_values_compare_with_the_column_type() {
+  let Some(db) = pg("typed_values_compare_with_the_column_type") else {
+    return;
+  };
+  db.create_many(items(), None).await.unwrap();
+  let filtered = [
+    ("quantity", GenericClause::Eq(2.into()), 1),
+    ("quantity", GenericClause::Eq("2".into()), 1),
+    ("quantity", GenericClause::In(vec![1.into(), 3.into()]), 2),
+    ("price", GenericClause::Gt(1.5.into()), 2),
+    ("price", GenericClause::Le(2.into()), 2),
+    ("active", GenericClause::Eq(true.into()), 2),
+    ("note", GenericClause::Eq(GenericValue::Null), 2),
+    ("note", GenericClause::Ne(GenericValue::Null), 1),
+  ];
+  for (column, clause, expected) in filtered {
+    let filter = GenericFilter::new().r#where(column, clause.clone());
+    let count = db.count_by::<DbItem>(&filter).await.unwrap();
+    assert_eq!(count, expected, "{column} {clause:?}");
+  }
+  for clause in [
+    GenericClause::Eq("two".into()),
+    GenericClause::Eq(true.into()),
+  ] {
+    let filter = GenericFilter::new().r#where("quantity", clause);
+    let res = db.count_by::<DbItem>(&filter).await;
+    assert!(
+      matches!(
+        res,
+        Err(TurboDieselError::Query(Error::QueryBuilderError(_)))
+      ),
+      "{res:?}"
+    );
+  }
+}
+
+#[ntex::test]
+async fn create_fast_returns_the_inserted_count() {
+  let Some(db) = pg("create_fast_returns_the_inserted_count") else {
+    return;
+  };
+  assert_eq!(db.create_fast(&user("1", "a")).await.unwrap(), 1);
+  assert!(db.create_fast(&user("1", "b")).await.is_err());
+  let found = db.read_by_pk::<DbUser, _>("1").await.unwrap().unwrap();
+  assert_eq!(found.name, "a");
+}
+
+#[ntex::test]
+async fn create_or_ignore_skips_the_existing_row() {
+  let Some(db) = pg("create_or_ignore_skips_the_existing_row") else {
+    return;
+  };
+  let created = db.create_or_ignore(&user("1", "a")).await.unwrap();
+  assert_eq!(created.map(|user| user.name).as_deref(), Some("a"));
+  let ignored = db.create_or_ignore(&user("1", "b")).await.unwrap();
+  assert!(ignored.is_none());
+  let found = db.read_by_pk::<DbUser, _>("1").await.unwrap().unwrap();
+  assert_eq!(found.name, "a");
+  assert_eq!(count_users(&db), 1);
+}
+
+#[ntex::test]
+async fn read_for_update_locks_the_rows_until_the_commit() {
+  let Some(url) = pg_url("read_for_update_locks_the_rows_until_the_commit")
+  else {
+    return;
+  };
+  let db = DbDriver::<PgConnection>::new(&url).unwrap();
+  db.create(&user("1", "a")).await.unwrap();
+  db.create(&user("2", "b")).await.unwrap();
+  let filter =
+    GenericFilter::new().r#where("id", GenericClause::Eq("1".into()));
+  let locked = db
+    .transaction(move |conn| {
+      let locked = DbUser::read_for_update(conn, &filter)?;
+      // Another connection can't lock the same row, but can the other one
+      let mut other = PgConnection::establish(&url).unwrap();
+      let mut nowait = |id: &str| {
+        diesel::sql_query(format!(
+          "SELECT id FROM users WHERE id = '{id}' FOR UPDATE NOWAIT"
+        ))
+        .execute(&mut other)
+      };
+      assert!(nowait("1").is_err());
+      assert!(nowait("2").is_ok());
+      Ok(locked)
+    })
+    .await
+    .unwrap();
+  assert_eq!(locked.len(), 1);
+  assert_eq!(locked[0].id, "1");
+}
+
+#[ntex::test]
+async fn json_path_clauses_filter_the_nested_values() {
+  let Some(db) = pg("json_path_clauses_filter_the_nested_values") else {
+    return;
+  };
+  let profiles = [
+    (
+      "1",
+      serde_json::json!({ "profile": { "country": "FR", "age": 30 } }),
+    ),
+    ("2", serde_json::json!({ "profile": { "country": "DE" } })),
+    ("3", serde_json::json!({})),
+  ];
+  for (id, data) in profiles {
+    let id = id.to_owned();
+    db.create(&DbProfile { id, data }).await.unwrap();
+  }
+  let path = |keys: &[&str]| keys.iter().map(|&key| key.to_owned()).collect();
+  let ids = |filter: GenericFilter| {
+    let db = &db;
+    async move {
+      let rows = db.read_by::<DbProfile>(&filter).await.unwrap();
+      let mut ids = rows.into_iter().map(|row| row.id).collect::<Vec<_>>();
+      ids.sort();
+      ids
+    }
+  };
+  let country = path(&["profile", "country"]);
+  let eq = GenericClause::JsonPathEq(country, "FR".into());
+  assert_eq!(ids(GenericFilter::new().r#where("data", eq)).await, ["1"]);
+  let eq = GenericClause::JsonPathEq(path(&["profile", "age"]), 30.into());
+  assert_eq!(ids(GenericFilter::new().r#where("data", eq)).await, ["1"]);
+  let exists = GenericClause::JsonPathExists(path(&["profile"]));
+  let filter = GenericFilter::new().r#where("data", exists);
+  assert_eq!(ids(filter).await, ["1", "2"]);
+}
+
+#[ntex::test]
+async fn json_contains_and_contains_text_are_told_apart() {
+  let Some(db) = pg("json_contains_and_contains_text_are_told_apart") else {
+    return;
+  };
+  let profiles = [
+    (
+      "1",
+      serde_json::json!({ "country": "FR", "tags": ["a", "b"] }),
+    ),
+    ("2", serde_json::json!({ "country": "DE" })),
+  ];
+  for (id, data) in profiles {
+    let id = id.to_owned();
+    db.create(&DbProfile { id, data }).await.unwrap();
+  }
+  let ids = |column: &str, clause: GenericClause| {
+    let db = &db;
+    let filter = GenericFilter::new()
+      .r#where(column, clause)
+      .order_by("id", OrderDirection::Asc);
+    async move {
+      let rows = db.read_by::<DbProfile>(&filter).await.unwrap();
+      rows.into_iter().map(|row| row.id).collect::<Vec<_>>()
+    }
+  };
+  let contains =
+    GenericClause::JsonContains(serde_json::json!({ "tags": ["b"] }));
+  assert_eq!(ids("data", contains).await, ["1"]);
+  let contains =
+    GenericClause::JsonContains(serde_json::json!({ "country": "DE" }));
+  assert_eq!(ids("data", contains).await, ["2"]);
+  assert_eq!(
+    ids("data", GenericClause::HasKey("tags".into())).await,
+    ["1"]
+  );
+  // A substring of a text column, not a JSON document
+  db.create(&DbProfile {
+    id: "10".to_owned(),
+    data: serde_json::json!({}),
+  })
+  .await
+  .unwrap();
+  let text = GenericClause::ContainsText("0".to_owned());
+  assert_eq!(ids("id", text).await, ["10"]);
+  let text = GenericClause::ContainsText("FR".to_owned());
+  let filter = GenericFilter::new().r#where("data", text);
+  assert!(db.read_by::<DbProfile>(&filter).await.is_err());
+}
+
+#[ntex::test]
+async fn array_clauses_filter_the_array_columns() {
+  let Some(db) = pg("array_clauses_filter_the_array_columns") else {
+    return;
+  };
+  let articles = [
+    ("1", vec!["rust", "db"]),
+    ("2", vec!["rust", "web"]),
+    ("3", vec!["go"]),
+  ];
+  for (id, tags) in articles {
+    let id = id.to_owned();
+    let tags = tags.into_iter().map(str::to_owned).collect();
+    db.create(&DbArticle { id, tags }).await.unwrap();
+  }
+  let ids = |clause: GenericClause| {
+    let db = &db;
+    async move {
+      let filter = GenericFilter::new()
+        .r#where("tags", clause)
+        .order_by("id", OrderDirection::Asc);
+      let rows = db.read_by::<DbArticle>(&filter).await.unwrap();
+      rows.into_iter().map(|row| row.id).collect::<Vec<_>>()
+    }
+  };
+  let contains = |tags: &[&str]| {
+    GenericClause::ArrayContains(tags.iter().map(|&tag| tag.into()).collect())
+  };
+  let overlaps = |tags: &[&str]| {
+    GenericClause::ArrayOverlaps(tags.iter().map(|&tag| tag.into()).collect())
+  };
+  assert_eq!(ids(contains(&["rust"])).await, ["1", "2"]);
+  assert_eq!(ids(contains(&["rust", "web"])).await, ["2"]);
+  assert_eq!(ids(contains(&["db", "go"])).await, Vec::<String>::new());
+  assert_eq!(ids(overlaps(&["db", "go"])).await, ["1", "3"]);
+  assert_eq!(ids(overlaps(&["java"])).await, Vec::<String>::new());
+}
+
+#[ntex::test]
+async fn full_text_matches_the_relevant_rows() {
+  let Some(db) = pg("full_text_matches_the_relevant_rows") else {
+    return;
+  };
+  let corpus = [
+    ("1", "The cats are sleeping on the sofa"),
+    ("2", "A cat sleeps all day"),
+    ("3", "Dogs run in the park"),
+    ("4", "The quick brown fox jumps"),
+  ];
+  for (id, name) in corpus {
+    db.create(&user(id, name)).await.unwrap();
+  }
+  let ids = |query: &str, config: Option<&str>| {
+    let db = &db;
+    let clause =
+      GenericClause::FullText(query.to_owned(), config.map(str::to_owned));
+    async move {
+      let filter = GenericFilter::new()
+        .r#where("name", clause)
+        .order_by("id", OrderDirection::Asc);
+      let rows = db.read_by::<DbUser>(&filter).await.unwrap();
+      rows.into_iter().map(|row| row.id).collect::<Vec<_>>()
+    }
+  };
+  // The english configuration stems the words
+  assert_eq!(ids("sleeping cat", Some("english")).await, ["1", "2"]);
+  assert_eq!(ids("dog park", Some("english")).await, ["3"]);
+  assert_eq!(ids("cat dog", Some("english")).await, Vec::<String>::new());
+  // The simple configuration only lowers the case
+  assert_eq!(ids("cats", Some("simple")).await, ["1"]);
+  assert_eq!(ids("FOX", Some("simple")).await, ["4"]);
+  let clause = GenericClause::FullText("cat".to_owned(), Some("nope".into()));
+  let filter = GenericFilter::new().r#where("name", clause);
+  assert!(db.read_by::<DbUser>(&filter).await.is_err());
+}
+
+#[ntex::test]
+async fn racing_versioned_updates_let_only_one_through() {
+  let Some(db) = pg("racing_versioned_updates_let_only_one_through") else {
+    return;
+  };
+  let read = db.create(&document("1", "a")).await.unwrap();
+  // Both writers read version 0, only the first one to commit wins
+  let update = |body: &str| {
+    let changeset = DbDocumentUpdate {
+      body: Some(body.to_owned()),
+    };
+    db.update_versioned_by_pk::<DbDocument, _, _>("1", read.version, changeset)
+  };
+  let (first, second) = ntex::util::join(update("b"), update("c")).await;
+  let (winner, conflict) = match (first, second) {
+    (Ok(winner), conflict) | (conflict, Ok(winner)) => (winner, conflict),
+    (first, second) => panic!("no update went through: {first:?} {second:?}"),
+  };
+  assert!(
+    matches!(conflict, Err(TurboDieselError::Conflict)),
+    "{conflict:?}"
+  );
+  assert_eq!(winner.version, 1);
+  let found = db.read_by_pk::<DbDocument, _>("1").await.unwrap().unwrap();
+  assert_eq!((found.body, found.version), (winner.body, 1));
+}
+
+#[ntex::test]
+async fn raw_sql_binds_the_values_in_order() {
+  let Some(db) = pg("raw_sql_binds_the_values_in_order") else {
+    return;
+  };
+  db.create(&user("1", "a")).await.unwrap();
+  db.create(&user("2", "b")).await.unwrap();
+  let sql = "UPDATE users SET name = $1 WHERE id = $2";
+  let count = db
+    .execute_sql(sql, vec!["raw".into(), "1".into()])
+    .await
+    .unwrap();
+  assert_eq!(count, 1);
+  let sql = "SELECT COUNT(*) AS count FROM users WHERE name = $1 OR id = $2";
+  let binds = vec!["raw".into(), "2".into()];
+  let rows = db.query_sql::<NameCount>(sql, binds).await.unwrap();
+  assert_eq!(rows[0].count, 2);
+}
+
+#[ntex::test]
+async fn distinct_on_keeps_the_first_row_of_each_value() {
+  let Some(db) = pg("distinct_on_keeps_the_first_row_of_each_value") else {
+    return;
+  };
+  for (id, name) in [("1", "a"), ("2", "a"), ("3", "b"), ("4", "b")] {
+    db.create(&user(id, name)).await.unwrap();
+  }
+  let filter = GenericFilter::new()
+    .distinct_on("name")
+    .order_by("id", OrderDirection::Desc);
+  let users = db.read_by::<DbUser>(&filter).await.unwrap();
+  let ids = users
+    .iter()
+    .map(|user| user.id.as_str())
+    .collect::<Vec<_>>();
+  assert_eq!(ids, ["2", "4"]);
+}
+
+#[ntex::test]
+async fn read_by_into_maps_the_rows_into_the_struct() {
+  let Some(db) = pg("read_by_into_maps_the_rows_into_the_struct") else {
+    return;
+  };
+  db.create(&user("1", "a")).await.unwrap();
+  db.create(&user("2", "b")).await.unwrap();
+  let filter = GenericFilter::new().order_by("name", OrderDirection::Desc);
+  let names = db.read_by_into::<DbUser, UserName>(&filter).await.unwrap();
+  let names = names.into_iter().map(|row| row.name).collect::<Vec<_>>();
+  assert_eq!(names, ["b", "a"]);
+}
+
+#[ntex::test]
+async fn paginate_keyset_orders_by_keys_of_opposite_directions() {
+  let Some(db) = pg("paginate_keyset_orders_by_keys_of_opposite_directions")
+  else {
+    return;
+  };
+  for (id, name) in [("1", "c"), ("2", "a"), ("3", "b"), ("4", "a"), ("5", "b")]
+  {
+    db.create(&user(id, name)).await.unwrap();
+  }
+  let filter = GenericFilter::new()
+    .order_by("name", OrderDirection::Desc)
+    .order_by("id", OrderDirection::Asc);
+  let mut pages = Vec::new();
+  let mut after = None;
+  loop {
+    let page = db
+      .paginate_keyset::<DbUser>(&filter, after.take(), 2)
+      .await
+      .unwrap();
+    pages.push(
+      page
+        .items
+        .into_iter()
+        .map(|user| user.id)
+        .collect::<Vec<_>>(),
+    );
+    match page.next {
+      Some(next) => after = Some(next),
+      None => break,
+    }
+  }
+  assert_eq!(pages, [vec!["1", "3"], vec!["5", "2"], vec!["4"]]);
+}
+
+#[ntex::test]
+async fn with_schema_runs_the_operations_on_the_schema() {
+  use diesel::connection::SimpleConnection;
+
+  let Some(db) = pg("with_schema_runs_the_operations_on_the_schema") else {
+    return;
+  };
+  for tenant in ["a", "b"] {
+    let schema = format!("turbo_diesel_tenant_{tenant}");
+    db.get_conn()
+      .unwrap()
+      .batch_execute(&format!(
+        "DROP SCHEMA IF EXISTS {schema} CASCADE; CREATE SCHEMA {schema}; \
+         CREATE TABLE {schema}.users (id TEXT PRIMARY KEY, name TEXT NOT NULL)"
+      ))
+      .unwrap();
+  }
+  let tenant_a = db.with_schema("turbo_diesel_tenant_a");
+  let tenant_b = db.with_schema("turbo_diesel_tenant_b");
+  tenant_a.create(&user("1", "a")).await.unwrap();
+  tenant_b.create(&user("1", "b")).await.unwrap();
+  tenant_b.create(&user("2", "b")).await.unwrap();
+  let read = |db: DbDriver<PgConnection>| async move {
+    let filter = GenericFilter::new().order_by("id", OrderDirection::Asc);
+    let users = db.read_by::<DbUser>(&filter).await.unwrap();
+    users
+      .into_iter()
+      .map(|user| (user.id, user.name))
+      .collect::<Vec<_>>()
+  };
+  let pair = |id: &str, name: &str| (id.to_owned(), name.to_owned());
+  assert_eq!(read(tenant_a.clone()).await, [pair("1", "a")]);
+  assert_eq!(read(tenant_b).await, [pair("1", "b"), pair("2", "b")]);
+  // The connections go back to the pool on the schema of the driver
+  assert_eq!(count_users(&db), 0);
+  assert_eq!(read(db.clone()).await, []);
+  let count = tenant_a
+    .transaction(|conn| {
+      diesel::sql_query("SELECT count(*) AS count FROM users")
+        .get_result::<NameCount>(conn)
+    })
+    .await
+    .unwrap();
+  assert_eq!(count.count, 1);
+}
+
+#[ntex::test]
+async fn transaction_with_isolation_sets_the_level() {
+  #[derive(QueryableByName)]
+  struct Isolation {
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    transaction_isolation: String,
+  }
+
+  let Some(db) = pg("transaction_with_isolation_sets_the_level") else {
+    return;
+  };
+  let levels = [
+    (IsolationLevel::ReadCommitted, "read committed"),
+    (IsolationLevel::RepeatableRead, "repeatable read"),
+    (IsolationLevel::Serializable, "serializable"),
+  ];
+  for (level, expected) in levels {
+    let isolation = db
+      .transaction_with_isolation(level, |conn| {
+        diesel::sql_query("SHOW transaction_isolation")
+          .get_result::<Isolation>(conn)
+      })
+      .await
+      .unwrap();
+    assert_eq!(isolation.transaction_isolation, expected);
+  }
+}
+
+#[ntex::test]
+async fn aggregate_applies_the_function_on_the_matching_rows() {
+  let Some(db) = pg("aggregate_applies_the_function_on_the_matching_rows")
+  else {
+    return;
+  };
+  db.create_many(items(), None).await.unwrap();
+  let active =
+    GenericFilter::new().r#where("active", GenericClause::Eq(true.into()));
+  let sum = db
+    .aggregate::<DbItem, i64>(&active, AggregateFn::Sum, "quantity")
+    .await
+    .unwrap();
+  assert_eq!(sum, Some(4));
+  let avg = db
+    .aggregate::<DbItem, f64>(&active, AggregateFn::Avg, "quantity")
+    .await
+    .unwrap();
+  assert_eq!(avg, Some(2.0));
+  let max = db
+    .aggregate::<DbItem, String>(&active, AggregateFn::Max, "note")
+    .await
+    .unwrap();
+  assert_eq!(max.as_deref(), Some("first"));
+}
+
+#[ntex::test]
+async fn constraint_violations_name_the_constraint() {
+  let Some(db) = pg("constraint_violations_name_the_constraint") else {
+    return;
+  };
+  db.create(&user("1", "a")).await.unwrap();
+  let res = db.create(&user("1", "b")).await;
+  let Err(TurboDieselError::UniqueViolation(violation)) = res else {
+    panic!("expected a unique violation, got {res:?}");
+  };
+  assert_eq!(violation.constraint.as_deref(), Some("users_pkey"));
+  assert_eq!(violation.table.as_deref(), Some("users"));
+  let sql = "INSERT INTO items (id, quantity, price, active) \
+             VALUES (1, NULL, 1, true)";
+  let res = db.execute_sql(sql, Vec::new()).await;
+  assert!(
+    matches!(res, Err(TurboDieselError::NotNullViolation(_))),
+    "{res:?}"
+  );
+}
+
+#[ntex::test]
+async fn statement_timeout_cancels_a_long_query() {
+  let Some(url) = pg_url("statement_timeout_cancels_a_long_query") else {
+    return;
+  };
+  let config = PoolConfig {
+    max_size: Some(1),
+    statement_timeout: Some(std::time::Duration::from_millis(50)),
+    ..Default::default()
+  };
+  let db = DbDriver::<PgConnection>::new_with_config(&url, config).unwrap();
+  let res = db.execute_sql("SELECT pg_sleep(5)", Vec::new()).await;
+  assert!(matches!(res, Err(TurboDieselError::Timeout(_))), "{res:?}");
+  // The connection stays usable for the next queries
+  db.execute_sql("SELECT 1", Vec::new()).await.unwrap();
+}
+
+#[ntex::test]
+async fn find_or_create_creates_only_a_missing_row() {
+  let Some(db) = pg("find_or_create_creates_only_a_missing_row") else {
+    return;
+  };
+  let filter =
+    GenericFilter::new().r#where("name", GenericClause::Eq("a".into()));
+  let (row, created) =
+    db.find_or_create(&filter, user("1", "a")).await.unwrap();
+  assert_eq!((row.id.as_str(), created), ("1", true));
+  let (row, created) =
+    db.find_or_create(&filter, user("2", "a")).await.unwrap();
+  assert_eq!((row.id.as_str(), created), ("1", false));
+  assert_eq!(count_users(&db), 1);
+}
+
+#[ntex::test]
+async fn upsert_many_updates_the_existing_rows() {
+  let Some(db) = pg("upsert_many_updates_the_existing_rows") else {
+    return;
+  };
+  db.create(&user("1", "a")).await.unwrap();
+  db.create(&user("2", "b")).await.unwrap();
+  let items = vec![user("2", "updated"), user("3", "new")];
+  let upserted = db.upsert_many(items, Some(1)).await.unwrap();
+  assert_eq!(upserted.len(), 2);
+  let filter = GenericFilter::new().order_by("id", OrderDirection::Asc);
+  let users = db.read_by::<DbUser>(&filter).await.unwrap();
+  let names = users
+    .iter()
+    .map(|user| user.name.as_str())
+    .collect::<Vec<_>>();
+  assert_eq!(names, ["a", "updated", "new"]);
+  // More binds than one statement of the default chunk size holds
+  let items = (0..1000)
+    .map(|id| user(&format!("batch-{id}"), "batch"))
+    .collect::<Vec<_>>();
+  let upserted = db.upsert_many(items, None).await.unwrap();
+  assert_eq!(upserted.len(), 1000);
+  assert_eq!(count_users(&db), 1003);
+}
+
+#[ntex::test]
+async fn concurrent_increments_add_every_delta() {
+  let Some(db) = pg("concurrent_increments_add_every_delta") else {
+    return;
+  };
+  db.create(&document("1", "a")).await.unwrap();
+  let increments =
+    (0..50).map(|_| db.increment::<DbDocument, _>("1", "version", 2));
+  for res in ntex::util::join_all(increments).await {
+    res.unwrap();
+  }
+  let found = db.read_by_pk::<DbDocument, _>("1").await.unwrap().unwrap();
+  assert_eq!(found.version, 100);
+}
+
+#[ntex::test]
+async fn exists_related_keeps_the_rows_having_a_related_row() {
+  let Some(db) = pg("exists_related_keeps_the_rows_having_a_related_row")
+  else {
+    return;
+  };
+  for (id, name) in [("1", "a"), ("2", "b"), ("3", "c")] {
+    db.create(&user(id, name)).await.unwrap();
+  }
+  for (id, user_id) in [("o1", "1"), ("o2", "1"), ("o3", "3")] {
+    db.create(&order(id, user_id)).await.unwrap();
+  }
+  let orders = GenericClause::ExistsRelated("orders".into(), "user_id".into());
+  let filter = GenericFilter::new()
+    .r#where("id", orders)
+    .order_by("id", OrderDirection::Asc);
+  let ids = |users: Vec<DbUser>| {
+    users.into_iter().map(|user| user.id).collect::<Vec<_>>()
+  };
+  let users = db.read_by::<DbUser>(&filter).await.unwrap();
+  assert_eq!(ids(users), ["1", "3"]);
+  let users = db.read_by::<DbUser>(&!filter.clone()).await.unwrap();
+  assert_eq!(ids(users), ["2"]);
+  assert_eq!(db.del_by::<DbUser>(&filter).await.unwrap(), 2);
+  assert_eq!(count_users(&db), 1);
+}
+
+#[ntex::test]
+async fn group_count_counts_the_rows_per_status() {
+  let Some(db) = pg("group_count_counts_the_rows_per_status") else {
+    return;
+  };
+  let accounts = [
+    ("1", Some("active")),
+    ("2", Some("banned")),
+    ("3", Some("active")),
+    ("4", None),
+    ("5", Some("active")),
+    ("6", Some("banned")),
+  ];
+  for (id, status) in accounts {
+    db.create(&account(id, status)).await.unwrap();
+  }
+  let every = GenericFilter::new();
+  let groups = db.group_count::<DbAccount>(&every, "status").await.unwrap();
+  assert_eq!(
+    groups,
+    [
+      (GenericValue::Text("active".to_owned()), 3),
+      (GenericValue::Text("banned".to_owned()), 2),
+      (GenericValue::Null, 1),
+    ]
+  );
+  // The limit and the order of the filter don't cut the groups
+  let filter = GenericFilter::new()
+    .r#where("id", GenericClause::Ne("1".into()))
+    .order_by("id", OrderDirection::Asc)
+    .limit(1);
+  let groups = db
+    .group_count::<DbAccount>(&filter, "status")
+    .await
+    .unwrap();
+  assert_eq!(
+    groups,
+    [
+      (GenericValue::Text("active".to_owned()), 2),
+      (GenericValue::Text("banned".to_owned()), 2),
+      (GenericValue::Null, 1),
+    ]
+  );
+  let distinct = db.count_distinct::<DbAccount>(&every, "status").await;
+  assert_eq!(distinct.unwrap(), 2);
+  let filter =
+    GenericFilter::new().r#where("id", GenericClause::Gt("4".into()));
+  let distinct = db.count_distinct::<DbAccount>(&filter, "status").await;
+  assert_eq!(distinct.unwrap(), 2);
+  let res = db.group_count::<DbAccount>(&every, "unknown").await;
+  assert!(matches!(
+    res,
+    Err(TurboDieselError::Query(Error::QueryBuilderError(_)))
+  ));
+}
+
+#[ntex::test]
+async fn a_missing_row_is_none_or_not_found() {
+  let Some(db) = pg("a_missing_row_is_none_or_not_found") else {
+    return;
+  };
+  db.create(&user("1", "a")).await.unwrap();
+  let found = db.read_by_pk_strict::<DbUser, _>("1").await.unwrap();
+  assert_eq!(found.name, "a");
+  assert!(db.read_by_pk::<DbUser, _>("2").await.unwrap().is_none());
+  let res = db.read_by_pk_strict::<DbUser, _>("2").await;
+  assert!(matches!(res, Err(TurboDieselError::NotFound)), "{res:?}");
+  assert_eq!(db.del_by_pk::<DbUser, _>("2").await.unwrap(), 0);
+  let res = db.del_by_pk_strict::<DbUser, _>("2").await;
+  assert!(matches!(res, Err(TurboDieselError::NotFound)), "{res:?}");
+}
+
+#[ntex::test]
+async fn count_by_column_skips_the_null_values() {
+  let Some(db) = pg("count_by_column_skips_the_null_values") else {
+    return;
+  };
+  db.create_many(items(), None).await.unwrap();
+  let every = GenericFilter::new();
+  assert_eq!(db.count_by::<DbItem>(&every).await.unwrap(), 3);
+  let count = db.count_by_column::<DbItem>(&every, "note").await;
+  assert_eq!(count.unwrap(), 1);
+  let count = db.count_by_column::<DbItem>(&every, "id").await;
+  assert_eq!(count.unwrap(), 3);
+  let filter = GenericFilter::new().r#where("id", GenericClause::Gt(1.into()));
+  let count = db.count_by_column::<DbItem>(&filter, "note").await;
+  assert_eq!(count.unwrap(), 0);
+  let res = db.count_by_column::<DbItem>(&every, "unknown").await;
+  assert!(matches!(
+    res,
+    Err(TurboDieselError::Query(Error::QueryBuilderError(_)))
+  ));
+}
+
+#[ntex::test]
+async fn set_field_archives_the_matched_rows() {
+  let Some(db) = pg("set_field_archives_the_matched_rows") else {
+    return;
+  };
+  let accounts = [
+    ("1", Some("active")),
+    ("2", Some("banned")),
+    ("3", None),
+    ("4", Some("banned")),
+  ];
+  for (id, status) in accounts {
+    db.create(&account(id, status)).await.unwrap();
+  }
+  let banned =
+    GenericFilter::new().r#where("status", GenericClause::Eq("banned".into()));
+  let archived = db
+    .set_field::<DbAccount>(&banned, "status", "archived".into())
+    .await
+    .unwrap();
+  assert_eq!(archived, 2);
+  let every = GenericFilter::new().order_by("id", OrderDirection::Asc);
+  let statuses = db
+    .read_by::<DbAccount>(&every)
+    .await
+    .unwrap()
+    .into_iter()
+    .map(|account| account.status)
+    .collect::<Vec<_>>();
+  assert_eq!(
+    statuses,
+    [
+      Some("active".to_owned()),
+      Some("archived".to_owned()),
+      None,
+      Some("archived".to_owned()),
+    ]
+  );
+  let filter =
+    GenericFilter::new().r#where("id", GenericClause::Eq("1".into()));
+  let cleared = db
+    .set_field::<DbAccount>(&filter, "status", GenericValue::Null)
+    .await
+    .unwrap();
+  assert_eq!(cleared, 1);
+  let account = db.read_by_pk::<DbAccount, _>("1").await.unwrap().unwrap();
+  assert_eq!(account.status, None);
+  // Refused without a where clause, the value is checked against the column
+  let res = db
+    .set_field::<DbAccount>(&GenericFilter::new(), "status", "x".into())
+    .await;
+  assert!(res.is_err());
+  db.create_many(items(), None).await.unwrap();
+  let filter = GenericFilter::new().r#where("id", GenericClause::Gt(1.into()));
+  let res = db
+    .set_field::<DbItem>(&filter, "quantity", "many".into())
+    .await;
+  assert!(matches!(
+    res,
+    Err(TurboDieselError::Query(Error::QueryBuilderError(_)))
+  ));
+  let res = db.set_field::<DbItem>(&filter, "unknown", 1.into()).await;
+  assert!(matches!(
+    res,
+    Err(TurboDieselError::Query(Error::QueryBuilderError(_)))
+  ));
+  let updated = db
+    .set_field::<DbItem>(&filter, "quantity", "7".into())
+    .await;
+  assert_eq!(updated.unwrap(), 2);
+  let item = db.read_by_pk::<DbItem, _>(&3).await.unwrap().unwrap();
+  assert_eq!(item.quantity, 7);
+  assert_eq!(db.count_by::<DbAccount>(&banned).await.unwrap(), 0);
+}
+
+#[ntex::test]
+async fn del_by_returning_returns_the_deleted_rows() {
+  let Some(db) = pg("del_by_returning_returns_the_deleted_rows") else {
+    return;
+  };
+  for (id, name) in [("1", "ab"), ("2", "b"), ("3", "ac")] {
+    db.create(&user(id, name)).await.unwrap();
+  }
+  let filter =
+    GenericFilter::new().r#where("name", GenericClause::StartsWith("a".into()));
+  let existing = db.read_by::<DbUser>(&filter).await.unwrap();
+  let mut removed = db.del_by_returning::<DbUser>(&filter).await.unwrap();
+  removed.sort_by(|a, b| a.id.cmp(&b.id));
+  let rows = |users: &[DbUser]| {
+    users
+      .iter()
+      .map(|user| (user.id.clone(), user.name.clone()))
+      .collect::<Vec<_>>()
+  };
+  assert_eq!(rows(&removed), rows(&existing));
+  assert_eq!(
+    rows(&removed),
+    [
+      ("1".to_owned(), "ab".to_owned()),
+      ("3".to_owned(), "ac".to_owned())
+    ]
+  );
+  assert!(db
+    .del_by_returning::<DbUser>(&filter)
+    .await
+    .unwrap()
+    .is_empty());
+  for filter in [GenericFilter::new(), !GenericFilter::new()] {
+    let res = db.del_by_returning::<DbUser>(&filter).await;
+    assert!(matches!(
+      res,
+      Err(TurboDieselError::Query(Error::QueryBuilderError(_)))
+    ));
+  }
+  assert_eq!(count_users(&db), 1);
+}
+
+#[ntex::test]
+async fn update_by_returning_returns_the_updated_rows() {
+  let Some(db) = pg("update_by_returning_returns_the_updated_rows") else {
+    return;
+  };
+  for (id, name) in [("1", "ab"), ("2", "b"), ("3", "ac")] {
+    db.create(&user(id, name)).await.unwrap();
+  }
+  let filter =
+    GenericFilter::new().r#where("name", GenericClause::StartsWith("a".into()));
+  let changeset = DbUserUpdate {
+    name: Some("renamed".to_owned()),
+  };
+  let mut updated = db
+    .update_by_returning::<DbUser, _>(&filter, changeset.clone())
+    .await
+    .unwrap();
+  updated.sort_by(|a, b| a.id.cmp(&b.id));
+  let rows = updated
+    .iter()
+    .map(|user| (user.id.as_str(), user.name.as_str()))
+    .collect::<Vec<_>>();
+  assert_eq!(rows, [("1", "renamed"), ("3", "renamed")]);
+  let untouched = db.read_by_pk::<DbUser, _>("2").await.unwrap().unwrap();
+  assert_eq!(untouched.name, "b");
+  let updated = db
+    .update_by_returning::<DbUser, _>(&filter, changeset.clone())
+    .await
+    .unwrap();
+  assert!(updated.is_empty());
+  let res = db
+    .update_by_returning::<DbUser, _>(&GenericFilter::new(), changeset)
+    .await;
+  assert!(matches!(
+    res,
+    Err(TurboDieselError::Query(Error::QueryBuilderError(_)))
+  ));
+}
+
+#[ntex::test]
+async fn read_scalar_reads_a_single_column_value() {
+  use diesel::sql_types::{Integer, Nullable, Text};
+
+  let Some(db) = pg("read_scalar_reads_a_single_column_value") else {
+    return;
+  };
+  db.create(&user("1", "a")).await.unwrap();
+  db.create(&user("2", "b")).await.unwrap();
+  let by_pk = GenericFilter::new().r#where("id", GenericClause::Eq("2".into()));
+  let name = db.read_scalar::<DbUser, String, Text>(&by_pk, "name").await;
+  assert_eq!(name.unwrap().as_deref(), Some("b"));
+  let missing =
+    GenericFilter::new().r#where("id", GenericClause::Eq("3".into()));
+  let name = db
+    .read_scalar::<DbUser, String, Text>(&missing, "name")
+    .await;
+  assert_eq!(name.unwrap(), None);
+  // The first row in the order of the filter
+  let last = GenericFilter::new().order_by("name", OrderDirection::Desc);
+  let id = db.read_scalar::<DbUser, String, Text>(&last, "id").await;
+  assert_eq!(id.unwrap().as_deref(), Some("2"));
+  db.create_many(items(), None).await.unwrap();
+  let by_pk = GenericFilter::new().r#where("id", GenericClause::Eq(2.into()));
+  let quantity = db.read_scalar::<DbItem, i32, Integer>(&by_pk, "quantity");
+  assert_eq!(quantity.await.unwrap(), Some(2));
+  let note = db
+    .read_scalar::<DbItem, Option<String>, Nullable<Text>>(&by_pk, "note")
+    .await;
+  assert_eq!(note.unwrap(), Some(None));
+  let res = db
+    .read_scalar::<DbUser, String, Text>(&missing, "unknown")
+    .await;
+  assert!(matches!(
+    res,
+    Err(TurboDieselError::Query(Error::QueryBuilderError(_)))
+  ));
+}
+
+#[ntex::test]
+async fn newtype_primary_keys_are_given_as_is() {
+  let Some(db) = pg("newtype_primary_keys_are_given_as_is") else {
+    return;
+  };
+  let id = CustomerId(uuid::Uuid::from_u128(1));
+  let customer = DbCustomer {
+    id,
+    name: "a".to_owned(),
+  };
+  db.create(&customer).await.unwrap();
+  let found = db.read_by_pk::<DbCustomer, _>(&id).await.unwrap().unwrap();
+  assert_eq!((found.id, found.name.as_str()), (id, "a"));
+  let missing = CustomerId(uuid::Uuid::from_u128(2));
+  let res = db.read_by_pk_strict::<DbCustomer, _>(&missing).await;
+  assert!(matches!(res, Err(TurboDieselError::NotFound)), "{res:?}");
+  assert_eq!(db.del_by_pk::<DbCustomer, _>(&missing).await.unwrap(), 0);
+  assert_eq!(db.del_by_pk::<DbCustomer, _>(&id).await.unwrap(), 1);
+  assert!(db.read_by_pk::<DbCustomer, _>(&id).await.unwrap().is_none());
+}
+
+#[cfg(feature = "uuid")]
+#[ntex::test]
+async fn uuid_primary_keys_work_with_every_pk_method() {
+  let Some(db) = pg("uuid_primary_keys_work_with_every_pk_method") else {
+    return;
+  };
+  let id = uuid::Uuid::from_u128(1);
+  assert_eq!(db.create(&ticket(id)).await.unwrap().id, id);
+  let found = db.read_by_pk_strict::<DbTicket, _>(&id).await.unwrap();
+  assert_eq!(found.title, "open");
+  let filter = GenericFilter::new()
+    .r#where("id", GenericClause::Eq(id.to_string().into()));
+  assert_eq!(db.count_by::<DbTicket>(&filter).await.unwrap(), 1);
+  let filter =
+    GenericFilter::new().r#where("id", GenericClause::Eq("not a uuid".into()));
+  let res = db.count_by::<DbTicket>(&filter).await;
+  assert!(
+    matches!(
+      res,
+      Err(TurboDieselError::Query(Error::QueryBuilderError(_)))
+    ),
+    "{res:?}"
+  );
+  let changeset = DbTicketUpdate {
+    title: Some("closed".to_owned()),
+  };
+  let updated = db
+    .update_by_pk::<DbTicket, _, _>(&id, changeset)
+    .await
+    .unwrap();
+  assert_eq!(updated.title, "closed");
+  let incremented = db
+    .increment::<DbTicket, _>(&id, "priority", 2)
+    .await
+    .unwrap();
+  assert_eq!(incremented.priority, 2);
+  assert_eq!(db.del_by_pk::<DbTicket, _>(&id).await.unwrap(), 1);
+  assert!(db.read_by_pk::<DbTicket, _>(&id).await.unwrap().is_none());
+  let res = db.del_by_pk_strict::<DbTicket, _>(&id).await;
+  assert!(matches!(res, Err(TurboDieselError::NotFound)), "{res:?}");
+  let ids = [uuid::Uuid::from_u128(2), uuid::Uuid::from_u128(3)];
+  for id in ids {
+    db.create(&ticket(id)).await.unwrap();
+  }
+  db.del_by_pk_strict::<DbTicket, _>(&ids[0]).await.unwrap();
+  assert_eq!(db.del_by_pks::<DbTicket, _>(&ids).await.unwrap(), 1);
+}
+
+#[ntex::test]
+async fn test_on_check_out_replaces_a_terminated_connection() {
+  #[derive(QueryableByName)]
+  struct BackendPid {
+    #[diesel(sql_type = diesel::sql_types::Integer)]
+    pid: i32,
+  }
+
+  let Some(url) = pg_url("test_on_check_out_replaces_a_terminated_connection")
+  else {
+    return;
+  };
+  let admin = DbDriver::<PgConnection>::new(&url).unwrap();
+  // Terminates the only connection of the pool, waiting for it to be gone
+  let terminate = |single: DbDriver<PgConnection>| {
+    let admin = admin.clone();
+    async move {
+      let pid = single
+        .query_sql::<BackendPid>("SELECT pg_backend_pid() AS pid", vec![])
+        .await
+        .unwrap();
+      admin
+        .execute_sql(
+          "SELECT pg_terminate_backend(CAST($1 AS INTEGER), 5000)",
+          vec![pid[0].pid.into()],
+        )
+        .await
+        .unwrap();
+      single
+    }
+  };
+  for (test_on_check_out, replaced) in [(None, true), (Some(false), false)] {
+    let config = PoolConfig {
+      max_size: Some(1),
+      test_on_check_out,
+      ..Default::default()
+    };
+    let single = DbDriver::<PgConnection>::new_with_config(&url, config);
+    let single = terminate(single.unwrap()).await;
+    let res = single.read_by::<DbUser>(&GenericFilter::new()).await;
+    assert_eq!(res.is_ok(), replaced, "{test_on_check_out:?} {res:?}");
+  }
+}
+
+#[ntex::test]
+async fn execute_cancellable_cancels_a_long_query() {
+  let Some(url) = pg_url("execute_cancellable_cancels_a_long_query") else {
+    return;
+  };
+  let config = PoolConfig {
+    max_size: Some(2),
+    ..Default::default()
+  };
+  let db = DbDriver::<PgConnection>::new_with_config(&url, config).unwrap();
+  // The cancel request goes through an idle connection
+  db.warmup(2).await.unwrap();
+  let sleep = |conn: &mut _| {
+    diesel::sql_query("SELECT pg_sleep(10)").execute(conn)?;
+    Ok(())
+  };
+  let start = std::time::Instant::now();
+  let timeout = ntex::time::sleep(std::time::Duration::from_millis(100));
+  let res = db.execute_cancellable(timeout, sleep).await;
+  assert!(matches!(res, Err(TurboDieselError::Cancelled)), "{res:?}");
+  assert!(start.elapsed().as_secs() < 2, "{:?}", start.elapsed());
+  // The statement is cancelled on the server, both connections come back
+  while db.pool_state().idle_connections < 2 {
+    assert!(start.elapsed().as_secs() < 5, "{:?}", db.pool_state());
+    ntex::time::sleep(std::time::Duration::from_millis(10)).await;
+  }
+}
+
+#[ntex::test]
+async fn execute_cancellable_returns_at_once_on_a_saturated_pool() {
+  let Some(url) =
+    pg_url("execute_cancellable_returns_at_once_on_a_saturated_pool")
+  else {
+    return;
+  };
+  let config = PoolConfig {
+    max_size: Some(1),
+    ..Default::default()
+  };
+  let db = DbDriver::<PgConnection>::new_with_config(&url, config).unwrap();
+  let sleep = |conn: &mut _| {
+    diesel::sql_query("SELECT pg_sleep(1)").execute(conn)?;
+    Ok(())
+  };
+  let start = std::time::Instant::now();
+  let timeout = ntex::time::sleep(std::time::Duration::from_millis(100));
+  let res = db.execute_cancellable(timeout, sleep).await;
+  assert!(matches!(res, Err(TurboDieselError::Cancelled)), "{res:?}");
+  assert!(start.elapsed().as_millis() < 900, "{:?}", start.elapsed());
+  // No connection to cancel it from, the statement runs to the end
+  db.execute_sql("SELECT 1", Vec::new()).await.unwrap();
+  assert!(start.elapsed().as_millis() >= 1000, "{:?}", start.elapsed());
+}
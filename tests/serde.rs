@@ -0,0 +1,263 @@
+//! The JSON of the filters.
+#![cfg(feature = "serde")]
+
+use turbo_diesel::prelude::*;
+
+#[test]
+fn between_round_trips() {
+  let clause = GenericClause::Between("1".into(), "10".into());
+  let json = serde_json::to_string(&clause).unwrap();
+  assert_eq!(json, r#"{"between":["1","10"]}"#);
+  let clause = serde_json::from_str::<GenericClause>(&json).unwrap();
+  assert!(
+    matches!(clause, GenericClause::Between(lower, upper) if lower == "1".into() && upper == "10".into())
+  );
+  let json = r#"{"not-between":["1","10"]}"#;
+  let clause = serde_json::from_str::<GenericClause>(json).unwrap();
+  assert!(matches!(clause, GenericClause::NotBetween(..)));
+}
+
+#[test]
+fn where_reads_the_list_and_the_object_forms() {
+  let json = r#"{"where":[["age",{"gt":"18"}],["age",{"lt":"65"}]]}"#;
+  let filter = serde_json::from_str::<GenericFilter>(json).unwrap();
+  let r#where = filter.r#where.unwrap();
+  assert_eq!(r#where.len(), 2);
+  assert!(
+    matches!(&r#where[0], (column, GenericClause::Gt(age)) if column == "age" && *age == "18".into())
+  );
+  assert!(
+    matches!(&r#where[1], (column, GenericClause::Lt(age)) if column == "age" && *age == "65".into())
+  );
+  let json = r#"{"where":{"name":{"eq":"a"}}}"#;
+  let filter = serde_json::from_str::<GenericFilter>(json).unwrap();
+  let r#where = filter.r#where.unwrap();
+  assert!(
+    matches!(&r#where[..], [(column, GenericClause::Eq(name))] if column == "name" && *name == "a".into())
+  );
+  let filter = serde_json::from_str::<GenericFilter>("{}").unwrap();
+  assert!(filter.r#where.is_none());
+}
+
+#[test]
+fn values_keep_their_json_type() {
+  let json = r#"[{"eq":18},{"eq":"18"},{"gt":1.5},{"eq":true},{"eq":null}]"#;
+  let clauses = serde_json::from_str::<Vec<GenericClause>>(json).unwrap();
+  let values = clauses
+    .into_iter()
+    .map(|clause| match clause {
+      GenericClause::Eq(value) | GenericClause::Gt(value) => value,
+      clause => panic!("{clause:?}"),
+    })
+    .collect::<Vec<_>>();
+  assert_eq!(
+    values,
+    [
+      GenericValue::Int(18),
+      GenericValue::Text("18".to_owned()),
+      GenericValue::Float(1.5),
+      GenericValue::Bool(true),
+      GenericValue::Null,
+    ]
+  );
+}
+
+#[test]
+fn where_object_keeps_the_document_order() {
+  let json = r#"{"where":{"name":{"eq":"a"},"id":{"ne":"1"}}}"#;
+  let filter = serde_json::from_str::<GenericFilter>(json).unwrap();
+  let columns = filter
+    .r#where
+    .unwrap()
+    .into_iter()
+    .map(|(column, _)| column)
+    .collect::<Vec<_>>();
+  assert_eq!(columns, ["name", "id"]);
+}
+
+#[test]
+fn filter_round_trips() {
+  let filter = GenericFilter::new()
+    .r#where("name", GenericClause::StartsWith("te".to_owned()))
+    .r#where("id", GenericClause::In(vec!["1".into(), "2".into()]))
+    .r#where("age", GenericClause::Between(1.into(), 10.into()))
+    .r#where("note", GenericClause::IsNotNull)
+    .r#where(
+      "data",
+      GenericClause::JsonContains(serde_json::json!({"a": 1})),
+    )
+    .r#where("body", GenericClause::FullText("rust".to_owned(), None))
+    .where_group(!FilterGroup::Or(vec![
+      FilterGroup::leaf("id", GenericClause::Eq("3".into())),
+      FilterGroup::leaf("name", GenericClause::Like("%b".to_owned())),
+    ]))
+    .limit(10)
+    .offset(20)
+    .order_by("name", OrderDirection::Desc);
+  let json = serde_json::to_value(&filter).unwrap();
+  assert_eq!(
+    json["where"][0],
+    serde_json::json!(["name", {"starts-with": "te"}])
+  );
+  assert_eq!(
+    json["where"][1],
+    serde_json::json!(["id", {"in": ["1", "2"]}])
+  );
+  assert_eq!(
+    json["where"][2],
+    serde_json::json!(["age", {"between": [1, 10]}])
+  );
+  assert_eq!(json["where"][3], serde_json::json!(["note", "is-not-null"]));
+  let back = serde_json::from_value::<GenericFilter>(json.clone()).unwrap();
+  assert_eq!(serde_json::to_value(&back).unwrap(), json);
+  assert_eq!((back.limit, back.offset), (Some(10), Some(20)));
+  assert!(matches!(
+    back.r#where.as_deref(),
+    Some([
+      (_, GenericClause::StartsWith(_)),
+      (_, GenericClause::In(_)),
+      (
+        _,
+        GenericClause::Between(GenericValue::Int(1), GenericValue::Int(10))
+      ),
+      (_, GenericClause::IsNotNull),
+      (_, GenericClause::JsonContains(_)),
+      (_, GenericClause::FullText(_, None)),
+    ])
+  ));
+  assert!(matches!(
+    back.groups.as_deref(),
+    Some([FilterGroup::Not(group)]) if matches!(**group, FilterGroup::Or(_))
+  ));
+}
+
+/// The name of the variant, without a wildcard arm so a new variant
+/// fails to build until it's added to `every_clause_has_a_fixed_encoding`.
+fn variant(clause: &GenericClause) -> &'static str {
+  match clause {
+    GenericClause::Eq(_) => "Eq",
+    GenericClause::Ne(_) => "Ne",
+    GenericClause::Gt(_) => "Gt",
+    GenericClause::Lt(_) => "Lt",
+    GenericClause::Ge(_) => "Ge",
+    GenericClause::Le(_) => "Le",
+    GenericClause::Like(_) => "Like",
+    GenericClause::NotLike(_) => "NotLike",
+    GenericClause::ILike(_) => "ILike",
+    GenericClause::StartsWith(_) => "StartsWith",
+    GenericClause::EndsWith(_) => "EndsWith",
+    GenericClause::ContainsText(_) => "ContainsText",
+    GenericClause::Between(..) => "Between",
+    GenericClause::NotBetween(..) => "NotBetween",
+    GenericClause::In(_) => "In",
+    GenericClause::NotIn(_) => "NotIn",
+    GenericClause::IsNull => "IsNull",
+    GenericClause::IsNotNull => "IsNotNull",
+    GenericClause::JsonContains(_) => "JsonContains",
+    GenericClause::HasKey(_) => "HasKey",
+    GenericClause::JsonPathEq(..) => "JsonPathEq",
+    GenericClause::JsonPathExists(_) => "JsonPathExists",
+    GenericClause::ArrayContains(_) => "ArrayContains",
+    GenericClause::ArrayOverlaps(_) => "ArrayOverlaps",
+    GenericClause::FullText(..) => "FullText",
+    GenericClause::ExistsRelated(..) => "ExistsRelated",
+  }
+}
+
+#[test]
+fn every_clause_has_a_fixed_encoding() {
+  use serde_json::json;
+
+  let encodings = [
+    (GenericClause::Eq(1.into()), json!({"eq": 1})),
+    (GenericClause::Ne(GenericValue::Null), json!({"ne": null})),
+    (GenericClause::Gt(1.5.into()), json!({"gt": 1.5})),
+    (GenericClause::Lt("b".into()), json!({"lt": "b"})),
+    (GenericClause::Ge(true.into()), json!({"ge": true})),
+    (
+      GenericClause::Le(json!([1, 2]).into()),
+      json!({"le": [1, 2]}),
+    ),
+    (GenericClause::Like("a%".into()), json!({"like": "a%"})),
+    (
+      GenericClause::NotLike("a%".into()),
+      json!({"not-like": "a%"}),
+    ),
+    (GenericClause::ILike("a%".into()), json!({"i-like": "a%"})),
+    (
+      GenericClause::StartsWith("a".into()),
+      json!({"starts-with": "a"}),
+    ),
+    (
+      GenericClause::EndsWith("a".into()),
+      json!({"ends-with": "a"}),
+    ),
+    (
+      GenericClause::ContainsText("a".into()),
+      json!({"contains-text": "a"}),
+    ),
+    (
+      GenericClause::Between(1.into(), 10.into()),
+      json!({"between": [1, 10]}),
+    ),
+    (
+      GenericClause::NotBetween("a".into(), "b".into()),
+      json!({"not-between": ["a", "b"]}),
+    ),
+    (
+      GenericClause::In(vec![1.into(), "2".into()]),
+      json!({"in": [1, "2"]}),
+    ),
+    (GenericClause::NotIn(vec![]), json!({"not-in": []})),
+    (GenericClause::IsNull, json!("is-null")),
+    (GenericClause::IsNotNull, json!("is-not-null")),
+    (
+      GenericClause::JsonContains(json!({"a": 1})),
+      json!({"json-contains": {"a": 1}}),
+    ),
+    (GenericClause::HasKey("a".into()), json!({"has-key": "a"})),
+    (
+      GenericClause::JsonPathEq(vec!["a".into(), "b".into()], json!("x")),
+      json!({"json-path-eq": [["a", "b"], "x"]}),
+    ),
+    (
+      GenericClause::JsonPathExists(vec!["a".into()]),
+      json!({"json-path-exists": ["a"]}),
+    ),
+    (
+      GenericClause::ArrayContains(vec![1.into()]),
+      json!({"array-contains": [1]}),
+    ),
+    (
+      GenericClause::ArrayOverlaps(vec!["a".into()]),
+      json!({"array-overlaps": ["a"]}),
+    ),
+    (
+      GenericClause::FullText("rust".into(), Some("english".into())),
+      json!({"full-text": ["rust", "english"]}),
+    ),
+    (
+      GenericClause::FullText("rust".into(), None),
+      json!({"full-text": ["rust", null]}),
+    ),
+  ];
+  let mut covered = std::collections::BTreeSet::new();
+  for (clause, encoded) in encodings {
+    covered.insert(variant(&clause));
+    assert_eq!(serde_json::to_value(&clause).unwrap(), encoded);
+    let decoded = serde_json::from_value::<GenericClause>(encoded).unwrap();
+    assert_eq!(decoded, clause);
+  }
+  // The names of the related tables must come from the code
+  let related = GenericClause::ExistsRelated("orders".into(), "user_id".into());
+  covered.insert(variant(&related));
+  assert!(serde_json::to_value(&related).is_err());
+  let json = json!({"exists-related": ["orders", "user_id"]});
+  assert!(serde_json::from_value::<GenericClause>(json).is_err());
+  assert_eq!(covered.len(), 26);
+  // A JSON null is always `Null`
+  let clause = GenericClause::Eq(GenericValue::Json(serde_json::Value::Null));
+  let json = serde_json::to_value(&clause).unwrap();
+  let decoded = serde_json::from_value::<GenericClause>(json).unwrap();
+  assert_eq!(decoded, GenericClause::Eq(GenericValue::Null));
+}
@@ -0,0 +1,674 @@
+//! The SQL built from the filters, without a database.
+
+mod common;
+
+use common::*;
+use diesel::pg::Pg;
+use diesel::sqlite::Sqlite;
+use turbo_diesel::prelude::*;
+
+#[test]
+fn between_renders_between() {
+  let filter = GenericFilter::new()
+    .r#where("name", GenericClause::Between("a".into(), "c".into()));
+  let query = DbUser::gen_read_query::<SqliteConnection>(&filter).unwrap();
+  assert_eq!(
+    diesel::debug_query::<Sqlite, _>(&query).to_string(),
+    "SELECT `users`.`id`, `users`.`name` FROM `users` \
+     WHERE (`users`.`name` BETWEEN ? AND ?) -- binds: [\"a\", \"c\"]"
+  );
+  let filter = GenericFilter::new()
+    .r#where("name", GenericClause::NotBetween("a".into(), "c".into()));
+  let query = DbUser::gen_read_query::<PgConnection>(&filter).unwrap();
+  assert_eq!(
+    diesel::debug_query::<Pg, _>(&query).to_string(),
+    "SELECT \"users\".\"id\", \"users\".\"name\" FROM \"users\" \
+     WHERE (\"users\".\"name\" NOT BETWEEN $1 AND $2) -- binds: [\"a\", \"c\"]"
+  );
+}
+
+#[test]
+fn groups_are_parenthesized() {
+  let filter = GenericFilter::new()
+    .where_group(FilterGroup::Or(vec![
+      FilterGroup::leaf("id", GenericClause::Eq("1".into())),
+      FilterGroup::And(vec![
+        FilterGroup::leaf("id", GenericClause::Eq("2".into())),
+        FilterGroup::leaf("name", GenericClause::Eq("b".into())),
+      ]),
+    ]))
+    .r#where("name", GenericClause::Ne("c".into()));
+  let query = DbUser::gen_read_query::<SqliteConnection>(&filter).unwrap();
+  assert_eq!(
+    diesel::debug_query::<Sqlite, _>(&query).to_string(),
+    "SELECT `users`.`id`, `users`.`name` FROM `users` \
+     WHERE ((`users`.`name` != ?) AND ((`users`.`id` = ?) \
+     OR ((`users`.`id` = ?) AND (`users`.`name` = ?)))) \
+     -- binds: [\"c\", \"1\", \"2\", \"b\"]"
+  );
+}
+
+#[test]
+fn not_wraps_the_group_in_parentheses() {
+  let filter = !GenericFilter::new()
+    .r#where("id", GenericClause::Eq("1".into()))
+    .r#where("name", GenericClause::Eq("b".into()));
+  let query = DbUser::gen_read_query::<SqliteConnection>(&filter).unwrap();
+  assert_eq!(
+    diesel::debug_query::<Sqlite, _>(&query).to_string(),
+    "SELECT `users`.`id`, `users`.`name` FROM `users` \
+     WHERE  NOT (((`users`.`id` = ?) AND (`users`.`name` = ?))) \
+     -- binds: [\"1\", \"b\"]"
+  );
+  let filter = GenericFilter::new().where_group(FilterGroup::Or(vec![
+    !FilterGroup::leaf("id", GenericClause::Eq("1".into())),
+    !FilterGroup::Or(vec![
+      FilterGroup::leaf("id", GenericClause::Eq("2".into())),
+      FilterGroup::leaf("name", GenericClause::Eq("b".into())),
+    ]),
+  ]));
+  let query = DbUser::gen_read_query::<PgConnection>(&filter).unwrap();
+  assert_eq!(
+    diesel::debug_query::<Pg, _>(&query).to_string(),
+    "SELECT \"users\".\"id\", \"users\".\"name\" FROM \"users\" \
+     WHERE ( NOT ((\"users\".\"id\" = $1)) \
+     OR  NOT (((\"users\".\"id\" = $2) OR (\"users\".\"name\" = $3)))) \
+     -- binds: [\"1\", \"2\", \"b\"]"
+  );
+}
+
+#[test]
+fn empty_groups_match_every_row_or_none() {
+  let filter = GenericFilter::new()
+    .where_group(FilterGroup::And(vec![]))
+    .where_group(FilterGroup::Or(vec![]));
+  let query = DbUser::gen_read_query::<PgConnection>(&filter).unwrap();
+  assert_eq!(
+    diesel::debug_query::<Pg, _>(&query).to_string(),
+    "SELECT \"users\".\"id\", \"users\".\"name\" FROM \"users\" \
+     WHERE (1 = 1 AND 1 = 0) -- binds: []"
+  );
+}
+
+#[test]
+fn ilike_renders_ilike_on_pg_and_lower_on_sqlite() {
+  let filter = GenericFilter::new()
+    .r#where("name", GenericClause::ILike("%Bo%".to_owned()));
+  let query = DbUser::gen_read_query::<PgConnection>(&filter).unwrap();
+  assert_eq!(
+    diesel::debug_query::<Pg, _>(&query).to_string(),
+    "SELECT \"users\".\"id\", \"users\".\"name\" FROM \"users\" \
+     WHERE (\"users\".\"name\" ILIKE $1) -- binds: [\"%Bo%\"]"
+  );
+  let query = DbUser::gen_read_query::<SqliteConnection>(&filter).unwrap();
+  assert_eq!(
+    diesel::debug_query::<Sqlite, _>(&query).to_string(),
+    "SELECT `users`.`id`, `users`.`name` FROM `users` \
+     WHERE (lower(`users`.`name`) LIKE lower(?)) -- binds: [\"%Bo%\"]"
+  );
+}
+
+#[test]
+fn starts_with_escapes_the_pattern() {
+  let filter = GenericFilter::new()
+    .r#where("name", GenericClause::StartsWith(r"50%_\".to_owned()));
+  let query = DbUser::gen_read_query::<SqliteConnection>(&filter).unwrap();
+  assert_eq!(
+    diesel::debug_query::<Sqlite, _>(&query).to_string(),
+    r#"SELECT `users`.`id`, `users`.`name` FROM `users` WHERE (`users`.`name` LIKE ? ESCAPE ?) -- binds: ["50\\%\\_\\\\%", "\\"]"#
+  );
+}
+
+#[test]
+fn empty_in_lists_are_constant_predicates() {
+  let sql = |clause| {
+    let filter = GenericFilter::new().r#where("id", clause);
+    let query = DbUser::gen_read_query::<SqliteConnection>(&filter).unwrap();
+    diesel::debug_query::<Sqlite, _>(&query).to_string()
+  };
+  let select = "SELECT `users`.`id`, `users`.`name` FROM `users`";
+  assert_eq!(
+    sql(GenericClause::In(vec![])),
+    format!("{select} WHERE 1 = 0 -- binds: []")
+  );
+  assert_eq!(
+    sql(GenericClause::NotIn(vec![])),
+    format!("{select} WHERE 1 = 1 -- binds: []")
+  );
+  assert_eq!(
+    sql(GenericClause::In(vec!["1".into(), "2".into()])),
+    format!("{select} WHERE (`users`.`id` IN (?, ?)) -- binds: [\"1\", \"2\"]")
+  );
+}
+
+#[test]
+fn derived_del_query_applies_the_filter() {
+  let filter =
+    GenericFilter::new().r#where("name", GenericClause::Eq("a".into()));
+  let query = DbUser::gen_del_query::<SqliteConnection>(&filter).unwrap();
+  assert_eq!(
+    diesel::debug_query::<Sqlite, _>(&query).to_string(),
+    "DELETE  FROM `users` WHERE (`users`.`name` = ?) -- binds: [\"a\"]"
+  );
+}
+
+#[test]
+fn typed_values_are_bound_with_the_column_type() {
+  let filter = GenericFilter::new()
+    .r#where("quantity", GenericClause::Eq("2".into()))
+    .r#where("price", GenericClause::Gt(1.into()))
+    .r#where("active", GenericClause::Eq(true.into()));
+  let query = DbItem::gen_read_query::<PgConnection>(&filter).unwrap();
+  assert_eq!(
+    diesel::debug_query::<Pg, _>(&query).to_string(),
+    "SELECT \"items\".\"id\", \"items\".\"quantity\", \"items\".\"price\", \
+     \"items\".\"active\", \"items\".\"note\" FROM \"items\" \
+     WHERE (((\"items\".\"quantity\" = $1) AND (\"items\".\"price\" > $2)) \
+     AND (\"items\".\"active\" = $3)) -- binds: [2, 1.0, true]"
+  );
+}
+
+#[test]
+fn array_clauses_render_the_pg_operators() {
+  let filter = GenericFilter::new()
+    .r#where("tags", GenericClause::ArrayContains(vec!["rust".into()]))
+    .r#where(
+      "tags",
+      GenericClause::ArrayOverlaps(vec!["db".into(), "web".into()]),
+    );
+  let query = DbArticle::gen_read_query::<PgConnection>(&filter).unwrap();
+  assert_eq!(
+    diesel::debug_query::<Pg, _>(&query).to_string(),
+    "SELECT \"articles\".\"id\", \"articles\".\"tags\" FROM \"articles\" \
+     WHERE ((\"articles\".\"tags\" @> $1) AND (\"articles\".\"tags\" && $2)) \
+     -- binds: [[\"rust\"], [\"db\", \"web\"]]"
+  );
+  let filter =
+    GenericFilter::new().r#where("tags", GenericClause::Eq("rust".into()));
+  assert!(DbArticle::gen_read_query::<PgConnection>(&filter).is_err());
+  let filter = GenericFilter::new()
+    .r#where("id", GenericClause::ArrayContains(vec!["1".into()]));
+  assert!(DbArticle::gen_read_query::<PgConnection>(&filter).is_err());
+}
+
+#[test]
+fn full_text_renders_the_text_search_match() {
+  let clause = GenericClause::FullText("fast db".to_owned(), None);
+  let filter = GenericFilter::new().r#where("name", clause);
+  let query = DbUser::gen_read_query::<PgConnection>(&filter).unwrap();
+  assert_eq!(
+    diesel::debug_query::<Pg, _>(&query).to_string(),
+    "SELECT \"users\".\"id\", \"users\".\"name\" FROM \"users\" \
+     WHERE (to_tsvector(\"users\".\"name\") @@ plainto_tsquery($1)) \
+     -- binds: [\"fast db\"]"
+  );
+  let clause =
+    GenericClause::FullText("fast db".to_owned(), Some("english".into()));
+  let filter = GenericFilter::new().r#where("name", clause);
+  let query = DbUser::gen_read_query::<PgConnection>(&filter).unwrap();
+  assert_eq!(
+    diesel::debug_query::<Pg, _>(&query).to_string(),
+    "SELECT \"users\".\"id\", \"users\".\"name\" FROM \"users\" \
+     WHERE (to_tsvector(CAST($1 AS regconfig), \"users\".\"name\") \
+     @@ plainto_tsquery(CAST($2 AS regconfig), $3)) \
+     -- binds: [\"english\", \"english\", \"fast db\"]"
+  );
+  assert!(DbUser::gen_read_query::<SqliteConnection>(&filter).is_err());
+}
+
+#[test]
+fn json_contains_is_not_a_text_clause() {
+  let value = serde_json::json!({ "country": "FR" });
+  let filter = GenericFilter::new()
+    .r#where("data", GenericClause::JsonContains(value.clone()));
+  let query = DbProfile::gen_read_query::<PgConnection>(&filter).unwrap();
+  assert_eq!(
+    diesel::debug_query::<Pg, _>(&query).to_string(),
+    "SELECT \"profiles\".\"id\", \"profiles\".\"data\" FROM \"profiles\" \
+     WHERE (\"profiles\".\"data\" @> $1) \
+     -- binds: [Object {\"country\": String(\"FR\")}]"
+  );
+  let filter =
+    GenericFilter::new().r#where("name", GenericClause::JsonContains(value));
+  let Err(err) = DbUser::gen_read_query::<PgConnection>(&filter) else {
+    panic!("json_contains applied on a text column");
+  };
+  assert!(err
+    .to_string()
+    .contains("json clauses need a Postgres jsonb column"));
+  let filter = GenericFilter::new()
+    .r#where("name", GenericClause::ContainsText("FR".to_owned()));
+  let query = DbUser::gen_read_query::<PgConnection>(&filter).unwrap();
+  assert_eq!(
+    diesel::debug_query::<Pg, _>(&query).to_string(),
+    "SELECT \"users\".\"id\", \"users\".\"name\" FROM \"users\" \
+     WHERE (\"users\".\"name\" LIKE $1 ESCAPE $2) \
+     -- binds: [\"%FR%\", \"\\\\\"]"
+  );
+}
+
+#[test]
+fn locking_select_renders_for_update() {
+  let filter = GenericFilter::new()
+    .r#where("id", GenericClause::Eq("1".into()))
+    .where_group(FilterGroup::Or(vec![
+      FilterGroup::leaf("name", GenericClause::Eq("a".into())),
+      FilterGroup::leaf("name", GenericClause::Eq("b".into())),
+    ]));
+  let predicate = filter_predicate::<DbUser, Pg>(&filter).unwrap();
+  let query = users::table.filter(predicate).for_update();
+  assert_eq!(
+    diesel::debug_query::<Pg, _>(&query).to_string(),
+    "SELECT \"users\".\"id\", \"users\".\"name\" FROM \"users\" \
+     WHERE ((\"users\".\"id\" = $1) AND ((\"users\".\"name\" = $2) \
+     OR (\"users\".\"name\" = $3))) FOR UPDATE -- binds: [\"1\", \"a\", \"b\"]"
+  );
+}
+
+#[test]
+fn same_filter_renders_the_same_sql() {
+  let render = || {
+    let filter = GenericFilter::new()
+      .r#where("name", GenericClause::Eq("a".into()))
+      .r#where("id", GenericClause::Ne("1".into()))
+      .r#where("name", GenericClause::Ne("b".into()));
+    let query = DbUser::gen_read_query::<PgConnection>(&filter).unwrap();
+    diesel::debug_query::<Pg, _>(&query).to_string()
+  };
+  let sql = render();
+  assert_eq!(sql, render());
+  // The clauses are applied in insertion order
+  assert!(sql.contains(
+    "WHERE (((\"users\".\"name\" = $1) AND (\"users\".\"id\" != $2)) \
+     AND (\"users\".\"name\" != $3))"
+  ));
+}
+
+#[test]
+fn validate_lists_the_unknown_columns_once() {
+  let filter = GenericFilter::new()
+    .r#where("name", GenericClause::Eq("a".into()))
+    .r#where("age", GenericClause::Eq("1".into()))
+    .where_group(FilterGroup::Or(vec![
+      FilterGroup::leaf("id", GenericClause::Eq("1".into())),
+      FilterGroup::leaf("email", GenericClause::IsNull),
+      FilterGroup::leaf("age", GenericClause::IsNull),
+    ]))
+    .order_by("rank", OrderDirection::Asc);
+  assert_eq!(
+    filter.validate::<DbUser, Sqlite>(),
+    Err(vec![
+      "age".to_owned(),
+      "email".to_owned(),
+      "rank".to_owned()
+    ])
+  );
+  let filter = GenericFilter::new()
+    .r#where("name", GenericClause::Eq("a".into()))
+    .order_by("id", OrderDirection::Desc);
+  assert_eq!(filter.validate::<DbUser, Pg>(), Ok(()));
+}
+
+#[test]
+fn distinct_on_renders_distinct_on_on_pg_only() {
+  let filter = GenericFilter::new()
+    .distinct_on("name")
+    .order_by("id", OrderDirection::Desc);
+  let query = DbUser::gen_read_query::<PgConnection>(&filter).unwrap();
+  let query = apply_order::<DbUser, Pg>(query, &filter).unwrap();
+  assert_eq!(
+    diesel::debug_query::<Pg, _>(&query).to_string(),
+    "SELECT DISTINCT ON (\"users\".\"name\") \"users\".\"id\", \
+     \"users\".\"name\" FROM \"users\" \
+     ORDER BY \"users\".\"name\" ASC , \"users\".\"id\" DESC  -- binds: []"
+  );
+  let Err(err) = DbUser::gen_read_query::<SqliteConnection>(&filter) else {
+    panic!("distinct_on rendered on SQLite");
+  };
+  assert_eq!(err.to_string(), "distinct_on is only supported on Postgres");
+}
+
+#[test]
+fn merge_ands_the_clauses_and_prefers_the_other_fields() {
+  let base = GenericFilter::new()
+    .r#where("name", GenericClause::Ne("admin".into()))
+    .order_by("id", OrderDirection::Asc)
+    .limit(10)
+    .offset(5);
+  let request = GenericFilter::new()
+    .r#where("name", GenericClause::StartsWith("a".to_owned()))
+    .order_by("name", OrderDirection::Desc)
+    .limit(20);
+  let filter = base.merge(request);
+  let query = DbUser::gen_read_query::<SqliteConnection>(&filter).unwrap();
+  assert_eq!(
+    diesel::debug_query::<Sqlite, _>(&query).to_string(),
+    "SELECT `users`.`id`, `users`.`name` FROM `users` \
+     WHERE ((`users`.`name` != ?) AND (`users`.`name` LIKE ? ESCAPE ?)) \
+     -- binds: [\"admin\", \"a%\", \"\\\\\"]"
+  );
+  assert_eq!(filter.limit, Some(20));
+  assert_eq!(filter.offset, Some(5));
+  assert_eq!(
+    filter.order,
+    Some(vec![("name".to_owned(), OrderDirection::Desc)])
+  );
+  let filter = GenericFilter::new().merge(GenericFilter::new());
+  assert!(filter.r#where.is_none() && filter.groups.is_none());
+  assert!(!filter.has_conditions());
+}
+
+#[test]
+fn null_operands_render_is_null() {
+  let filter = GenericFilter::new()
+    .r#where("name", GenericClause::Eq(GenericValue::Null))
+    .r#where("id", GenericClause::Ne(GenericValue::Null));
+  let query = DbUser::gen_read_query::<SqliteConnection>(&filter).unwrap();
+  assert_eq!(
+    diesel::debug_query::<Sqlite, _>(&query).to_string(),
+    "SELECT `users`.`id`, `users`.`name` FROM `users` \
+     WHERE ((`users`.`name` IS NULL) AND (`users`.`id` IS NOT NULL)) \
+     -- binds: []"
+  );
+  let query = DbUser::gen_read_query::<PgConnection>(&filter).unwrap();
+  assert_eq!(
+    diesel::debug_query::<Pg, _>(&query).to_string(),
+    "SELECT \"users\".\"id\", \"users\".\"name\" FROM \"users\" \
+     WHERE ((\"users\".\"name\" IS NULL) AND (\"users\".\"id\" IS NOT NULL)) \
+     -- binds: []"
+  );
+}
+
+#[test]
+fn filters_of_a_fixed_shape_stay_in_the_statement_cache() {
+  use diesel::query_builder::QueryFragment;
+
+  let cached = |filter: GenericFilter| {
+    let query = DbUser::gen_read_query::<SqliteConnection>(&filter).unwrap();
+    QueryFragment::<Sqlite>::is_safe_to_cache_prepared(&query, &Sqlite).unwrap()
+  };
+  let filter = GenericFilter::new()
+    .r#where("name", GenericClause::Eq("a".into()))
+    .r#where("id", GenericClause::In(vec![]));
+  assert!(cached(filter));
+  let filter = GenericFilter::new()
+    .where_group(FilterGroup::Or(vec![]))
+    .where_group(FilterGroup::And(vec![]));
+  assert!(cached(filter));
+  // A bind per value, the SQL changes with the length of the list
+  let filter = GenericFilter::new()
+    .r#where("id", GenericClause::In(vec!["1".into(), "2".into()]));
+  assert!(!cached(filter));
+}
+
+#[test]
+fn build_sql_returns_the_sql_and_the_operands() {
+  let db = sqlite("build_sql_returns_the_sql_and_the_operands");
+  let filter = GenericFilter::new()
+    .r#where("id", GenericClause::In(vec!["1".into(), "2".into()]))
+    .r#where("name", GenericClause::StartsWith("te".to_owned()));
+  let (sql, binds) = db.build_del_sql::<DbUser>(&filter).unwrap();
+  assert_eq!(
+    sql,
+    "DELETE  FROM `users` WHERE ((`users`.`id` IN (?, ?)) \
+     AND (`users`.`name` LIKE ? ESCAPE ?))"
+  );
+  assert_eq!(
+    binds,
+    [
+      SqlValue::Text("1".to_owned()),
+      SqlValue::Text("2".to_owned()),
+      SqlValue::Text("te".to_owned()),
+    ]
+  );
+  let (sql, binds) = db
+    .build_read_sql::<DbUser>(&filter.clone().limit(5))
+    .unwrap();
+  assert_eq!(
+    sql,
+    "SELECT `users`.`id`, `users`.`name` FROM `users` \
+     WHERE ((`users`.`id` IN (?, ?)) AND (`users`.`name` LIKE ? ESCAPE ?)) \
+     LIMIT ?"
+  );
+  // The limit is bound but isn't an operand of the filter
+  assert_eq!(binds.len(), 3);
+  let changeset = DbUserUpdate {
+    name: Some("reviewed".to_owned()),
+  };
+  let (sql, binds) = db
+    .build_update_sql::<DbUser, _>(&filter, changeset)
+    .unwrap();
+  assert_eq!(
+    sql,
+    "UPDATE `users` SET `name` = ? \
+     WHERE ((`users`.`id` IN (?, ?)) AND (`users`.`name` LIKE ? ESCAPE ?))"
+  );
+  assert_eq!(binds, filter.operands());
+  let everyone = GenericFilter::new();
+  assert!(db.build_del_sql::<DbUser>(&everyone).is_err());
+  assert_eq!(count_users(&db), 0);
+}
+
+#[test]
+fn operands_follow_the_clauses_then_the_groups() {
+  let filter = GenericFilter::new()
+    .r#where("quantity", GenericClause::Between(1.into(), 3.into()))
+    .r#where("note", GenericClause::IsNull)
+    .where_group(FilterGroup::Or(vec![
+      FilterGroup::leaf("price", GenericClause::Gt(2.5.into())),
+      FilterGroup::And(vec![FilterGroup::leaf(
+        "active",
+        GenericClause::Eq(true.into()),
+      )]),
+    ]))
+    .r#where("note", GenericClause::Like("a%".to_owned()));
+  assert_eq!(
+    filter.operands(),
+    [
+      SqlValue::Int(1),
+      SqlValue::Int(3),
+      SqlValue::Text("a%".to_owned()),
+      SqlValue::Float(2.5),
+      SqlValue::Bool(true),
+    ]
+  );
+  let (sql, binds) = build_sql::<Pg, _>(
+    &DbItem::gen_read_query::<PgConnection>(&filter).unwrap(),
+    &filter,
+  )
+  .unwrap();
+  assert_eq!(
+    sql,
+    "SELECT \"items\".\"id\", \"items\".\"quantity\", \"items\".\"price\", \
+     \"items\".\"active\", \"items\".\"note\" FROM \"items\" \
+     WHERE ((((\"items\".\"quantity\" BETWEEN $1 AND $2) \
+     AND (\"items\".\"note\" IS NULL)) AND (\"items\".\"note\" LIKE $3)) \
+     AND ((\"items\".\"price\" > $4) OR (\"items\".\"active\" = $5)))"
+  );
+  assert_eq!(binds, filter.operands());
+}
+
+#[test]
+fn into_filter_skips_the_none_fields() {
+  let filter = UserQuery::default().into_filter();
+  assert!(!filter.has_conditions());
+  let query = DbUser::gen_read_query::<SqliteConnection>(&filter).unwrap();
+  assert_eq!(
+    diesel::debug_query::<Sqlite, _>(&query).to_string(),
+    "SELECT `users`.`id`, `users`.`name` FROM `users` -- binds: []"
+  );
+  let query = UserQuery {
+    ids: Some(vec!["1".to_owned(), "2".to_owned()]),
+    page: 3,
+    ..Default::default()
+  };
+  let filter = query.into_filter();
+  let query = DbUser::gen_read_query::<SqliteConnection>(&filter).unwrap();
+  assert_eq!(
+    diesel::debug_query::<Sqlite, _>(&query).to_string(),
+    "SELECT `users`.`id`, `users`.`name` FROM `users` \
+     WHERE (`users`.`id` IN (?, ?)) -- binds: [\"1\", \"2\"]"
+  );
+}
+
+#[test]
+fn into_filter_adds_a_clause_per_some_field() {
+  let query = UserQuery {
+    name: Some("te".to_owned()),
+    ids: Some(vec!["1".to_owned()]),
+    outside: Some(("a".to_owned(), "c".to_owned())),
+    page: 0,
+  };
+  let filter = query.into_filter();
+  let query = DbUser::gen_read_query::<SqliteConnection>(&filter).unwrap();
+  assert_eq!(
+    diesel::debug_query::<Sqlite, _>(&query).to_string(),
+    "SELECT `users`.`id`, `users`.`name` FROM `users` \
+     WHERE (((`users`.`name` LIKE ? ESCAPE ?) AND (`users`.`id` IN (?))) \
+     AND (`users`.`name` NOT BETWEEN ? AND ?)) \
+     -- binds: [\"te%\", \"\\\\\", \"1\", \"a\", \"c\"]"
+  );
+  let filter = ItemQuery {
+    min_quantity: 2,
+    no_note: Some(false),
+    active: None,
+  }
+  .into_filter();
+  let query = DbItem::gen_read_query::<PgConnection>(&filter).unwrap();
+  assert_eq!(
+    diesel::debug_query::<Pg, _>(&query).to_string(),
+    "SELECT \"items\".\"id\", \"items\".\"quantity\", \"items\".\"price\", \
+     \"items\".\"active\", \"items\".\"note\" FROM \"items\" \
+     WHERE ((\"items\".\"quantity\" >= $1) AND (\"items\".\"note\" IS NOT NULL)) \
+     -- binds: [2]"
+  );
+}
+
+#[test]
+fn exists_related_renders_a_correlated_subquery() {
+  let orders = GenericClause::ExistsRelated("orders".into(), "user_id".into());
+  let filter = GenericFilter::new().r#where("id", orders.clone());
+  assert!(filter.has_conditions());
+  assert!(filter.operands().is_empty());
+  let query = DbUser::gen_read_query::<SqliteConnection>(&filter).unwrap();
+  assert_eq!(
+    diesel::debug_query::<Sqlite, _>(&query).to_string(),
+    "SELECT `users`.`id`, `users`.`name` FROM `users` \
+     WHERE EXISTS (SELECT 1 FROM `orders` \
+     WHERE `orders`.`user_id` = `users`.`id`) -- binds: []"
+  );
+  let filter = !GenericFilter::new().r#where("id", orders);
+  let query = DbUser::gen_read_query::<PgConnection>(&filter).unwrap();
+  assert_eq!(
+    diesel::debug_query::<Pg, _>(&query).to_string(),
+    "SELECT \"users\".\"id\", \"users\".\"name\" FROM \"users\" \
+     WHERE  NOT (EXISTS (SELECT 1 FROM \"orders\" \
+     WHERE \"orders\".\"user_id\" = \"users\".\"id\")) -- binds: []"
+  );
+}
+
+#[test]
+fn group_count_groups_the_filtered_read_query() {
+  let filter =
+    GenericFilter::new().r#where("status", GenericClause::Ne("banned".into()));
+  let query = DbAccount::gen_read_query::<SqliteConnection>(&filter).unwrap();
+  let query = group_count_query::<DbAccount, Sqlite>(query, "status").unwrap();
+  assert_eq!(
+    diesel::debug_query::<Sqlite, _>(&query).to_string(),
+    "SELECT `group_value`, COUNT(*) FROM (SELECT \
+     CAST(`accounts`.`status` AS TEXT) AS `group_value` FROM `accounts` WHERE (`accounts`.`status` != ?)) \
+     AS `grouped` GROUP BY `group_value` \
+     ORDER BY COUNT(*) DESC, `group_value` -- binds: [\"banned\"]"
+  );
+}
+
+#[test]
+fn read_and_del_queries_render_without_a_database() {
+  let filter = GenericFilter::new()
+    .r#where("name", GenericClause::Eq("test".into()))
+    .r#where("id", GenericClause::In(vec!["1".into(), "2".into()]));
+  let read = DbUser::gen_read_query::<PgConnection>(&filter).unwrap();
+  assert_eq!(
+    diesel::debug_query::<Pg, _>(&read).to_string(),
+    "SELECT \"users\".\"id\", \"users\".\"name\" FROM \"users\" \
+     WHERE ((\"users\".\"name\" = $1) AND (\"users\".\"id\" = ANY($2))) \
+     -- binds: [\"test\", [\"1\", \"2\"]]"
+  );
+  let del = DbUser::gen_del_query::<PgConnection>(&!filter.clone()).unwrap();
+  assert_eq!(
+    diesel::debug_query::<Pg, _>(&del).to_string(),
+    "DELETE  FROM \"users\" \
+     WHERE  NOT (((\"users\".\"name\" = $1) AND (\"users\".\"id\" = ANY($2)))) \
+     -- binds: [\"test\", [\"1\", \"2\"]]"
+  );
+  // Nothing listens on the port, the driver never connects
+  let manager = diesel::r2d2::ConnectionManager::<PgConnection>::new(
+    "postgres://postgres@127.0.0.1:1/postgres",
+  );
+  let pool = diesel::r2d2::Pool::builder().build_unchecked(manager);
+  let db = DbDriver::from_pool(pool);
+  assert_eq!(
+    db.explain_sql::<DbUser>(&filter).unwrap(),
+    "SELECT \"users\".\"id\", \"users\".\"name\" FROM \"users\" \
+     WHERE ((\"users\".\"name\" = $1) AND (\"users\".\"id\" = ANY($2))) \
+     LIMIT $3 -- binds: [\"test\", [\"1\", \"2\"], 100]"
+  );
+  let (sql, binds) = db.build_del_sql::<DbUser>(&filter).unwrap();
+  assert_eq!(
+    sql,
+    "DELETE  FROM \"users\" \
+     WHERE ((\"users\".\"name\" = $1) AND (\"users\".\"id\" = ANY($2)))"
+  );
+  assert_eq!(binds, ["test".into(), "1".into(), "2".into()]);
+  assert!(db.build_del_sql::<DbUser>(&GenericFilter::new()).is_err());
+}
+
+#[test]
+fn count_by_column_counts_the_column() {
+  let filter =
+    GenericFilter::new().r#where("active", GenericClause::Eq(true.into()));
+  let query = DbItem::gen_read_query::<PgConnection>(&filter).unwrap();
+  let query =
+    select_aggregate::<DbItem, Pg, i64>(query, AggregateFn::Count, "note")
+      .unwrap();
+  assert_eq!(
+    diesel::debug_query::<Pg, _>(&query).to_string(),
+    "SELECT CAST(COUNT(\"items\".\"note\") AS BIGINT) FROM \"items\" \
+     WHERE (\"items\".\"active\" = $1) -- binds: [true]"
+  );
+}
+
+#[test]
+fn conditions_convert_into_a_filter_of_where_clauses() {
+  let conditions = std::collections::HashMap::from([
+    ("name".to_owned(), GenericClause::Eq("a".into())),
+    ("id".to_owned(), GenericClause::Ne("1".into())),
+  ]);
+  let filter = GenericFilter::from(conditions);
+  assert!(filter.limit.is_none() && filter.offset.is_none());
+  assert!(filter.order.is_none() && filter.groups.is_none());
+  // Sorted by column whatever the order of the map
+  let query = DbUser::gen_read_query::<SqliteConnection>(&filter).unwrap();
+  assert_eq!(
+    diesel::debug_query::<Sqlite, _>(&query).to_string(),
+    "SELECT `users`.`id`, `users`.`name` FROM `users` \
+     WHERE ((`users`.`id` != ?) AND (`users`.`name` = ?)) \
+     -- binds: [\"1\", \"a\"]"
+  );
+  let filter = GenericFilter::from(vec![
+    ("name".to_owned(), GenericClause::Eq("a".into())),
+    ("id".to_owned(), GenericClause::Ne("1".into())),
+  ]);
+  assert!(filter.limit.is_none() && filter.order.is_none());
+  // In the order of the list
+  let query = DbUser::gen_read_query::<SqliteConnection>(&filter).unwrap();
+  assert_eq!(
+    diesel::debug_query::<Sqlite, _>(&query).to_string(),
+    "SELECT `users`.`id`, `users`.`name` FROM `users` \
+     WHERE ((`users`.`name` = ?) AND (`users`.`id` != ?)) \
+     -- binds: [\"a\", \"1\"]"
+  );
+  let empty = GenericFilter::from(std::collections::HashMap::new());
+  assert!(!empty.has_conditions());
+  assert!(!GenericFilter::from(Vec::new()).has_conditions());
+}
@@ -0,0 +1,1992 @@
+//! The queries of the driver on SQLite.
+#![cfg(feature = "runtime-ntex")]
+
+mod common;
+
+use common::*;
+use diesel::result::Error;
+use turbo_diesel::prelude::*;
+
+#[ntex::test]
+async fn del_by_applies_the_where_clause() {
+  let db = sqlite("del_by_applies_the_where_clause");
+  db.create(&user("1", "a")).await.unwrap();
+  db.create(&user("2", "b")).await.unwrap();
+  let filter =
+    GenericFilter::new().r#where("name", GenericClause::Eq("a".into()));
+  assert_eq!(db.del_by::<DbUser>(&filter).await.unwrap(), 1);
+  assert_eq!(db.del_by::<DbUser>(&filter).await.unwrap(), 0);
+  assert_eq!(count_users(&db), 1);
+}
+
+#[ntex::test]
+async fn del_by_refuses_a_filter_true_for_every_row() {
+  let db = sqlite("del_by_refuses_a_filter_true_for_every_row");
+  db.create(&user("1", "a")).await.unwrap();
+  let filters = [
+    GenericFilter::new(),
+    GenericFilter {
+      r#where: Some(Vec::new()),
+      ..Default::default()
+    },
+    GenericFilter::new().r#where("id", GenericClause::NotIn(vec![])),
+    GenericFilter::new()
+      .where_group(FilterGroup::Or(vec![FilterGroup::And(vec![])])),
+    GenericFilter::new().where_group(FilterGroup::And(vec![
+      FilterGroup::leaf("id", GenericClause::NotIn(vec![])),
+      FilterGroup::Or(vec![
+        FilterGroup::leaf("id", GenericClause::Eq("1".into())),
+        FilterGroup::And(vec![]),
+      ]),
+    ])),
+    GenericFilter::new().where_group(!FilterGroup::And(vec![])),
+    !GenericFilter::new(),
+    !!GenericFilter::new(),
+  ];
+  for filter in filters {
+    assert!(!filter.has_conditions(), "{filter:?}");
+    let res = db.del_by::<DbUser>(&filter).await;
+    assert!(
+      matches!(
+        res,
+        Err(TurboDieselError::Query(Error::QueryBuilderError(_)))
+      ),
+      "{filter:?}"
+    );
+  }
+  assert_eq!(count_users(&db), 1);
+}
+
+#[ntex::test]
+async fn del_all_empties_the_table() {
+  let db = sqlite("del_all_empties_the_table");
+  db.create(&user("1", "a")).await.unwrap();
+  db.create(&user("2", "b")).await.unwrap();
+  assert_eq!(db.del_all::<DbUser>(Confirm::Yes).await.unwrap(), 2);
+  assert_eq!(count_users(&db), 0);
+}
+
+#[ntex::test]
+async fn del_by_pk_returns_the_deleted_count() {
+  let db = sqlite("del_by_pk_returns_the_deleted_count");
+  db.create(&user("1", "a")).await.unwrap();
+  assert_eq!(db.del_by_pk::<DbUser, _>("1").await.unwrap(), 1);
+  assert_eq!(db.del_by_pk::<DbUser, _>("1").await.unwrap(), 0);
+}
+
+#[ntex::test]
+async fn del_by_pks_deletes_every_listed_row() {
+  let db = sqlite("del_by_pks_deletes_every_listed_row");
+  for id in ["1", "2", "3"] {
+    db.create(&user(id, "a")).await.unwrap();
+  }
+  assert_eq!(
+    db.del_by_pks::<DbUser, _>(&["1", "3", "4"]).await.unwrap(),
+    2
+  );
+  assert_eq!(db.del_by_pks::<DbUser, &str>(&[]).await.unwrap(), 0);
+  assert_eq!(count_users(&db), 1);
+}
+
+#[ntex::test]
+async fn del_by_pk_strict_is_not_found_without_a_row() {
+  let db = sqlite("del_by_pk_strict_is_not_found_without_a_row");
+  db.create(&user("1", "a")).await.unwrap();
+  db.del_by_pk_strict::<DbUser, _>("1").await.unwrap();
+  let res = db.del_by_pk_strict::<DbUser, _>("1").await;
+  assert!(matches!(res, Err(TurboDieselError::NotFound)), "{res:?}");
+  assert_eq!(count_users(&db), 0);
+}
+
+#[ntex::test]
+async fn read_by_pk_returns_none_for_a_missing_row() {
+  let db = sqlite("read_by_pk_returns_none_for_a_missing_row");
+  db.create(&user("1", "a")).await.unwrap();
+  let found = db.read_by_pk::<DbUser, _>("1").await.unwrap();
+  assert_eq!(found.map(|user| user.name), Some("a".to_owned()));
+  let missing = db.read_by_pk::<DbUser, _>("2").await.unwrap();
+  assert!(missing.is_none());
+}
+
+#[ntex::test]
+async fn read_by_applies_the_filter() {
+  let db = sqlite("read_by_applies_the_filter");
+  for (id, name) in [("1", "ab"), ("2", "ac"), ("3", "ad"), ("4", "b")] {
+    db.create(&user(id, name)).await.unwrap();
+  }
+  let filter =
+    GenericFilter::new().r#where("name", GenericClause::Like("a%".to_owned()));
+  let users = db.read_by::<DbUser>(&filter).await.unwrap();
+  assert_eq!(users.len(), 3);
+  let users = db
+    .read_by::<DbUser>(&filter.limit(2).offset(2))
+    .await
+    .unwrap();
+  assert_eq!(users.len(), 1);
+}
+
+#[ntex::test]
+async fn read_by_refuses_an_unknown_column() {
+  let db = sqlite("read_by_refuses_an_unknown_column");
+  let filter =
+    GenericFilter::new().r#where("email", GenericClause::Eq("a".into()));
+  let err = db.read_by::<DbUser>(&filter).await.unwrap_err();
+  let TurboDieselError::Query(Error::QueryBuilderError(err)) = err else {
+    panic!("{err:?}");
+  };
+  assert_eq!(err.to_string(), "unknown column email");
+  assert_eq!(
+    <DbUser as FilterableColumns<diesel::sqlite::Sqlite>>::COLUMNS,
+    ["id", "name"]
+  );
+}
+
+#[ntex::test]
+async fn update_by_pk_updates_the_name() {
+  let db = sqlite("update_by_pk_updates_the_name");
+  db.create(&user("1", "a")).await.unwrap();
+  let changeset = DbUserUpdate {
+    name: Some("b".to_owned()),
+  };
+  let updated = db
+    .update_by_pk::<DbUser, _, _>("1", changeset)
+    .await
+    .unwrap();
+  assert_eq!(updated.name, "b");
+  let read = db.read_by_pk::<DbUser, _>("1").await.unwrap().unwrap();
+  assert_eq!(read.name, "b");
+}
+
+#[ntex::test]
+async fn create_many_inserts_every_chunk() {
+  let db = sqlite("create_many_inserts_every_chunk");
+  let created = db.create_many::<DbUser>(vec![], None).await.unwrap();
+  assert!(created.is_empty());
+  let users = (0..1200)
+    .map(|i| user(&i.to_string(), "a"))
+    .collect::<Vec<_>>();
+  let created = db.create_many(users, None).await.unwrap();
+  assert_eq!(created.len(), 1200);
+  assert_eq!(created[1199].id, "1199");
+  let users = vec![user("a", "b"), user("b", "b"), user("c", "b")];
+  let created = db.create_many(users, Some(2)).await.unwrap();
+  assert_eq!(created.len(), 3);
+  assert_eq!(count_users(&db), 1203);
+}
+
+#[ntex::test]
+async fn create_many_rolls_back_every_chunk() {
+  let db = sqlite("create_many_rolls_back_every_chunk");
+  let users = vec![user("1", "a"), user("2", "a"), user("1", "a")];
+  db.create_many(users, Some(1)).await.unwrap_err();
+  assert_eq!(count_users(&db), 0);
+}
+
+#[ntex::test]
+async fn count_by_counts_the_matching_rows() {
+  let db = sqlite("count_by_counts_the_matching_rows");
+  for (id, name) in [("1", "a"), ("2", "a"), ("3", "b")] {
+    db.create(&user(id, name)).await.unwrap();
+  }
+  let filter = GenericFilter::new()
+    .r#where("name", GenericClause::Eq("a".into()))
+    .limit(1)
+    .offset(1);
+  assert_eq!(db.count_by::<DbUser>(&filter).await.unwrap(), 2);
+  let all = db.count_by::<DbUser>(&GenericFilter::new()).await.unwrap();
+  assert_eq!(all, 3);
+}
+
+#[ntex::test]
+async fn exists_by_tells_if_a_row_matches() {
+  let db = sqlite("exists_by_tells_if_a_row_matches");
+  db.create(&user("1", "a")).await.unwrap();
+  let a = GenericFilter::new().r#where("name", GenericClause::Eq("a".into()));
+  assert!(db.exists_by::<DbUser>(&a.offset(5)).await.unwrap());
+  let b = GenericFilter::new().r#where("name", GenericClause::Eq("b".into()));
+  assert!(!db.exists_by::<DbUser>(&b).await.unwrap());
+}
+
+#[ntex::test]
+async fn update_by_pk_of_a_missing_row_is_not_found() {
+  let db = sqlite("update_by_pk_of_a_missing_row_is_not_found");
+  let changeset = DbUserUpdate {
+    name: Some("b".to_owned()),
+  };
+  let res = db.update_by_pk::<DbUser, _, _>("1", changeset).await;
+  assert!(matches!(res, Err(TurboDieselError::NotFound)));
+}
+
+#[ntex::test]
+async fn new_with_config_sets_up_the_pool() {
+  let config = PoolConfig {
+    max_size: Some(2),
+    connection_timeout: Some(std::time::Duration::from_millis(100)),
+    ..Default::default()
+  };
+  let path = sqlite_path("new_with_config_sets_up_the_pool");
+  let db =
+    DbDriver::<SqliteConnection>::new_with_config(&path, config).unwrap();
+  let _first = db.get_conn().unwrap();
+  let _second = db.get_conn().unwrap();
+  let started = std::time::Instant::now();
+  assert!(matches!(db.get_conn(), Err(TurboDieselError::PoolTimeout)));
+  assert!(started.elapsed() < std::time::Duration::from_secs(5));
+}
+
+#[ntex::test]
+async fn a_saturated_pool_times_out_apart_from_a_failed_connection() {
+  use diesel::r2d2::{ConnectionManager, Pool};
+
+  let config = PoolConfig {
+    max_size: Some(1),
+    connection_timeout: Some(std::time::Duration::from_millis(100)),
+    ..Default::default()
+  };
+  let path = sqlite_path("a_saturated_pool_times_out");
+  let db =
+    DbDriver::<SqliteConnection>::new_with_config(&path, config).unwrap();
+  let held = db.get_conn().unwrap();
+  let res = db.read_by::<DbUser>(&GenericFilter::new()).await;
+  assert!(matches!(res, Err(TurboDieselError::PoolTimeout)), "{res:?}");
+  drop(held);
+  assert!(db.get_conn().is_ok());
+  // A database that can't be opened isn't a saturation
+  let manager = ConnectionManager::<SqliteConnection>::new(
+    "/turbo_diesel/missing/directory.db",
+  );
+  let pool = Pool::builder()
+    .max_size(1)
+    .connection_timeout(std::time::Duration::from_millis(100))
+    .build_unchecked(manager);
+  let db = DbDriver::from_pool(pool);
+  assert!(matches!(
+    db.get_conn(),
+    Err(TurboDieselError::Connection(_))
+  ));
+}
+
+#[ntex::test]
+async fn connection_customizer_runs_on_every_connection() {
+  #[derive(QueryableByName)]
+  struct ForeignKeys {
+    #[diesel(sql_type = diesel::sql_types::Integer)]
+    foreign_keys: i32,
+  }
+
+  let config = PoolConfig {
+    max_size: Some(2),
+    connection_customizer: Some(
+      ConnectionCustomizer::new().sql("PRAGMA foreign_keys = ON"),
+    ),
+    ..Default::default()
+  };
+  let path = sqlite_path("connection_customizer_runs_on_every_connection");
+  let db =
+    DbDriver::<SqliteConnection>::new_with_config(&path, config).unwrap();
+  let conns = [db.get_conn().unwrap(), db.get_conn().unwrap()];
+  for mut conn in conns {
+    let pragma = diesel::sql_query("PRAGMA foreign_keys")
+      .get_result::<ForeignKeys>(&mut *conn)
+      .unwrap();
+    assert_eq!(pragma.foreign_keys, 1);
+  }
+  let config = PoolConfig {
+    connection_customizer: Some(ConnectionCustomizer::new().sql("NOT SQL")),
+    connection_timeout: Some(std::time::Duration::from_millis(100)),
+    ..Default::default()
+  };
+  let db = DbDriver::<SqliteConnection>::new_with_config(&path, config);
+  assert!(db.is_err_and(|err| matches!(err, TurboDieselError::Connection(_))));
+}
+
+#[ntex::test]
+async fn transaction_commits_every_statement() {
+  let db = sqlite("transaction_commits_every_statement");
+  db.transaction(|conn| {
+    diesel::insert_into(users::table)
+      .values(user("1", "a"))
+      .execute(conn)?;
+    diesel::insert_into(users::table)
+      .values(user("2", "b"))
+      .execute(conn)?;
+    Ok(())
+  })
+  .await
+  .unwrap();
+  assert_eq!(count_users(&db), 2);
+}
+
+#[ntex::test]
+async fn transaction_rolls_back_on_err() {
+  let db = sqlite("transaction_rolls_back_on_err");
+  let res = db
+    .transaction(|conn| {
+      diesel::insert_into(users::table)
+        .values(user("1", "a"))
+        .execute(conn)?;
+      Err::<(), _>(Error::RollbackTransaction)
+    })
+    .await;
+  assert!(matches!(
+    res,
+    Err(TurboDieselError::Query(Error::RollbackTransaction))
+  ));
+  assert_eq!(count_users(&db), 0);
+}
+
+#[ntex::test]
+async fn transaction_with_retry_reruns_a_retryable_error() {
+  use diesel::result::DatabaseErrorKind;
+  use std::sync::atomic::{AtomicU32, Ordering};
+  use std::sync::Arc;
+
+  let db = sqlite("transaction_with_retry_reruns_a_retryable_error");
+  // Fail the first `failures` attempts with a serialization failure,
+  // after inserting a row rolled back with the transaction
+  let run = |failures: u32, max_retries: u32| {
+    let attempts = Arc::new(AtomicU32::new(0));
+    let counter = attempts.clone();
+    let res = db.transaction_with_retry(max_retries, None, move |conn| {
+      let attempt = counter.fetch_add(1, Ordering::SeqCst);
+      diesel::insert_into(users::table)
+        .values(user(&format!("{max_retries}-{attempt}"), "a"))
+        .execute(conn)?;
+      if attempt < failures {
+        let info = Box::new("could not serialize access".to_owned());
+        return Err(Error::DatabaseError(
+          DatabaseErrorKind::SerializationFailure,
+          info,
+        ));
+      }
+      Ok(attempt)
+    });
+    async move { (res.await, attempts.load(Ordering::SeqCst)) }
+  };
+  let (res, attempts) = run(1, 3).await;
+  assert_eq!((res.unwrap(), attempts), (1, 2));
+  assert_eq!(count_users(&db), 1);
+  let (res, attempts) = run(u32::MAX, 2).await;
+  assert!(matches!(
+    res,
+    Err(TurboDieselError::Query(Error::DatabaseError(
+      DatabaseErrorKind::SerializationFailure,
+      _
+    )))
+  ));
+  assert_eq!(attempts, 3);
+  assert_eq!(count_users(&db), 1);
+  // Other errors aren't retried
+  let res = db
+    .transaction_with_retry(3, None, |_| {
+      Err::<(), _>(Error::RollbackTransaction)
+    })
+    .await;
+  assert!(matches!(
+    res,
+    Err(TurboDieselError::Query(Error::RollbackTransaction))
+  ));
+}
+
+#[ntex::test]
+async fn read_by_between_includes_the_bounds() {
+  let db = sqlite("read_by_between_includes_the_bounds");
+  for (id, name) in [("1", "a"), ("2", "b"), ("3", "c"), ("4", "d")] {
+    db.create(&user(id, name)).await.unwrap();
+  }
+  let between = GenericClause::Between("b".into(), "c".into());
+  let filter = GenericFilter::new().r#where("name", between);
+  let users = db.read_by::<DbUser>(&filter).await.unwrap();
+  assert_eq!(users.len(), 2);
+  let not_between = GenericClause::NotBetween("b".into(), "c".into());
+  let filter = GenericFilter::new().r#where("name", not_between);
+  let users = db.read_by::<DbUser>(&filter).await.unwrap();
+  assert_eq!(users.len(), 2);
+}
+
+#[ntex::test]
+async fn read_by_sorts_by_every_order_entry() {
+  let db = sqlite("read_by_sorts_by_every_order_entry");
+  for (id, name) in [("1", "b"), ("2", "a"), ("3", "b"), ("4", "c")] {
+    db.create(&user(id, name)).await.unwrap();
+  }
+  let filter = GenericFilter::new()
+    .order_by("name", OrderDirection::Desc)
+    .order_by("id", OrderDirection::Asc);
+  let users = db.read_by::<DbUser>(&filter).await.unwrap();
+  let ids = users
+    .iter()
+    .map(|user| user.id.as_str())
+    .collect::<Vec<_>>();
+  assert_eq!(ids, ["4", "1", "3", "2"]);
+}
+
+#[ntex::test]
+async fn read_by_refuses_to_order_by_an_unknown_column() {
+  let db = sqlite("read_by_refuses_to_order_by_an_unknown_column");
+  let filter = GenericFilter::new().order_by("email", OrderDirection::Asc);
+  let err = db.read_by::<DbUser>(&filter).await.unwrap_err();
+  let TurboDieselError::Query(Error::QueryBuilderError(err)) = err else {
+    panic!("{err:?}");
+  };
+  assert_eq!(err.to_string(), "unknown column email");
+}
+
+#[ntex::test]
+async fn read_by_keeps_every_clause_on_a_column() {
+  let db = sqlite("read_by_keeps_every_clause_on_a_column");
+  for (id, name) in [("1", "a"), ("2", "b"), ("3", "c"), ("4", "d")] {
+    db.create(&user(id, name)).await.unwrap();
+  }
+  let filter = GenericFilter::new()
+    .r#where("name", GenericClause::Gt("a".into()))
+    .r#where("name", GenericClause::Lt("d".into()));
+  assert_eq!(filter.r#where.as_ref().unwrap().len(), 2);
+  let users = db.read_by::<DbUser>(&filter).await.unwrap();
+  let ids = users
+    .iter()
+    .map(|user| user.id.as_str())
+    .collect::<Vec<_>>();
+  assert_eq!(ids, ["2", "3"]);
+}
+
+#[ntex::test]
+async fn read_by_applies_the_groups() {
+  let db = sqlite("read_by_applies_the_groups");
+  for (id, name) in [("1", "a"), ("2", "b"), ("3", "b"), ("4", "c")] {
+    db.create(&user(id, name)).await.unwrap();
+  }
+  let filter = GenericFilter::new()
+    .where_group(FilterGroup::Or(vec![
+      FilterGroup::leaf("id", GenericClause::Eq("1".into())),
+      FilterGroup::leaf("name", GenericClause::Eq("b".into())),
+    ]))
+    .r#where("id", GenericClause::Ne("3".into()))
+    .order_by("id", OrderDirection::Asc);
+  let users = db.read_by::<DbUser>(&filter).await.unwrap();
+  let ids = users
+    .iter()
+    .map(|user| user.id.as_str())
+    .collect::<Vec<_>>();
+  assert_eq!(ids, ["1", "2"]);
+  let filter = GenericFilter::new().where_group(FilterGroup::Or(vec![]));
+  assert!(db.read_by::<DbUser>(&filter).await.unwrap().is_empty());
+}
+
+#[ntex::test]
+async fn read_by_ilike_ignores_the_case() {
+  let db = sqlite("read_by_ilike_ignores_the_case");
+  for (id, name) in [("1", "Bob"), ("2", "BOBBY"), ("3", "alice")] {
+    db.create(&user(id, name)).await.unwrap();
+  }
+  let filter = GenericFilter::new()
+    .r#where("name", GenericClause::ILike("bob%".to_owned()));
+  assert_eq!(db.count_by::<DbUser>(&filter).await.unwrap(), 2);
+}
+
+#[ntex::test]
+async fn text_clauses_match_the_wildcards_literally() {
+  let db = sqlite("text_clauses_match_the_wildcards_literally");
+  let names = [
+    ("1", "50% off"),
+    ("2", "500 off"),
+    ("3", "a_b"),
+    ("4", "axb"),
+  ];
+  for (id, name) in names {
+    db.create(&user(id, name)).await.unwrap();
+  }
+  db.create(&user("5", "c:\\dir")).await.unwrap();
+  let db = &db;
+  let count = |clause| {
+    let filter = GenericFilter::new().r#where("name", clause);
+    async move { db.count_by::<DbUser>(&filter).await.unwrap() }
+  };
+  assert_eq!(count(GenericClause::StartsWith("50%".to_owned())).await, 1);
+  assert_eq!(count(GenericClause::Like("50%".to_owned())).await, 2);
+  assert_eq!(count(GenericClause::EndsWith("_b".to_owned())).await, 1);
+  assert_eq!(count(GenericClause::ContainsText("_".to_owned())).await, 1);
+  assert_eq!(count(GenericClause::ContainsText("\\".to_owned())).await, 1);
+  assert_eq!(
+    count(GenericClause::ContainsText("off".to_owned())).await,
+    2
+  );
+}
+
+#[ntex::test]
+async fn read_by_empty_in_lists_follow_the_set_semantics() {
+  let db = sqlite("read_by_empty_in_lists_follow_the_set_semantics");
+  db.create(&user("1", "a")).await.unwrap();
+  db.create(&user("2", "b")).await.unwrap();
+  let filter_by = |clause| GenericFilter::new().r#where("id", clause);
+  let filter = filter_by(GenericClause::In(vec![]));
+  assert_eq!(db.count_by::<DbUser>(&filter).await.unwrap(), 0);
+  let filter = filter_by(GenericClause::NotIn(vec![]));
+  assert_eq!(db.count_by::<DbUser>(&filter).await.unwrap(), 2);
+  let filter = filter_by(GenericClause::In(vec!["2".into()]));
+  assert_eq!(db.count_by::<DbUser>(&filter).await.unwrap(), 1);
+}
+
+#[ntex::test]
+async fn read_by_applies_the_default_limit() {
+  let db = sqlite("read_by_applies_the_default_limit");
+  let users = (0..150)
+    .map(|i| user(&i.to_string(), "a"))
+    .collect::<Vec<_>>();
+  db.create_many(users, None).await.unwrap();
+  let filter = GenericFilter::new();
+  assert_eq!(db.read_by::<DbUser>(&filter).await.unwrap().len(), 100);
+  let unlimited = GenericFilter::new().unlimited();
+  assert_eq!(db.read_by::<DbUser>(&unlimited).await.unwrap().len(), 150);
+  let db = db.with_default_limit(Some(10));
+  assert_eq!(db.read_by::<DbUser>(&filter).await.unwrap().len(), 10);
+  let limited = GenericFilter::new().limit(120);
+  assert_eq!(db.read_by::<DbUser>(&limited).await.unwrap().len(), 120);
+  let db = db.with_default_limit(None);
+  assert_eq!(db.read_by::<DbUser>(&filter).await.unwrap().len(), 150);
+}
+
+#[ntex::test]
+async fn pool_config_sets_the_default_limit() {
+  let path = sqlite_path("pool_config_sets_the_default_limit");
+  let config = PoolConfig {
+    default_limit: Some(3),
+    ..Default::default()
+  };
+  let db =
+    DbDriver::<SqliteConnection>::new_with_config(&path, config).unwrap();
+  db.execute_sql(
+    "CREATE TABLE users (id TEXT PRIMARY KEY NOT NULL, name TEXT NOT NULL)",
+    Vec::new(),
+  )
+  .await
+  .unwrap();
+  let users = (0..5).map(|i| user(&i.to_string(), "a")).collect();
+  db.create_many(users, None).await.unwrap();
+  let filter = GenericFilter::new();
+  assert_eq!(db.read_by::<DbUser>(&filter).await.unwrap().len(), 3);
+  let limited = GenericFilter::new().limit(4);
+  assert_eq!(db.read_by::<DbUser>(&limited).await.unwrap().len(), 4);
+  let path = sqlite_path("pool_config_keeps_the_default_limit");
+  let db =
+    DbDriver::<SqliteConnection>::new_with_config(&path, PoolConfig::default())
+      .unwrap();
+  assert_eq!(db.default_limit(), Some(DEFAULT_LIMIT));
+}
+
+#[ntex::test]
+async fn upsert_updates_the_existing_row() {
+  let db = sqlite("upsert_updates_the_existing_row");
+  let created = db.upsert(&user("1", "first")).await.unwrap();
+  assert_eq!(created.name, "first");
+  let updated = db.upsert(&user("1", "second")).await.unwrap();
+  assert_eq!(updated.name, "second");
+  assert_eq!(count_users(&db), 1);
+  let read = db.read_by_pk::<DbUser, _>("1").await.unwrap().unwrap();
+  assert_eq!(read.name, "second");
+}
+
+#[ntex::test]
+async fn a_negated_filter_matches_the_other_rows() {
+  let db = sqlite("a_negated_filter_matches_the_other_rows");
+  for (id, name) in [("1", "a"), ("2", "a"), ("1b", "b"), ("3", "c")] {
+    db.create(&user(id, name)).await.unwrap();
+  }
+  let filter = GenericFilter::new()
+    .r#where("name", GenericClause::Eq("a".into()))
+    .r#where("id", GenericClause::Eq("1".into()))
+    .order_by("id", OrderDirection::Asc);
+  let ids = |users: Vec<DbUser>| {
+    users.into_iter().map(|user| user.id).collect::<Vec<_>>()
+  };
+  let matched = db.read_by::<DbUser>(&filter).await.unwrap();
+  assert_eq!(ids(matched), ["1"]);
+  let negated = !filter.clone();
+  assert!(negated.has_conditions());
+  let others = db.read_by::<DbUser>(&negated).await.unwrap();
+  assert_eq!(ids(others), ["1b", "2", "3"]);
+  let twice = db.read_by::<DbUser>(&!negated.clone()).await.unwrap();
+  assert_eq!(ids(twice), ["1"]);
+  // A negated group inside an `Or`
+  let either = GenericFilter::new()
+    .where_group(FilterGroup::Or(vec![
+      !FilterGroup::leaf("name", GenericClause::Ne("c".into())),
+      FilterGroup::leaf("id", GenericClause::Eq("2".into())),
+    ]))
+    .order_by("id", OrderDirection::Asc);
+  let rows = db.read_by::<DbUser>(&either).await.unwrap();
+  assert_eq!(ids(rows), ["2", "3"]);
+  assert_eq!(db.del_by::<DbUser>(&negated).await.unwrap(), 3);
+  assert_eq!(count_users(&db), 1);
+}
+
+#[ntex::test]
+async fn read_one_returns_the_first_match() {
+  let db = sqlite("read_one_returns_the_first_match");
+  for (id, name) in [("2", "b"), ("1", "a"), ("3", "b")] {
+    db.create(&user(id, name)).await.unwrap();
+  }
+  let filter =
+    GenericFilter::new().r#where("name", GenericClause::Eq("a".into()));
+  let found = db.read_one::<DbUser>(&filter).await.unwrap().unwrap();
+  assert_eq!(found.id, "1");
+  let filter =
+    GenericFilter::new().r#where("name", GenericClause::Eq("b".into()));
+  let found = db.read_one::<DbUser>(&filter).await.unwrap().unwrap();
+  assert_eq!(found.id, "2");
+  let filter = filter.order_by("id", OrderDirection::Desc);
+  let found = db.read_one::<DbUser>(&filter).await.unwrap().unwrap();
+  assert_eq!(found.id, "3");
+  let filter =
+    GenericFilter::new().r#where("name", GenericClause::Eq("c".into()));
+  assert!(db.read_one::<DbUser>(&filter).await.unwrap().is_none());
+}
+
+#[ntex::test]
+#[should_panic(expected = "closure panicked")]
+async fn execute_resumes_the_panic_of_the_closure() {
+  let db = sqlite("execute_resumes_the_panic_of_the_closure");
+  let _ = db
+    .transaction(|_| -> Result<(), Error> { panic!("closure panicked") })
+    .await;
+}
+
+#[ntex::test]
+async fn update_by_updates_the_matching_rows() {
+  let db = sqlite("update_by_updates_the_matching_rows");
+  for (id, name) in [("1", "test a"), ("2", "test b"), ("3", "other")] {
+    db.create(&user(id, name)).await.unwrap();
+  }
+  let filter = GenericFilter::new()
+    .r#where("name", GenericClause::Like("test%".to_owned()));
+  let changeset = DbUserUpdate {
+    name: Some("matched".to_owned()),
+  };
+  assert_eq!(
+    db.update_by::<DbUser, _>(&filter, changeset).await.unwrap(),
+    2
+  );
+  let filter =
+    GenericFilter::new().r#where("name", GenericClause::Eq("matched".into()));
+  assert_eq!(db.count_by::<DbUser>(&filter).await.unwrap(), 2);
+  let refused = [
+    GenericFilter::new(),
+    GenericFilter::new().r#where("id", GenericClause::NotIn(vec![])),
+  ];
+  for filter in refused {
+    let changeset = DbUserUpdate {
+      name: Some("everyone".to_owned()),
+    };
+    let res = db.update_by::<DbUser, _>(&filter, changeset).await;
+    assert!(
+      matches!(
+        res,
+        Err(TurboDieselError::Query(Error::QueryBuilderError(_)))
+      ),
+      "{filter:?}"
+    );
+  }
+  let changeset = DbUserUpdate {
+    name: Some("everyone".to_owned()),
+  };
+  assert_eq!(
+    db.update_all::<DbUser, _>(changeset, Confirm::Yes)
+      .await
+      .unwrap(),
+    3
+  );
+}
+
+/// A model skipping traits of `TurboModel`, implemented by hand instead,
+/// which only compiles when they're skipped.
+#[derive(
+  Clone, Debug, Queryable, Identifiable, FilterableColumns, TurboModel,
+)]
+#[diesel(table_name = users)]
+#[turbo(skip(Update, DelBy))]
+struct DbUserSkip {
+  id: String,
+  name: String,
+}
+
+impl DbModelUpdate for DbUserSkip {}
+
+#[ntex::test]
+async fn turbo_model_implements_the_unskipped_traits() {
+  let db = sqlite("turbo_model_implements_the_unskipped_traits");
+  db.create(&user("1", "a")).await.unwrap();
+  let filter =
+    GenericFilter::new().r#where("name", GenericClause::Eq("a".into()));
+  let users = db.read_by::<DbUserSkip>(&filter).await.unwrap();
+  assert_eq!(users[0].name, "a");
+  assert_eq!(db.count_by::<DbUserSkip>(&filter).await.unwrap(), 1);
+  assert_eq!(db.del_by_pk::<DbUserSkip, _>("1").await.unwrap(), 1);
+  assert_eq!(users[0].id, "1");
+}
+
+#[ntex::test]
+async fn ping_and_pool_state_report_the_pool() {
+  let config = PoolConfig {
+    max_size: Some(2),
+    min_idle: Some(2),
+    ..Default::default()
+  };
+  let path = sqlite_path("ping_and_pool_state_report_the_pool");
+  let db =
+    DbDriver::<SqliteConnection>::new_with_config(&path, config).unwrap();
+  db.ping().await.unwrap();
+  let conn = db.get_conn().unwrap();
+  let state = db.pool_state();
+  assert_eq!(state.connections, 2);
+  assert_eq!(state.idle_connections, 1);
+  drop(conn);
+  assert_eq!(db.pool_state().idle_connections, 2);
+}
+
+#[ntex::test]
+async fn warmup_opens_the_idle_connections() {
+  use diesel::r2d2::{ConnectionManager, Pool};
+
+  let config = PoolConfig {
+    max_size: Some(4),
+    min_idle: Some(0),
+    ..Default::default()
+  };
+  let path = sqlite_path("warmup_opens_the_idle_connections");
+  let replica = Pool::builder()
+    .max_size(2)
+    .min_idle(Some(0))
+    .build(ConnectionManager::<SqliteConnection>::new(&path))
+    .unwrap();
+  let db = DbDriver::<SqliteConnection>::new_with_config(&path, config)
+    .unwrap()
+    .with_replicas(vec![replica.clone()]);
+  assert_eq!(db.pool_state().idle_connections, 0);
+  db.warmup(3).await.unwrap();
+  let state = db.pool_state();
+  assert_eq!((state.connections, state.idle_connections), (3, 3));
+  // Capped by the max size of each pool
+  db.warmup(8).await.unwrap();
+  assert_eq!(db.pool_state().idle_connections, 4);
+  assert_eq!(replica.state().idle_connections, 2);
+  db.clone()
+    .shutdown(std::time::Duration::from_secs(1))
+    .await
+    .unwrap();
+  assert!(matches!(
+    db.warmup(1).await,
+    Err(TurboDieselError::Connection(_))
+  ));
+}
+
+#[ntex::test]
+async fn by_pk_methods_take_a_composite_key() {
+  let db = sqlite("by_pk_methods_take_a_composite_key");
+  db.create(&membership("1", "1", "owner")).await.unwrap();
+  db.create(&membership("1", "2", "member")).await.unwrap();
+  let pk = ("1".to_owned(), "2".to_owned());
+  let found = db
+    .read_by_pk::<DbMembership, _>(&pk)
+    .await
+    .unwrap()
+    .unwrap();
+  assert_eq!(found.role, "member");
+  let missing = ("2".to_owned(), "1".to_owned());
+  assert!(db
+    .read_by_pk::<DbMembership, _>(&missing)
+    .await
+    .unwrap()
+    .is_none());
+  assert_eq!(db.del_by_pk::<DbMembership, _>(&pk).await.unwrap(), 1);
+  assert_eq!(db.del_by_pk::<DbMembership, _>(&pk).await.unwrap(), 0);
+  let pk = ("1".to_owned(), "1".to_owned());
+  assert!(db
+    .read_by_pk::<DbMembership, _>(&pk)
+    .await
+    .unwrap()
+    .is_some());
+}
+
+#[ntex::test]
+async fn with_replicas_reads_from_the_replicas_in_turn() {
+  use diesel::r2d2::{ConnectionManager, Pool};
+
+  let mut replicas = Vec::new();
+  for name in ["a", "b"] {
+    let test = format!("with_replicas_reads_from_the_replicas_in_turn_{name}");
+    sqlite(&test).create(&user("r", name)).await.unwrap();
+    let path = std::env::temp_dir().join(format!("turbo_diesel_{test}.db"));
+    let manager =
+      ConnectionManager::<SqliteConnection>::new(path.to_str().unwrap());
+    replicas.push(Pool::builder().build(manager).unwrap());
+  }
+  let db = sqlite("with_replicas_reads_from_the_replicas_in_turn")
+    .with_replicas(replicas);
+  db.create(&user("1", "p")).await.unwrap();
+  db.create(&user("2", "p")).await.unwrap();
+  let mut names = Vec::new();
+  for _ in 0..4 {
+    let user = db.read_by_pk::<DbUser, _>("r").await.unwrap().unwrap();
+    names.push(user.name);
+  }
+  assert_eq!(names, ["a", "b", "a", "b"]);
+  let filter = GenericFilter::new().r#where("id", GenericClause::Ne("".into()));
+  assert_eq!(db.count_by::<DbUser>(&filter).await.unwrap(), 1);
+  assert!(db.read_by_pk::<DbUser, _>("1").await.unwrap().is_none());
+  // The writes and the transactions stay on the primary
+  assert_eq!(count_users(&db), 2);
+  let ids = db
+    .transaction(|conn| users::table.select(users::id).load::<String>(conn))
+    .await
+    .unwrap();
+  assert_eq!(ids, ["1", "2"]);
+}
+
+#[ntex::test]
+async fn with_schema_is_refused() {
+  let db = sqlite("with_schema_is_refused").with_schema("tenant");
+  let res = db.read_by::<DbUser>(&GenericFilter::new()).await;
+  assert!(
+    matches!(
+      res,
+      Err(TurboDieselError::Query(Error::QueryBuilderError(_)))
+    ),
+    "{res:?}"
+  );
+}
+
+#[ntex::test]
+async fn from_pool_shares_the_pool() {
+  use diesel::r2d2::{ConnectionManager, Pool};
+
+  let path = sqlite_path("from_pool_shares_the_pool");
+  // Create the tables at the path
+  sqlite("from_pool_shares_the_pool");
+  let pool = Pool::builder()
+    .max_size(1)
+    .build(ConnectionManager::<SqliteConnection>::new(&path))
+    .unwrap();
+  let db = DbDriver::from_pool(pool.clone());
+  db.create(&user("1", "a")).await.unwrap();
+  assert_eq!(
+    db.read_by_pk::<DbUser, _>("1").await.unwrap().unwrap().name,
+    "a"
+  );
+  let count = users::table
+    .count()
+    .get_result::<i64>(&mut pool.get().unwrap())
+    .unwrap();
+  assert_eq!(count, 1);
+  assert_eq!(db.pool_state().connections, 1);
+}
+
+#[ntex::test]
+async fn create_and_find_reads_the_row_back() {
+  let db = sqlite("create_and_find_reads_the_row_back");
+  let created = db.create_and_find(&user("1", "a")).await.unwrap();
+  assert_eq!((created.id.as_str(), created.name.as_str()), ("1", "a"));
+  let res = db.create_and_find(&user("1", "b")).await;
+  assert!(res.is_err());
+  assert_eq!(count_users(&db), 1);
+}
+
+#[ntex::test]
+async fn paginate_counts_every_matching_row() {
+  let db = sqlite("paginate_counts_every_matching_row");
+  let users = (0..25)
+    .map(|i| user(&format!("{i:02}"), if i < 20 { "a" } else { "b" }))
+    .collect::<Vec<_>>();
+  db.create_many(users, None).await.unwrap();
+  let filter = GenericFilter::new()
+    .r#where("name", GenericClause::Eq("a".into()))
+    .order_by("id", OrderDirection::Asc)
+    .limit(8)
+    .offset(16);
+  let page = db.paginate::<DbUser>(&filter).await.unwrap();
+  assert_eq!(page.total, 20);
+  assert_eq!((page.limit, page.offset), (8, 16));
+  let ids = page
+    .items
+    .iter()
+    .map(|user| user.id.as_str())
+    .collect::<Vec<_>>();
+  assert_eq!(ids, ["16", "17", "18", "19"]);
+  let page = db.paginate::<DbUser>(&GenericFilter::new()).await.unwrap();
+  assert_eq!((page.total, page.items.len()), (25, 25));
+  assert_eq!((page.limit, page.offset), (100, 0));
+}
+
+#[ntex::test]
+async fn typed_values_compare_with_the_column_type() {
+  let db = sqlite("typed_values_compare_with_the_column_type");
+  db.create_many(items(), None).await.unwrap();
+  let filtered = [
+    ("quantity", GenericClause::Eq(2.into()), 1),
+    ("quantity", GenericClause::Eq("2".into()), 1),
+    ("quantity", GenericClause::In(vec![1.into(), 3.into()]), 2),
+    ("price", GenericClause::Gt(1.5.into()), 2),
+    ("price", GenericClause::Le(2.into()), 2),
+    ("active", GenericClause::Eq(true.into()), 2),
+    ("note", GenericClause::Eq(GenericValue::Null), 2),
+    ("note", GenericClause::Ne(GenericValue::Null), 1),
+  ];
+  for (column, clause, expected) in filtered {
+    let filter = GenericFilter::new().r#where(column, clause.clone());
+    let count = db.count_by::<DbItem>(&filter).await.unwrap();
+    assert_eq!(count, expected, "{column} {clause:?}");
+  }
+  for clause in [
+    GenericClause::Eq("two".into()),
+    GenericClause::Eq(true.into()),
+  ] {
+    let filter = GenericFilter::new().r#where("quantity", clause);
+    let res = db.count_by::<DbItem>(&filter).await;
+    assert!(
+      matches!(
+        res,
+        Err(TurboDieselError::Query(Error::QueryBuilderError(_)))
+      ),
+      "{res:?}"
+    );
+  }
+}
+
+#[ntex::test]
+async fn create_fast_returns_the_inserted_count() {
+  let db = sqlite("create_fast_returns_the_inserted_count");
+  assert_eq!(db.create_fast(&user("1", "a")).await.unwrap(), 1);
+  assert!(db.create_fast(&user("1", "b")).await.is_err());
+  let found = db.read_by_pk::<DbUser, _>("1").await.unwrap().unwrap();
+  assert_eq!(found.name, "a");
+}
+
+#[ntex::test]
+async fn create_or_ignore_skips_the_existing_row() {
+  let db = sqlite("create_or_ignore_skips_the_existing_row");
+  let created = db.create_or_ignore(&user("1", "a")).await.unwrap();
+  assert_eq!(created.map(|user| user.name).as_deref(), Some("a"));
+  let ignored = db.create_or_ignore(&user("1", "b")).await.unwrap();
+  assert!(ignored.is_none());
+  let found = db.read_by_pk::<DbUser, _>("1").await.unwrap().unwrap();
+  assert_eq!(found.name, "a");
+  assert_eq!(count_users(&db), 1);
+}
+
+#[ntex::test]
+async fn explain_sql_renders_the_queries_of_the_filter() {
+  let db = sqlite("explain_sql_renders_the_queries_of_the_filter");
+  let filter = GenericFilter::new()
+    .r#where("name", GenericClause::Eq("a".into()))
+    .order_by("id", OrderDirection::Desc);
+  assert_eq!(
+    db.explain_sql::<DbUser>(&filter).unwrap(),
+    "SELECT `users`.`id`, `users`.`name` FROM `users` \
+     WHERE (`users`.`name` = ?) ORDER BY `users`.`id` DESC  LIMIT ? \
+     -- binds: [\"a\", 100]"
+  );
+  assert_eq!(
+    db.explain_del_sql::<DbUser>(&filter).unwrap(),
+    "DELETE  FROM `users` WHERE (`users`.`name` = ?) -- binds: [\"a\"]"
+  );
+  assert_eq!(count_users(&db), 0);
+}
+
+#[ntex::test]
+async fn postgres_clauses_are_refused_on_sqlite() {
+  let db = sqlite("postgres_clauses_are_refused_on_sqlite");
+  let clauses = [
+    (
+      "JsonContains",
+      GenericClause::JsonContains(serde_json::json!({ "a": 1 })),
+    ),
+    ("HasKey", GenericClause::HasKey("a".to_owned())),
+    (
+      "JsonPathExists",
+      GenericClause::JsonPathExists(vec!["a".to_owned()]),
+    ),
+    (
+      "ArrayOverlaps",
+      GenericClause::ArrayOverlaps(vec!["a".into()]),
+    ),
+    ("FullText", GenericClause::FullText("a".to_owned(), None)),
+  ];
+  for (name, clause) in clauses {
+    // Refused before the column is looked up, even an unknown one
+    for column in ["name", "unknown"] {
+      let filter = GenericFilter::new().r#where(column, clause.clone());
+      let res = db.read_by::<DbUser>(&filter).await;
+      let Err(TurboDieselError::UnsupportedOnBackend { clause, backend }) = res
+      else {
+        panic!("{name} on {column}: {res:?}");
+      };
+      assert_eq!((clause, backend), (name, "SQLite"));
+    }
+  }
+  let filter =
+    GenericFilter::new().r#where("name", GenericClause::HasKey("a".to_owned()));
+  let Err(err) = db.del_by::<DbUser>(&filter).await else {
+    panic!("HasKey deleted on SQLite");
+  };
+  assert_eq!(err.to_string(), "HasKey is not supported on SQLite");
+}
+
+#[ntex::test]
+async fn soft_deleted_rows_are_skipped_until_restored() {
+  let db = sqlite("soft_deleted_rows_are_skipped_until_restored");
+  db.create(&note("1", "a")).await.unwrap();
+  db.create(&note("2", "b")).await.unwrap();
+  assert_eq!(db.soft_del_by_pk::<DbNote, _>("1").await.unwrap(), 1);
+  assert_eq!(db.soft_del_by_pk::<DbNote, _>("3").await.unwrap(), 0);
+  let every = GenericFilter::new();
+  let notes = db.read_by::<DbNote>(&every).await.unwrap();
+  assert_eq!(notes.len(), 1);
+  assert_eq!(notes[0].id, "2");
+  assert_eq!(db.count_by::<DbNote>(&every).await.unwrap(), 1);
+  let deleted = every.clone().with_deleted();
+  assert_eq!(db.count_by::<DbNote>(&deleted).await.unwrap(), 2);
+  let found = db.read_by_pk::<DbNote, _>("1").await.unwrap().unwrap();
+  assert!(found.deleted_at.is_some());
+  assert_eq!(db.restore_by_pk::<DbNote, _>("1").await.unwrap(), 1);
+  assert_eq!(db.count_by::<DbNote>(&every).await.unwrap(), 2);
+  let filter =
+    GenericFilter::new().r#where("body", GenericClause::Eq("b".into()));
+  assert_eq!(db.soft_del_by::<DbNote>(&filter).await.unwrap(), 1);
+  assert!(db.soft_del_by::<DbNote>(&every).await.is_err());
+  let notes = db.read_by::<DbNote>(&every).await.unwrap();
+  assert_eq!(notes.len(), 1);
+  assert_eq!(notes[0].id, "1");
+}
+
+#[ntex::test]
+async fn timestamps_are_set_on_create_and_moved_on_update() {
+  let db = sqlite("timestamps_are_set_on_create_and_moved_on_update");
+  let post = DbPostCreate {
+    id: "1".to_owned(),
+    title: "a".to_owned(),
+  };
+  let created = db.create_timestamped::<DbPost, _>(&post).await.unwrap();
+  assert!(!created.created_at.is_empty());
+  assert_eq!(created.created_at, created.updated_at);
+  // Move both in the past, `now` has a precision of one second on SQLite
+  let past = "2000-01-01 00:00:00";
+  diesel::update(posts::table)
+    .set((posts::created_at.eq(past), posts::updated_at.eq(past)))
+    .execute(&mut *db.get_conn().unwrap())
+    .unwrap();
+  let title = |title: &str| DbPostUpdate {
+    title: Some(title.to_owned()),
+  };
+  let updated = db
+    .update_timestamped_by_pk::<DbPost, _, _>("1", title("b"))
+    .await
+    .unwrap();
+  assert_eq!(updated.title, "b");
+  assert_eq!(updated.created_at, past);
+  assert!(updated.updated_at.as_str() > past);
+  diesel::update(posts::table)
+    .set(posts::updated_at.eq(past))
+    .execute(&mut *db.get_conn().unwrap())
+    .unwrap();
+  let filter =
+    GenericFilter::new().r#where("title", GenericClause::Eq("b".into()));
+  let count = db
+    .update_timestamped_by::<DbPost, _>(&filter, title("c"))
+    .await
+    .unwrap();
+  assert_eq!(count, 1);
+  let found = db.read_by_pk::<DbPost, _>("1").await.unwrap().unwrap();
+  assert_eq!(
+    (found.title.as_str(), found.created_at.as_str()),
+    ("c", past)
+  );
+  assert!(found.updated_at.as_str() > past);
+}
+
+#[ntex::test]
+async fn update_versioned_by_pk_refuses_a_stale_version() {
+  let db = sqlite("update_versioned_by_pk_refuses_a_stale_version");
+  let read = db.create(&document("1", "a")).await.unwrap();
+  let body = |body: &str| DbDocumentUpdate {
+    body: Some(body.to_owned()),
+  };
+  let updated = db
+    .update_versioned_by_pk::<DbDocument, _, _>("1", read.version, body("b"))
+    .await
+    .unwrap();
+  assert_eq!((updated.body.as_str(), updated.version), ("b", 1));
+  let stale = db
+    .update_versioned_by_pk::<DbDocument, _, _>("1", read.version, body("c"))
+    .await;
+  assert!(
+    matches!(stale, Err(TurboDieselError::Conflict)),
+    "{stale:?}"
+  );
+  let missing = db
+    .update_versioned_by_pk::<DbDocument, _, _>("2", 0, body("c"))
+    .await;
+  assert!(matches!(missing, Err(TurboDieselError::Conflict)));
+  let found = db.read_by_pk::<DbDocument, _>("1").await.unwrap().unwrap();
+  assert_eq!((found.body.as_str(), found.version), ("b", 1));
+}
+
+#[ntex::test]
+async fn raw_sql_binds_the_values_in_order() {
+  let db = sqlite("raw_sql_binds_the_values_in_order");
+  db.create(&user("1", "a")).await.unwrap();
+  db.create(&user("2", "b")).await.unwrap();
+  let sql = "UPDATE users SET name = ? WHERE id = ?";
+  let count = db
+    .execute_sql(sql, vec!["raw".into(), "1".into()])
+    .await
+    .unwrap();
+  assert_eq!(count, 1);
+  // A quote in a bound value can't end the string
+  let sql = "SELECT COUNT(*) AS count FROM users WHERE name = ? OR name = ?";
+  let binds = vec!["raw".into(), "x' OR '1' = '1".into()];
+  let rows = db.query_sql::<NameCount>(sql, binds).await.unwrap();
+  assert_eq!(rows[0].count, 1);
+}
+
+#[ntex::test]
+async fn read_by_as_loads_only_the_named_columns() {
+  use diesel::sql_types::Text;
+
+  let db = sqlite("read_by_as_loads_only_the_named_columns");
+  db.create(&user("1", "a")).await.unwrap();
+  db.create(&user("2", "b")).await.unwrap();
+  let filter = GenericFilter::new().order_by("id", OrderDirection::Desc);
+  let ids = db
+    .read_by_as::<DbUser, (String,), (Text,)>(&filter, &["id"])
+    .await
+    .unwrap();
+  assert_eq!(ids, [("2".to_owned(),), ("1".to_owned(),)]);
+  let filter =
+    GenericFilter::new().r#where("id", GenericClause::Eq("1".into()));
+  let rows = db
+    .read_by_as::<DbUser, (String, String), (Text, Text)>(
+      &filter,
+      &["name", "id"],
+    )
+    .await
+    .unwrap();
+  assert_eq!(rows, [("a".to_owned(), "1".to_owned())]);
+  let unknown = db
+    .read_by_as::<DbUser, (String,), (Text,)>(&filter, &["email"])
+    .await;
+  assert!(unknown.is_err());
+}
+
+#[ntex::test]
+async fn read_by_into_maps_the_rows_into_the_struct() {
+  let db = sqlite("read_by_into_maps_the_rows_into_the_struct");
+  db.create(&user("1", "a")).await.unwrap();
+  db.create(&user("2", "b")).await.unwrap();
+  db.create(&user("3", "c")).await.unwrap();
+  let filter = GenericFilter::new()
+    .r#where("id", GenericClause::Ne("2".into()))
+    .order_by("id", OrderDirection::Desc);
+  let names = db.read_by_into::<DbUser, UserName>(&filter).await.unwrap();
+  let name = |name: &str| UserName {
+    name: name.to_owned(),
+  };
+  assert_eq!(names, [name("c"), name("a")]);
+}
+
+#[ntex::test]
+async fn paginate_keyset_walks_the_pages_across_inserts() {
+  let db = sqlite("paginate_keyset_walks_the_pages_across_inserts");
+  for (id, name) in [("1", "c"), ("2", "a"), ("3", "b"), ("4", "a")] {
+    db.create(&user(id, name)).await.unwrap();
+  }
+  let filter = GenericFilter::new().order_by("name", OrderDirection::Asc);
+  let ids = |page: &KeysetPage<DbUser>| {
+    page
+      .items
+      .iter()
+      .map(|user| user.id.clone())
+      .collect::<Vec<_>>()
+  };
+  let page = db
+    .paginate_keyset::<DbUser>(&filter, None, 2)
+    .await
+    .unwrap();
+  assert_eq!(ids(&page), ["2", "4"]);
+  // A row before the cursor doesn't shift the next page
+  db.create(&user("0", "a")).await.unwrap();
+  let after = Cursor::decode(&page.next.unwrap().encode());
+  assert!(after.is_some());
+  let page = db
+    .paginate_keyset::<DbUser>(&filter, after, 2)
+    .await
+    .unwrap();
+  assert_eq!(ids(&page), ["3", "1"]);
+  assert!(page.next.is_none());
+  assert!(Cursor::decode("not a cursor").is_none());
+}
+
+#[ntex::test]
+async fn read_stream_reads_more_rows_than_a_chunk() {
+  use futures_util::StreamExt;
+
+  let db = sqlite("read_stream_reads_more_rows_than_a_chunk");
+  let users = (0..10).map(|i| user(&format!("{i:02}"), "a")).collect();
+  db.create_many(users, None).await.unwrap();
+  db.create(&user("10", "b")).await.unwrap();
+  let filter = GenericFilter::new()
+    .r#where("name", GenericClause::Eq("a".into()))
+    .order_by("id", OrderDirection::Desc)
+    .limit(2);
+  let stream = db.read_stream::<DbUser>(&filter, Some(3));
+  let ids = stream
+    .map(|user| user.unwrap().id)
+    .collect::<Vec<_>>()
+    .await;
+  let expected = (0..10).rev().map(|i| format!("{i:02}")).collect::<Vec<_>>();
+  assert_eq!(ids, expected);
+}
+
+#[ntex::test]
+async fn paginate_keyset_refuses_float_and_bool_keys() {
+  let db = sqlite("paginate_keyset_refuses_float_and_bool_keys");
+  db.create_many(items(), None).await.unwrap();
+  for column in ["price", "active"] {
+    let filter = GenericFilter::new().order_by(column, OrderDirection::Asc);
+    let res = db.paginate_keyset::<DbItem>(&filter, None, 2).await;
+    let Err(err) = res else {
+      panic!("{column} ordered a keyset page");
+    };
+    let message = format!("the column {column} can't order a keyset page");
+    assert!(err.to_string().contains(&message), "{err}");
+  }
+  let filter = GenericFilter::new().order_by("quantity", OrderDirection::Desc);
+  let page = db
+    .paginate_keyset::<DbItem>(&filter, None, 2)
+    .await
+    .unwrap();
+  let ids = page.items.iter().map(|item| item.id).collect::<Vec<_>>();
+  assert_eq!(ids, [3, 2]);
+}
+
+#[ntex::test]
+async fn paginate_keyset_orders_by_keys_of_opposite_directions() {
+  let db = sqlite("paginate_keyset_orders_by_keys_of_opposite_directions");
+  for (id, name) in [("1", "c"), ("2", "a"), ("3", "b"), ("4", "a"), ("5", "b")]
+  {
+    db.create(&user(id, name)).await.unwrap();
+  }
+  let filter = GenericFilter::new()
+    .order_by("name", OrderDirection::Desc)
+    .order_by("id", OrderDirection::Asc);
+  let mut pages = Vec::new();
+  let mut after = None;
+  loop {
+    let page = db
+      .paginate_keyset::<DbUser>(&filter, after.take(), 2)
+      .await
+      .unwrap();
+    pages.push(
+      page
+        .items
+        .into_iter()
+        .map(|user| user.id)
+        .collect::<Vec<_>>(),
+    );
+    match page.next {
+      Some(next) => after = Some(next),
+      None => break,
+    }
+  }
+  assert_eq!(pages, [vec!["1", "3"], vec!["5", "2"], vec!["4"]]);
+}
+
+#[ntex::test]
+async fn paginate_keyset_refuses_a_nullable_key() {
+  let db = sqlite("paginate_keyset_refuses_a_nullable_key");
+  db.create_many(items(), None).await.unwrap();
+  let filter = GenericFilter::new()
+    .order_by("quantity", OrderDirection::Asc)
+    .order_by("note", OrderDirection::Desc);
+  let res = db.paginate_keyset::<DbItem>(&filter, None, 2).await;
+  let Err(err) = res else {
+    panic!("note ordered a keyset page");
+  };
+  let message = "the nullable column note can't order a keyset page";
+  assert!(err.to_string().contains(message), "{err}");
+}
+
+#[ntex::test]
+async fn transaction_with_isolation_ignores_the_level() {
+  let db = sqlite("transaction_with_isolation_ignores_the_level");
+  db.transaction_with_isolation(IsolationLevel::ReadCommitted, |conn| {
+    diesel::insert_into(users::table)
+      .values(user("1", "a"))
+      .execute(conn)
+  })
+  .await
+  .unwrap();
+  assert_eq!(count_users(&db), 1);
+}
+
+#[ntex::test]
+async fn aggregate_applies_the_function_on_the_matching_rows() {
+  let db = sqlite("aggregate_applies_the_function_on_the_matching_rows");
+  db.create_many(items(), None).await.unwrap();
+  let active =
+    GenericFilter::new().r#where("active", GenericClause::Eq(true.into()));
+  let aggregate =
+    |function, column| db.aggregate::<DbItem, i64>(&active, function, column);
+  assert_eq!(
+    aggregate(AggregateFn::Sum, "quantity").await.unwrap(),
+    Some(4)
+  );
+  assert_eq!(
+    aggregate(AggregateFn::Min, "quantity").await.unwrap(),
+    Some(1)
+  );
+  assert_eq!(aggregate(AggregateFn::Max, "id").await.unwrap(), Some(3));
+  let avg = db
+    .aggregate::<DbItem, f64>(&active, AggregateFn::Avg, "price")
+    .await
+    .unwrap();
+  assert_eq!(avg, Some(2.0));
+  let none = GenericFilter::new().r#where("id", GenericClause::Gt(10.into()));
+  let sum = db
+    .aggregate::<DbItem, i64>(&none, AggregateFn::Sum, "quantity")
+    .await
+    .unwrap();
+  assert_eq!(sum, None);
+}
+
+#[ntex::test]
+async fn create_of_a_duplicate_key_is_a_unique_violation() {
+  let db = sqlite("create_of_a_duplicate_key_is_a_unique_violation");
+  db.create(&user("1", "a")).await.unwrap();
+  let res = db.create(&user("1", "b")).await;
+  let Err(TurboDieselError::UniqueViolation(violation)) = res else {
+    panic!("expected a unique violation, got {res:?}");
+  };
+  assert_eq!(violation.constraint, None);
+  assert!(violation.message.contains("users.id"), "{violation:?}");
+}
+
+#[ntex::test]
+async fn shutdown_waits_for_the_connections_in_use() {
+  use std::time::Duration;
+
+  let db = sqlite("shutdown_waits_for_the_connections_in_use");
+  let clone = db.clone();
+  let conn = db.get_conn().unwrap();
+  let res = db.clone().shutdown(Duration::from_millis(50)).await;
+  assert!(
+    matches!(res, Err(TurboDieselError::Connection(_))),
+    "{res:?}"
+  );
+  assert!(matches!(
+    clone.read_by_pk::<DbUser, _>("1").await,
+    Err(TurboDieselError::Connection(_))
+  ));
+  // Giving the connection back lets the shutdown finish
+  let release = std::thread::spawn(move || {
+    std::thread::sleep(Duration::from_millis(50));
+    drop(conn);
+  });
+  db.shutdown(Duration::from_secs(5)).await.unwrap();
+  release.join().unwrap();
+}
+
+#[ntex::test]
+async fn statement_timeout_bounds_the_wait_for_a_lock() {
+  use diesel::connection::SimpleConnection;
+
+  let path = sqlite_path("statement_timeout_bounds_the_wait_for_a_lock");
+  let config = PoolConfig {
+    statement_timeout: Some(std::time::Duration::from_millis(50)),
+    ..Default::default()
+  };
+  let db =
+    DbDriver::<SqliteConnection>::new_with_config(&path, config).unwrap();
+  let mut conn = db.get_conn().unwrap();
+  conn
+    .batch_execute(
+      "CREATE TABLE users (id TEXT PRIMARY KEY NOT NULL, name TEXT NOT NULL);
+       BEGIN EXCLUSIVE",
+    )
+    .unwrap();
+  let started = std::time::Instant::now();
+  let res = db.create(&user("1", "a")).await;
+  // A locked database isn't a timeout of the statement
+  assert!(matches!(res, Err(TurboDieselError::Query(_))), "{res:?}");
+  assert!(started.elapsed() < std::time::Duration::from_secs(5));
+  conn.batch_execute("ROLLBACK").unwrap();
+}
+
+#[ntex::test]
+async fn find_or_create_creates_only_a_missing_row() {
+  let db = sqlite("find_or_create_creates_only_a_missing_row");
+  let filter =
+    GenericFilter::new().r#where("name", GenericClause::Eq("a".into()));
+  let (row, created) =
+    db.find_or_create(&filter, user("1", "a")).await.unwrap();
+  assert_eq!((row.id.as_str(), created), ("1", true));
+  let (row, created) =
+    db.find_or_create(&filter, user("2", "a")).await.unwrap();
+  assert_eq!((row.id.as_str(), created), ("1", false));
+  assert_eq!(count_users(&db), 1);
+}
+
+#[ntex::test]
+async fn upsert_many_updates_the_existing_rows() {
+  let db = sqlite("upsert_many_updates_the_existing_rows");
+  db.create(&user("1", "a")).await.unwrap();
+  db.create(&user("2", "b")).await.unwrap();
+  let items = vec![user("2", "updated"), user("3", "new")];
+  let upserted = db.upsert_many(items, None).await.unwrap();
+  assert_eq!(upserted.len(), 2);
+  let filter = GenericFilter::new().order_by("id", OrderDirection::Asc);
+  let users = db.read_by::<DbUser>(&filter).await.unwrap();
+  let names = users
+    .iter()
+    .map(|user| user.name.as_str())
+    .collect::<Vec<_>>();
+  assert_eq!(names, ["a", "updated", "new"]);
+}
+
+#[ntex::test]
+async fn concurrent_increments_add_every_delta() {
+  let path = sqlite_path("concurrent_increments_add_every_delta");
+  // Wait for the other writers instead of failing on the locked database
+  let config = PoolConfig {
+    max_size: Some(4),
+    statement_timeout: Some(std::time::Duration::from_secs(5)),
+    ..Default::default()
+  };
+  let db =
+    DbDriver::<SqliteConnection>::new_with_config(&path, config).unwrap();
+  db.execute_sql(
+    "CREATE TABLE documents (
+       id TEXT PRIMARY KEY NOT NULL,
+       body TEXT NOT NULL,
+       version INTEGER NOT NULL
+     )",
+    Vec::new(),
+  )
+  .await
+  .unwrap();
+  db.create(&document("1", "a")).await.unwrap();
+  let increments =
+    (0..20).map(|_| db.increment::<DbDocument, _>("1", "version", 1));
+  for res in ntex::util::join_all(increments).await {
+    res.unwrap();
+  }
+  let document = db
+    .increment::<DbDocument, _>("1", "version", -5)
+    .await
+    .unwrap();
+  assert_eq!(document.version, 15);
+  let res = db.increment::<DbDocument, _>("1", "body", 1).await;
+  assert!(res.is_err());
+  let res = db.increment::<DbDocument, _>("2", "version", 1).await;
+  assert!(matches!(res, Err(TurboDieselError::NotFound)), "{res:?}");
+}
+
+#[ntex::test]
+async fn into_filter_reads_the_rows_of_the_query() {
+  let db = sqlite("into_filter_reads_the_rows_of_the_query");
+  db.create(&user("1", "test")).await.unwrap();
+  db.create(&user("2", "team")).await.unwrap();
+  db.create(&user("3", "other")).await.unwrap();
+  let query = UserQuery {
+    name: Some("te".to_owned()),
+    page: 1,
+    ..Default::default()
+  };
+  let offset = query.page * 10;
+  let filter = query.into_filter();
+  let mut ids = db
+    .read_by::<DbUser>(&filter)
+    .await
+    .unwrap()
+    .into_iter()
+    .map(|user| user.id)
+    .collect::<Vec<_>>();
+  ids.sort();
+  assert_eq!(ids, ["1", "2"]);
+  let filter = filter.offset(offset);
+  assert!(db.read_by::<DbUser>(&filter).await.unwrap().is_empty());
+  let filter = UserQuery::default().into_filter();
+  assert_eq!(db.read_by::<DbUser>(&filter).await.unwrap().len(), 3);
+}
+
+#[ntex::test]
+async fn exists_related_keeps_the_rows_having_a_related_row() {
+  let db = sqlite("exists_related_keeps_the_rows_having_a_related_row");
+  for (id, name) in [("1", "a"), ("2", "b"), ("3", "c")] {
+    db.create(&user(id, name)).await.unwrap();
+  }
+  for (id, user_id) in [("o1", "1"), ("o2", "1"), ("o3", "3")] {
+    db.create(&order(id, user_id)).await.unwrap();
+  }
+  let orders = GenericClause::ExistsRelated("orders".into(), "user_id".into());
+  let filter = GenericFilter::new()
+    .r#where("id", orders)
+    .order_by("id", OrderDirection::Asc);
+  let ids = |users: Vec<DbUser>| {
+    users.into_iter().map(|user| user.id).collect::<Vec<_>>()
+  };
+  let users = db.read_by::<DbUser>(&filter).await.unwrap();
+  assert_eq!(ids(users), ["1", "3"]);
+  let users = db.read_by::<DbUser>(&!filter.clone()).await.unwrap();
+  assert_eq!(ids(users), ["2"]);
+  assert_eq!(db.del_by::<DbUser>(&filter).await.unwrap(), 2);
+  assert_eq!(count_users(&db), 1);
+}
+
+#[ntex::test]
+async fn group_count_counts_the_rows_per_status() {
+  let db = sqlite("group_count_counts_the_rows_per_status");
+  let accounts = [
+    ("1", Some("active")),
+    ("2", Some("banned")),
+    ("3", Some("active")),
+    ("4", None),
+    ("5", Some("active")),
+    ("6", Some("banned")),
+  ];
+  for (id, status) in accounts {
+    db.create(&account(id, status)).await.unwrap();
+  }
+  let every = GenericFilter::new();
+  let groups = db.group_count::<DbAccount>(&every, "status").await.unwrap();
+  assert_eq!(
+    groups,
+    [
+      (GenericValue::Text("active".to_owned()), 3),
+      (GenericValue::Text("banned".to_owned()), 2),
+      (GenericValue::Null, 1),
+    ]
+  );
+  // The limit and the order of the filter don't cut the groups
+  let filter = GenericFilter::new()
+    .r#where("id", GenericClause::Ne("1".into()))
+    .order_by("id", OrderDirection::Asc)
+    .limit(1);
+  let groups = db
+    .group_count::<DbAccount>(&filter, "status")
+    .await
+    .unwrap();
+  assert_eq!(
+    groups,
+    [
+      (GenericValue::Text("active".to_owned()), 2),
+      (GenericValue::Text("banned".to_owned()), 2),
+      (GenericValue::Null, 1),
+    ]
+  );
+  let distinct = db.count_distinct::<DbAccount>(&every, "status").await;
+  assert_eq!(distinct.unwrap(), 2);
+  let filter =
+    GenericFilter::new().r#where("id", GenericClause::Gt("4".into()));
+  let distinct = db.count_distinct::<DbAccount>(&filter, "status").await;
+  assert_eq!(distinct.unwrap(), 2);
+  let res = db.group_count::<DbAccount>(&every, "unknown").await;
+  assert!(matches!(
+    res,
+    Err(TurboDieselError::Query(Error::QueryBuilderError(_)))
+  ));
+}
+
+#[ntex::test]
+async fn a_missing_row_is_none_or_not_found() {
+  let db = sqlite("a_missing_row_is_none_or_not_found");
+  db.create(&user("1", "a")).await.unwrap();
+  let found = db.read_by_pk_strict::<DbUser, _>("1").await.unwrap();
+  assert_eq!(found.name, "a");
+  assert!(db.read_by_pk::<DbUser, _>("2").await.unwrap().is_none());
+  let res = db.read_by_pk_strict::<DbUser, _>("2").await;
+  assert!(matches!(res, Err(TurboDieselError::NotFound)), "{res:?}");
+  assert_eq!(db.del_by_pk::<DbUser, _>("2").await.unwrap(), 0);
+  let res = db.del_by_pk_strict::<DbUser, _>("2").await;
+  assert!(matches!(res, Err(TurboDieselError::NotFound)), "{res:?}");
+}
+
+#[ntex::test]
+async fn count_by_column_skips_the_null_values() {
+  let db = sqlite("count_by_column_skips_the_null_values");
+  db.create_many(items(), None).await.unwrap();
+  let every = GenericFilter::new();
+  assert_eq!(db.count_by::<DbItem>(&every).await.unwrap(), 3);
+  let count = db.count_by_column::<DbItem>(&every, "note").await;
+  assert_eq!(count.unwrap(), 1);
+  let count = db.count_by_column::<DbItem>(&every, "id").await;
+  assert_eq!(count.unwrap(), 3);
+  let filter = GenericFilter::new().r#where("id", GenericClause::Gt(1.into()));
+  let count = db.count_by_column::<DbItem>(&filter, "note").await;
+  assert_eq!(count.unwrap(), 0);
+  let res = db.count_by_column::<DbItem>(&every, "unknown").await;
+  assert!(matches!(
+    res,
+    Err(TurboDieselError::Query(Error::QueryBuilderError(_)))
+  ));
+}
+
+#[ntex::test]
+async fn set_field_archives_the_matched_rows() {
+  let db = sqlite("set_field_archives_the_matched_rows");
+  let accounts = [
+    ("1", Some("active")),
+    ("2", Some("banned")),
+    ("3", None),
+    ("4", Some("banned")),
+  ];
+  for (id, status) in accounts {
+    db.create(&account(id, status)).await.unwrap();
+  }
+  let banned =
+    GenericFilter::new().r#where("status", GenericClause::Eq("banned".into()));
+  let archived = db
+    .set_field::<DbAccount>(&banned, "status", "archived".into())
+    .await
+    .unwrap();
+  assert_eq!(archived, 2);
+  let every = GenericFilter::new().order_by("id", OrderDirection::Asc);
+  let statuses = db
+    .read_by::<DbAccount>(&every)
+    .await
+    .unwrap()
+    .into_iter()
+    .map(|account| account.status)
+    .collect::<Vec<_>>();
+  assert_eq!(
+    statuses,
+    [
+      Some("active".to_owned()),
+      Some("archived".to_owned()),
+      None,
+      Some("archived".to_owned()),
+    ]
+  );
+  let filter =
+    GenericFilter::new().r#where("id", GenericClause::Eq("1".into()));
+  let cleared = db
+    .set_field::<DbAccount>(&filter, "status", GenericValue::Null)
+    .await
+    .unwrap();
+  assert_eq!(cleared, 1);
+  let account = db.read_by_pk::<DbAccount, _>("1").await.unwrap().unwrap();
+  assert_eq!(account.status, None);
+  // Refused without a where clause, the value is checked against the column
+  let res = db
+    .set_field::<DbAccount>(&GenericFilter::new(), "status", "x".into())
+    .await;
+  assert!(res.is_err());
+  db.create_many(items(), None).await.unwrap();
+  let filter = GenericFilter::new().r#where("id", GenericClause::Gt(1.into()));
+  let res = db
+    .set_field::<DbItem>(&filter, "quantity", "many".into())
+    .await;
+  assert!(matches!(
+    res,
+    Err(TurboDieselError::Query(Error::QueryBuilderError(_)))
+  ));
+  let res = db.set_field::<DbItem>(&filter, "unknown", 1.into()).await;
+  assert!(matches!(
+    res,
+    Err(TurboDieselError::Query(Error::QueryBuilderError(_)))
+  ));
+  let updated = db
+    .set_field::<DbItem>(&filter, "quantity", "7".into())
+    .await;
+  assert_eq!(updated.unwrap(), 2);
+  let item = db.read_by_pk::<DbItem, _>(&3).await.unwrap().unwrap();
+  assert_eq!(item.quantity, 7);
+  assert_eq!(db.count_by::<DbAccount>(&banned).await.unwrap(), 0);
+}
+
+#[ntex::test]
+async fn del_by_returning_returns_the_deleted_rows() {
+  let db = sqlite("del_by_returning_returns_the_deleted_rows");
+  for (id, name) in [("1", "ab"), ("2", "b"), ("3", "ac")] {
+    db.create(&user(id, name)).await.unwrap();
+  }
+  let filter =
+    GenericFilter::new().r#where("name", GenericClause::StartsWith("a".into()));
+  let existing = db.read_by::<DbUser>(&filter).await.unwrap();
+  let mut removed = db.del_by_returning::<DbUser>(&filter).await.unwrap();
+  removed.sort_by(|a, b| a.id.cmp(&b.id));
+  let rows = |users: &[DbUser]| {
+    users
+      .iter()
+      .map(|user| (user.id.clone(), user.name.clone()))
+      .collect::<Vec<_>>()
+  };
+  assert_eq!(rows(&removed), rows(&existing));
+  assert_eq!(
+    rows(&removed),
+    [
+      ("1".to_owned(), "ab".to_owned()),
+      ("3".to_owned(), "ac".to_owned())
+    ]
+  );
+  assert!(db
+    .del_by_returning::<DbUser>(&filter)
+    .await
+    .unwrap()
+    .is_empty());
+  for filter in [GenericFilter::new(), !GenericFilter::new()] {
+    let res = db.del_by_returning::<DbUser>(&filter).await;
+    assert!(matches!(
+      res,
+      Err(TurboDieselError::Query(Error::QueryBuilderError(_)))
+    ));
+  }
+  assert_eq!(count_users(&db), 1);
+}
+
+#[ntex::test]
+async fn update_by_returning_returns_the_updated_rows() {
+  let db = sqlite("update_by_returning_returns_the_updated_rows");
+  for (id, name) in [("1", "ab"), ("2", "b"), ("3", "ac")] {
+    db.create(&user(id, name)).await.unwrap();
+  }
+  let filter =
+    GenericFilter::new().r#where("name", GenericClause::StartsWith("a".into()));
+  let changeset = DbUserUpdate {
+    name: Some("renamed".to_owned()),
+  };
+  let mut updated = db
+    .update_by_returning::<DbUser, _>(&filter, changeset.clone())
+    .await
+    .unwrap();
+  updated.sort_by(|a, b| a.id.cmp(&b.id));
+  let rows = updated
+    .iter()
+    .map(|user| (user.id.as_str(), user.name.as_str()))
+    .collect::<Vec<_>>();
+  assert_eq!(rows, [("1", "renamed"), ("3", "renamed")]);
+  let untouched = db.read_by_pk::<DbUser, _>("2").await.unwrap().unwrap();
+  assert_eq!(untouched.name, "b");
+  let updated = db
+    .update_by_returning::<DbUser, _>(&filter, changeset.clone())
+    .await
+    .unwrap();
+  assert!(updated.is_empty());
+  let res = db
+    .update_by_returning::<DbUser, _>(&GenericFilter::new(), changeset)
+    .await;
+  assert!(matches!(
+    res,
+    Err(TurboDieselError::Query(Error::QueryBuilderError(_)))
+  ));
+}
+
+#[ntex::test]
+async fn wal_and_busy_timeout_let_concurrent_writers_wait() {
+  use diesel::connection::SimpleConnection;
+
+  #[derive(QueryableByName)]
+  struct JournalMode {
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    journal_mode: String,
+  }
+
+  #[derive(QueryableByName)]
+  struct BusyTimeout {
+    #[diesel(sql_type = diesel::sql_types::BigInt)]
+    timeout: i64,
+  }
+
+  let name = "wal_and_busy_timeout_let_concurrent_writers_wait";
+  let plain = sqlite(name);
+  let path = std::env::temp_dir().join(format!("turbo_diesel_{name}.db"));
+  let path = path.to_str().unwrap().to_owned();
+  let config = PoolConfig {
+    max_size: Some(8),
+    sqlite: Some(SqliteConfig {
+      busy_timeout: Some(std::time::Duration::from_secs(5)),
+      wal: true,
+      synchronous: Some(SqliteSynchronous::Normal),
+    }),
+    ..Default::default()
+  };
+  let db =
+    DbDriver::<SqliteConnection>::new_with_config(&path, config).unwrap();
+  let mut conn = db.get_conn().unwrap();
+  let mode = diesel::sql_query("PRAGMA journal_mode")
+    .get_result::<JournalMode>(&mut *conn)
+    .unwrap();
+  assert_eq!(mode.journal_mode, "wal");
+  let busy = diesel::sql_query("PRAGMA busy_timeout")
+    .get_result::<BusyTimeout>(&mut *conn)
+    .unwrap();
+  assert_eq!(busy.timeout, 5000);
+  drop(conn);
+  // Another writer holds the lock for a while
+  let (locked, wait_locked) = std::sync::mpsc::channel();
+  let writer = std::thread::spawn({
+    let path = path.clone();
+    move || {
+      let mut conn = SqliteConnection::establish(&path).unwrap();
+      conn
+        .batch_execute("BEGIN IMMEDIATE; INSERT INTO users VALUES ('0', 'a')")
+        .unwrap();
+      locked.send(()).unwrap();
+      std::thread::sleep(std::time::Duration::from_millis(300));
+      conn.batch_execute("COMMIT").unwrap();
+    }
+  });
+  wait_locked.recv().unwrap();
+  // Without a busy_timeout the write fails at once
+  let res = plain.create(&user("plain", "a")).await;
+  assert!(matches!(res, Err(TurboDieselError::Query(_))), "{res:?}");
+  let users = (1..=8)
+    .map(|id| user(&id.to_string(), "concurrent"))
+    .collect::<Vec<_>>();
+  let creates = users.iter().map(|user| db.create(user));
+  for res in ntex::util::join_all(creates).await {
+    res.unwrap();
+  }
+  writer.join().unwrap();
+  assert_eq!(count_users(&db), 9);
+}
+
+#[ntex::test]
+async fn read_scalar_reads_a_single_column_value() {
+  use diesel::sql_types::{Integer, Nullable, Text};
+
+  let db = sqlite("read_scalar_reads_a_single_column_value");
+  db.create(&user("1", "a")).await.unwrap();
+  db.create(&user("2", "b")).await.unwrap();
+  let by_pk = GenericFilter::new().r#where("id", GenericClause::Eq("2".into()));
+  let name = db.read_scalar::<DbUser, String, Text>(&by_pk, "name").await;
+  assert_eq!(name.unwrap().as_deref(), Some("b"));
+  let missing =
+    GenericFilter::new().r#where("id", GenericClause::Eq("3".into()));
+  let name = db
+    .read_scalar::<DbUser, String, Text>(&missing, "name")
+    .await;
+  assert_eq!(name.unwrap(), None);
+  // The first row in the order of the filter
+  let last = GenericFilter::new().order_by("name", OrderDirection::Desc);
+  let id = db.read_scalar::<DbUser, String, Text>(&last, "id").await;
+  assert_eq!(id.unwrap().as_deref(), Some("2"));
+  db.create_many(items(), None).await.unwrap();
+  let by_pk = GenericFilter::new().r#where("id", GenericClause::Eq(2.into()));
+  let quantity = db.read_scalar::<DbItem, i32, Integer>(&by_pk, "quantity");
+  assert_eq!(quantity.await.unwrap(), Some(2));
+  let note = db
+    .read_scalar::<DbItem, Option<String>, Nullable<Text>>(&by_pk, "note")
+    .await;
+  assert_eq!(note.unwrap(), Some(None));
+  let res = db
+    .read_scalar::<DbUser, String, Text>(&missing, "unknown")
+    .await;
+  assert!(matches!(
+    res,
+    Err(TurboDieselError::Query(Error::QueryBuilderError(_)))
+  ));
+}
+
+#[ntex::test]
+async fn max_offset_clamps_the_offset_and_needs_a_limit() {
+  let db = sqlite("max_offset_clamps_the_offset_and_needs_a_limit");
+  for id in 1..=5 {
+    db.create(&user(&id.to_string(), "a")).await.unwrap();
+  }
+  let ids = |users: Vec<DbUser>| {
+    users.into_iter().map(|user| user.id).collect::<Vec<_>>()
+  };
+  let deep = GenericFilter::new()
+    .order_by("id", OrderDirection::Asc)
+    .limit(10)
+    .offset(100);
+  assert!(db.read_by::<DbUser>(&deep).await.unwrap().is_empty());
+  let path = std::env::temp_dir()
+    .join("turbo_diesel_max_offset_clamps_the_offset_and_needs_a_limit.db");
+  let config = PoolConfig {
+    max_offset: Some(2),
+    ..Default::default()
+  };
+  let db = DbDriver::<SqliteConnection>::new_with_config(
+    path.to_str().unwrap(),
+    config,
+  )
+  .unwrap();
+  assert_eq!(db.max_offset(), Some(2));
+  assert_eq!(
+    ids(db.read_by::<DbUser>(&deep).await.unwrap()),
+    ["3", "4", "5"]
+  );
+  let sql = db.explain_sql::<DbUser>(&deep).unwrap();
+  assert!(sql.ends_with("LIMIT ? OFFSET ? -- binds: [10, 2]"), "{sql}");
+  let page = db.paginate::<DbUser>(&deep).await.unwrap();
+  assert_eq!((page.offset, page.items.len()), (2, 3));
+  let first = db.read_one::<DbUser>(&deep).await.unwrap();
+  assert_eq!(first.map(|user| user.id).as_deref(), Some("3"));
+  let id =
+    db.read_scalar::<DbUser, String, diesel::sql_types::Text>(&deep, "id");
+  assert_eq!(id.await.unwrap().as_deref(), Some("3"));
+  // The default limit of the driver is a limit
+  let skipped = GenericFilter::new()
+    .order_by("id", OrderDirection::Asc)
+    .offset(1);
+  assert_eq!(db.read_by::<DbUser>(&skipped).await.unwrap().len(), 4);
+  let unlimited = db.clone().with_default_limit(None);
+  let res = unlimited.read_by::<DbUser>(&skipped).await;
+  assert!(
+    matches!(res, Err(TurboDieselError::InvalidFilter(_))),
+    "{res:?}"
+  );
+  let everything = GenericFilter::new().unlimited().offset(1);
+  for res in [
+    db.read_by::<DbUser>(&everything).await.map(|_| ()),
+    db.build_read_sql::<DbUser>(&everything).map(|_| ()),
+    db.explain_sql::<DbUser>(&everything).map(|_| ()),
+  ] {
+    assert!(
+      matches!(res, Err(TurboDieselError::InvalidFilter(_))),
+      "{res:?}"
+    );
+  }
+  // Without an offset nothing is checked
+  let every = GenericFilter::new().unlimited().offset(0);
+  assert_eq!(unlimited.read_by::<DbUser>(&every).await.unwrap().len(), 5);
+}
+
+#[ntex::test]
+async fn newtype_primary_keys_are_given_as_is() {
+  let db = sqlite("newtype_primary_keys_are_given_as_is");
+  db.create(&label("1", "bug")).await.unwrap();
+  let found = db
+    .read_by_pk::<DbLabel, _>(&LabelId("1".to_owned()))
+    .await
+    .unwrap()
+    .unwrap();
+  assert_eq!(
+    (found.id, found.name),
+    (LabelId("1".to_owned()), "bug".into())
+  );
+  let missing = LabelId("2".to_owned());
+  let res = db.read_by_pk_strict::<DbLabel, _>(&missing).await;
+  assert!(matches!(res, Err(TurboDieselError::NotFound)), "{res:?}");
+  let id = LabelId("1".to_owned());
+  assert_eq!(db.del_by_pk::<DbLabel, _>(&id).await.unwrap(), 1);
+  assert!(db.read_by_pk::<DbLabel, _>(&id).await.unwrap().is_none());
+}
+
+#[ntex::test]
+async fn execute_cancellable_returns_the_result_until_cancelled() {
+  let db = sqlite("execute_cancellable_returns_the_result_until_cancelled");
+  db.create(&user("1", "a")).await.unwrap();
+  let count = |conn: &mut _| users::table.count().get_result::<i64>(conn);
+  let res = db.execute_cancellable(std::future::pending(), count).await;
+  assert_eq!(res.unwrap(), 1);
+  // The other backends only stop waiting for the query
+  let slow_count = move |conn: &mut _| {
+    std::thread::sleep(std::time::Duration::from_millis(200));
+    count(conn)
+  };
+  let res = db
+    .execute_cancellable(std::future::ready(()), slow_count)
+    .await;
+  assert!(matches!(res, Err(TurboDieselError::Cancelled)), "{res:?}");
+}
@@ -0,0 +1,135 @@
+//! The spans and events of the `tracing` feature.
+#![cfg(all(feature = "tracing", feature = "runtime-ntex"))]
+
+mod common;
+
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+
+use common::*;
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Level, Metadata, Subscriber};
+use turbo_diesel::prelude::*;
+
+type Fields = Vec<(&'static str, String)>;
+
+/// The spans and events sent to the subscriber.
+#[derive(Default)]
+struct Records {
+  /// Name and fields of every span
+  spans: Vec<(&'static str, Fields)>,
+  /// Level and fields of every event
+  events: Vec<(Level, Fields)>,
+}
+
+/// A global subscriber keeping the records in memory,
+/// the events of the blocking threads only reach the global one.
+struct Recorder(Mutex<Records>);
+
+struct Visitor<'a>(&'a mut Fields);
+
+impl Visit for Visitor<'_> {
+  fn record_str(&mut self, field: &Field, value: &str) {
+    self.0.push((field.name(), value.to_owned()));
+  }
+
+  fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+    self.0.push((field.name(), format!("{value:?}")));
+  }
+}
+
+impl Subscriber for &'static Recorder {
+  fn enabled(&self, _: &Metadata<'_>) -> bool {
+    true
+  }
+
+  fn new_span(&self, span: &Attributes<'_>) -> Id {
+    let mut records = self.0.lock().unwrap();
+    let mut fields = Vec::new();
+    span.record(&mut Visitor(&mut fields));
+    records.spans.push((span.metadata().name(), fields));
+    Id::from_u64(records.spans.len() as u64)
+  }
+
+  fn record(&self, span: &Id, values: &Record<'_>) {
+    let mut records = self.0.lock().unwrap();
+    // The records taken by the test forget the spans
+    if let Some((_, fields)) =
+      records.spans.get_mut(span.into_u64() as usize - 1)
+    {
+      values.record(&mut Visitor(fields));
+    }
+  }
+
+  fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+  fn event(&self, event: &Event<'_>) {
+    let mut fields = Vec::new();
+    event.record(&mut Visitor(&mut fields));
+    let level = *event.metadata().level();
+    self.0.lock().unwrap().events.push((level, fields));
+  }
+
+  fn enter(&self, _: &Id) {}
+
+  fn exit(&self, _: &Id) {}
+}
+
+/// The global subscriber, set by the single test of the file
+/// as the spans and events of the tests would mix.
+fn recorder() -> &'static Recorder {
+  static RECORDER: OnceLock<Recorder> = OnceLock::new();
+  let recorder = RECORDER.get_or_init(|| Recorder(Mutex::default()));
+  tracing::subscriber::set_global_default(recorder).unwrap();
+  recorder
+}
+
+fn field(fields: &Fields, key: &str) -> Option<String> {
+  fields
+    .iter()
+    .find(|(name, _)| *name == key)
+    .map(|(_, value)| value.clone())
+}
+
+#[ntex::test]
+async fn queries_run_in_spans_and_warn_when_slow() {
+  let recorder = recorder();
+  let db = sqlite("queries_run_in_spans_and_warn_when_slow");
+  db.create(&user("1", "a")).await.unwrap();
+  db.read_by::<DbUser>(&GenericFilter::new()).await.unwrap();
+  db.del_all::<DbUser>(Confirm::Yes).await.unwrap();
+  let records = std::mem::take(&mut *recorder.0.lock().unwrap());
+  let operations = records
+    .spans
+    .iter()
+    .filter(|(name, _)| *name == "turbo_diesel.execute")
+    .map(|(_, fields)| {
+      let elapsed = field(fields, "elapsed_ms").expect("no elapsed_ms");
+      assert!(elapsed.parse::<f64>().unwrap() >= 0.0);
+      field(fields, "operation").unwrap()
+    })
+    .collect::<Vec<_>>();
+  assert_eq!(operations, ["create", "read_by", "del_all"]);
+  assert!(records
+    .events
+    .iter()
+    .all(|(level, _)| *level != Level::WARN));
+
+  let db = db.with_slow_query_threshold(Some(Duration::from_secs(60)));
+  db.create(&user("1", "a")).await.unwrap();
+  let db = db.with_slow_query_threshold(Some(Duration::ZERO));
+  db.create(&user("2", "b")).await.unwrap();
+  let db = db.with_slow_query_threshold(None);
+  db.create(&user("3", "c")).await.unwrap();
+  let records = std::mem::take(&mut *recorder.0.lock().unwrap());
+  let warnings = records
+    .events
+    .iter()
+    .filter(|(level, _)| *level == Level::WARN)
+    .collect::<Vec<_>>();
+  assert_eq!(warnings.len(), 1);
+  let (_, fields) = warnings[0];
+  assert_eq!(field(fields, "message").as_deref(), Some("slow query"));
+  assert!(field(fields, "elapsed").is_some());
+}